@@ -0,0 +1,202 @@
+use colored::*;
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::display::Display;
+use crate::error::{Result, TfocusError};
+use crate::project::{read_config, TerraformProject};
+
+/// Categories of issues reported by `tfocus audit`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Category {
+    CountAndForEach,
+    ModuleWithoutSource,
+    DuplicateAddress,
+    CommentedOutBlock,
+}
+
+impl Category {
+    fn title(&self) -> &'static str {
+        match self {
+            Category::CountAndForEach => "Both count and for_each",
+            Category::ModuleWithoutSource => "Module without source",
+            Category::DuplicateAddress => "Duplicate address",
+            Category::CommentedOutBlock => "Commented-out block",
+        }
+    }
+}
+
+/// A single audit finding with its `file:line` location
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub category: Category,
+    pub message: String,
+    pub location: String,
+}
+
+/// Runs every audit check over a parsed project
+pub fn audit(project: &TerraformProject) -> Result<Vec<Finding>> {
+    let resources = project.get_all_resources();
+    let mut findings = Vec::new();
+
+    for resource in &resources {
+        if resource.has_count && resource.has_for_each {
+            findings.push(Finding {
+                category: Category::CountAndForEach,
+                message: format!("{} sets both count and for_each", resource.full_name()),
+                location: resource.location(),
+            });
+        }
+        if resource.is_module && resource.source.is_none() {
+            findings.push(Finding {
+                category: Category::ModuleWithoutSource,
+                message: format!("{} has no source", resource.full_name()),
+                location: resource.location(),
+            });
+        }
+    }
+
+    // Addresses only clash within one directory (one Terraform module)
+    let mut by_address: BTreeMap<(PathBuf, String), Vec<String>> = BTreeMap::new();
+    for resource in &resources {
+        let dir = resource
+            .file_path
+            .parent()
+            .unwrap_or(Path::new(""))
+            .to_path_buf();
+        by_address
+            .entry((dir, resource.full_name()))
+            .or_default()
+            .push(resource.location());
+    }
+    for ((_, address), locations) in by_address {
+        if locations.len() > 1 {
+            findings.push(Finding {
+                category: Category::DuplicateAddress,
+                message: format!("{} is declared {} times", address, locations.len()),
+                location: locations.join(", "),
+            });
+        }
+    }
+
+    let commented_regex = Regex::new(
+        r#"(?m)^[ \t]*(?:#|//)\s*((?:resource|data)\s+"[^"]+"\s+"[^"]+"|module\s+"[^"]+")\s*\{"#,
+    )
+    .map_err(TfocusError::RegexError)?;
+    for file in project.get_parsed_files() {
+        let content = read_config(file)?;
+        for cap in commented_regex.captures_iter(&content) {
+            let line = content[..cap.get(0).unwrap().start()].matches('\n').count() + 1;
+            findings.push(Finding {
+                category: Category::CommentedOutBlock,
+                message: format!("commented-out {}", &cap[1]),
+                location: format!("{}:{}", file.display(), line),
+            });
+        }
+    }
+
+    findings.sort_by_key(|f| f.category);
+    Ok(findings)
+}
+
+/// Prints findings grouped by category
+pub fn print_report(findings: &[Finding]) {
+    if findings.is_empty() {
+        println!("{} No issues found", "Audit:".green().bold());
+        return;
+    }
+
+    let mut current = None;
+    for finding in findings {
+        if current != Some(finding.category) {
+            current = Some(finding.category);
+            let count = findings
+                .iter()
+                .filter(|f| f.category == finding.category)
+                .count();
+            println!(
+                "\n{} ({})",
+                Display::header_style(finding.category.title()),
+                count
+            );
+        }
+        println!("  - {} ({})", finding.message, finding.location.dimmed());
+    }
+    println!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::ParseOptions;
+
+    #[test]
+    fn test_audit_reports_each_category() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("main.tf"),
+            r#"
+resource "aws_instance" "web" {
+  count    = 2
+  for_each = toset(["a"])
+}
+
+module "orphan" {
+  version = "1.0.0"
+}
+
+module "vpc" {
+  source = "./vpc"
+}
+
+# resource "aws_s3_bucket" "old" {
+#   bucket = "old"
+# }
+"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("dup.tf"),
+            "resource \"aws_instance\" \"web\" {\n  ami = \"ami\"\n}\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("legacy.tf"),
+            "// module \"legacy\" {\n//   source = \"./legacy\"\n// }\n",
+        )
+        .unwrap();
+
+        let options = ParseOptions {
+            quiet: true,
+            ..ParseOptions::default()
+        };
+        std::fs::create_dir(dir.path().join("other")).unwrap();
+        std::fs::write(
+            dir.path().join("other/main.tf"),
+            "resource \"aws_instance\" \"web\" {\n  ami = \"ami\"\n}\n",
+        )
+        .unwrap();
+
+        let project = TerraformProject::parse_path(dir.path(), &options).unwrap();
+        let findings = audit(&project).unwrap();
+
+        let categories: Vec<Category> = findings.iter().map(|f| f.category).collect();
+        assert_eq!(
+            categories,
+            vec![
+                Category::CountAndForEach,
+                Category::ModuleWithoutSource,
+                Category::DuplicateAddress,
+                Category::CommentedOutBlock,
+                Category::CommentedOutBlock,
+            ]
+        );
+        assert_eq!(findings[1].message, "module.orphan has no source");
+        assert_eq!(findings[2].message, "aws_instance.web is declared 2 times");
+        assert!(findings[0].location.ends_with("main.tf:2"));
+        assert!(findings
+            .iter()
+            .any(|f| f.message == "commented-out module \"legacy\""));
+    }
+}
@@ -1,37 +1,803 @@
-use clap::{Parser, ValueEnum};
+use clap::{Parser, Subcommand, ValueEnum};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use regex::Regex;
 use std::path::PathBuf;
 
-#[derive(Parser)]
+use crate::error::{Result, TfocusError};
+use crate::project::ParseOptions;
+use crate::types::{Resource, ResourceKind, Target};
+
+#[derive(Parser, Clone)]
 #[command(author, version, about)]
 pub struct Cli {
-    /// The path to the Terraform directory
-    #[arg(short, long, default_value = ".")]
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// The path to the Terraform directory, or a single Terraform file
+    #[arg(short, long, default_value = ".", global = true)]
     pub path: PathBuf,
 
-    /// The operation to perform
-    #[arg(short, long)]
-    pub operation: Option<Operation>,
+    /// The operation to perform (plan-destroy previews a destroy), or a
+    /// comma-separated sequence such as "plan,apply"
+    /// (also read from TFOCUS_OPERATION)
+    #[arg(short, long, value_delimiter = ',')]
+    pub operation: Vec<Operation>,
+
+    /// Output format for errors
+    #[arg(long, value_enum, default_value_t = Format::Human)]
+    pub format: Format,
+
+    /// Format of tfocus's own log lines (enabled with RUST_LOG); json emits one
+    /// object per line with timestamp, level, target, message and fields
+    #[arg(long, value_enum, default_value_t = Format::Human)]
+    pub log_format: Format,
 
     /// Enable verbose output
     #[arg(short, long)]
     pub verbose: bool,
 
+    /// Suppress the discovered-file listing and parse progress
+    #[arg(short, long)]
+    pub quiet: bool,
+
     /// Non-interactive mode
     #[arg(short, long)]
     pub non_interactive: bool,
+
+    /// Target all resources in files matching a glob (e.g. "modules/*/main.tf")
+    #[arg(long, value_name = "PATTERN")]
+    pub file_glob: Option<String>,
+
+    /// Target every resource whose address starts with a prefix (e.g. "module.platform")
+    #[arg(long, value_name = "ADDRESS", conflicts_with = "file_glob")]
+    pub prefix: Option<String>,
+
+    /// Target the resources whose block starts within a line range of a file
+    /// (e.g. "main.tf:10-40", or "main.tf:12" for a single line)
+    #[arg(
+        long,
+        value_name = "FILE:START-END",
+        value_parser = parse_line_range,
+        conflicts_with_all = ["file_glob", "prefix"]
+    )]
+    pub in_file: Option<(PathBuf, usize, usize)>,
+
+    /// Select among the resource changes of a plan (`terraform show -json <planfile>` output)
+    /// instead of parsing the configuration
+    #[arg(long, value_name = "PLAN_JSON")]
+    pub from_plan: Option<PathBuf>,
+
+    /// Leave out resources whose address matches this glob, e.g. 'aws_db_instance.*'
+    /// (repeatable); applied to the selector and to expanded targets
+    #[arg(long, value_name = "PATTERN")]
+    pub exclude_target: Vec<String>,
+
+    /// Target a focus set saved from the selector with Ctrl+S
+    #[arg(long, value_name = "NAME", conflicts_with_all = ["file_glob", "prefix", "in_file"])]
+    pub set: Option<String>,
+
+    /// Target the first N resources, in the order `--list resources` prints them,
+    /// without the selector (e.g. for smoke tests in CI); modules count as resources
+    #[arg(
+        long,
+        value_name = "N",
+        conflicts_with_all = ["file_glob", "prefix", "in_file", "set"]
+    )]
+    pub select_first: Option<usize>,
+
+    /// Show resources inside modules as `type.name` in the selector; targets keep the full address
+    #[arg(long)]
+    pub short_names: bool,
+
+    /// Print selected outputs as JSON (terraform output -json)
+    #[arg(long)]
+    pub output_json: bool,
+
+    /// Only offer these kinds in the selector (repeatable; kinds are combined)
+    #[arg(long, value_enum, value_name = "KIND")]
+    pub only: Vec<OnlyKind>,
+
+    /// Print the selection as one line of shell-quoted -target flags instead of running terraform
+    #[arg(long, conflicts_with_all = ["list", "count", "graph"])]
+    pub print_target_args: bool,
+
+    /// Print a Makefile target per operation (plan by default) running the
+    /// assembled terraform command for the selection, instead of running it
+    #[arg(long, conflicts_with_all = ["list", "count", "graph", "print_target_args"])]
+    pub emit_makefile: bool,
+
+    /// Only offer resources and modules that use count or for_each
+    #[arg(long, conflicts_with = "only_static")]
+    pub only_dynamic: bool,
+
+    /// Only offer resources and modules that use neither count nor for_each
+    #[arg(long)]
+    pub only_static: bool,
+
+    /// Only offer resources managed by this provider (repeatable), e.g. `aws`;
+    /// inferred from the type prefix unless the block sets `provider`
+    #[arg(long, value_name = "NAME")]
+    pub provider: Vec<String>,
+
+    /// Print a tab-separated list instead of launching the selector
+    #[arg(long, value_name = "KIND")]
+    pub list: Option<ListKind>,
+
+    /// Indent the --list json inventory for reading
+    #[arg(long, requires = "list")]
+    pub json_pretty: bool,
+
+    /// Omit the header line in --list output
+    #[arg(long, requires = "list")]
+    pub no_header: bool,
+
+    /// Print how many resources match (with --only and --filter) and exit
+    #[arg(long, conflicts_with = "list")]
+    pub count: bool,
+
+    /// Print the resources and their depends_on edges as a graph (with --only and --filter)
+    #[arg(long, value_name = "FORMAT", conflicts_with_all = ["list", "count"])]
+    pub graph: Option<GraphFormat>,
+
+    /// Only list, count or graph resources whose address matches this regular expression
+    #[arg(long, value_name = "REGEX")]
+    pub filter: Option<String>,
+
+    /// Disable colored output (also passes -no-color to terraform)
+    #[arg(long)]
+    pub no_color: bool,
+
+    /// Pass -compact-warnings to terraform
+    #[arg(long)]
+    pub compact_warnings: bool,
+
+    /// Pass -var-file=FILE to terraform plan, apply and destroy; may be repeated
+    #[arg(long, value_name = "FILE")]
+    pub var_file: Vec<PathBuf>,
+
+    /// Always pass -auto-approve to apply (overrides config)
+    #[arg(long, conflicts_with = "no_auto_approve")]
+    pub auto_approve: bool,
+
+    /// Never pass -auto-approve, so terraform asks for confirmation (overrides config)
+    #[arg(long)]
+    pub no_auto_approve: bool,
+
+    /// Pass an extra argument to terraform plan only, e.g. --plan-arg=-refresh=false
+    /// (repeatable; adds to `extra_args.plan` in the config)
+    #[arg(long, value_name = "ARG", allow_hyphen_values = true)]
+    pub plan_arg: Vec<String>,
+
+    /// Pass an extra argument to terraform apply only (repeatable; adds to `extra_args.apply`)
+    #[arg(long, value_name = "ARG", allow_hyphen_values = true)]
+    pub apply_arg: Vec<String>,
+
+    /// Pass an extra argument to terraform destroy only (repeatable; adds to `extra_args.destroy`)
+    #[arg(long, value_name = "ARG", allow_hyphen_values = true)]
+    pub destroy_arg: Vec<String>,
+
+    /// Set an environment variable for terraform (KEY=VALUE, repeatable)
+    #[arg(long = "env", value_name = "KEY=VALUE", value_parser = parse_env_var)]
+    pub env: Vec<(String, String)>,
+
+    /// Set AWS_PROFILE for terraform
+    #[arg(long, value_name = "NAME")]
+    pub profile: Option<String>,
+
+    /// Run terraform once per target, in the order the targets were selected
+    #[arg(long)]
+    pub per_target: bool,
+
+    /// Run terraform once per batch of at most N targets, for selections too
+    /// large for one command line (batches are not atomic)
+    #[arg(long, value_name = "N", conflicts_with = "per_target")]
+    pub batch_size: Option<std::num::NonZeroUsize>,
+
+    /// Reverse the order of the -target flags (dependents before dependencies)
+    #[arg(long)]
+    pub reverse: bool,
+
+    /// OpenTofu mode: also discover .tofu, .tofu.json and .tf.json files
+    #[arg(long)]
+    pub opentofu: bool,
+
+    /// Inspect the state and mark tainted resources in the selector
+    #[arg(long)]
+    pub check_tainted: bool,
+
+    /// Pass -detailed-exitcode to plan; exit code 2 (changes present) is passed through
+    #[arg(long)]
+    pub detailed_exitcode: bool,
+
+    /// With --detailed-exitcode, treat exit code 2 from plan as success
+    #[arg(long, requires = "detailed_exitcode")]
+    pub changes_ok: bool,
+
+    /// File of resource addresses, one per line, targeted in addition to the
+    /// selection (e.g. a known base set combined with ad-hoc picks)
+    #[arg(long, value_name = "FILE")]
+    pub also_target_file: Option<PathBuf>,
+
+    /// File of for_each keys, one per line; selected for_each resources are
+    /// targeted once per key (e.g. `aws_instance.web["blue"]`)
+    #[arg(long, value_name = "FILE")]
+    pub keys: Option<PathBuf>,
+
+    /// Policy file of `allow <pattern>` / `deny <pattern>` rules for target addresses
+    #[arg(long, value_name = "FILE")]
+    pub policy: Option<PathBuf>,
+
+    /// Do not scan git submodules listed in .gitmodules
+    #[arg(long)]
+    pub skip_submodules: bool,
+
+    /// List recently modified files, and their resources, first
+    #[arg(long)]
+    pub recent_first: bool,
+
+    /// Leave files without modules, resources or data sources out of the
+    /// discovered-file listing (they are still parsed)
+    #[arg(long)]
+    pub hide_empty_files: bool,
+
+    /// Choose a root module first, then resources within it
+    #[arg(long)]
+    pub root_picker: bool,
+
+    /// Run terraform validate before the operation and stop if it fails
+    #[arg(long)]
+    pub validate: bool,
+
+    /// Warn about targets that are not in the state (from `terraform state list`)
+    /// before running the operation
+    #[arg(long)]
+    pub validate_targets: bool,
+
+    /// Plan the import blocks that have no configuration yet, writing generated
+    /// configuration to FILE (terraform plan -generate-config-out)
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["list", "count", "graph"])]
+    pub generate_config_out: Option<PathBuf>,
+
+    /// Fail instead of warning when terraform does not satisfy required_version
+    /// or differs from the version pinned in .terraform-version
+    #[arg(long)]
+    pub strict_version: bool,
+
+    /// Before running, print the terraform version, the configured backend and
+    /// the selected workspace (suppressed by --quiet)
+    #[arg(long)]
+    pub context: bool,
+
+    /// Mark resources that are new or in files changed since the last run with this
+    /// flag, and list removed ones; the inventory is kept in .tfocus/inventory.json
+    #[arg(long)]
+    pub since_last_run: bool,
+
+    /// Apply even when Terraform files have uncommitted changes in git
+    #[arg(long)]
+    pub allow_dirty: bool,
+
+    /// Ask for confirmation before targeting more than N resources; 0 removes the cap
+    /// (overrides `safety.max_targets` in the config)
+    #[arg(long, value_name = "N")]
+    pub max_targets: Option<usize>,
+
+    /// Warn when parsing finds more than N resources, a sign of a too-broad scan root;
+    /// 0 turns the warning off (overrides `safety.resource_warning` in the config)
+    #[arg(long, value_name = "N")]
+    pub resource_warning: Option<usize>,
+
+    /// Print local timing metrics (parse, selection, terraform, total) after the run
+    #[arg(long)]
+    pub metrics: bool,
+
+    /// Run this shell command in the project directory after an operation succeeds
+    /// (TFOCUS_HOOK_OPERATION, TFOCUS_HOOK_TARGET_COUNT and TFOCUS_HOOK_STATUS are set)
+    #[arg(long, value_name = "CMD")]
+    pub after_success: Option<String>,
+
+    /// Run this shell command in the project directory after an operation fails
+    #[arg(long, value_name = "CMD")]
+    pub after_failure: Option<String>,
+
+    /// Hide terraform's progress lines such as "Refreshing state..."; errors and the
+    /// plan summary are always shown (patterns from `condense.patterns` in the config)
+    #[arg(long)]
+    pub condense: bool,
+
+    /// Open each assembled terraform command in $EDITOR before it runs, and run
+    /// the edited line instead
+    #[arg(long)]
+    pub edit_command: bool,
+
+    /// Do not record executed commands in the history log
+    #[arg(long)]
+    pub no_history: bool,
+
+    /// Switch to this terraform workspace before running; without a name,
+    /// pick one from the workspace list
+    #[arg(long, value_name = "NAME", num_args = 0..=1)]
+    pub workspace: Option<Option<String>>,
+
+    /// Print the terraform workspaces, marking the current one, and exit
+    #[arg(long, conflicts_with = "workspace")]
+    pub list_workspaces: bool,
 }
 
-#[derive(Debug, Clone, Copy, ValueEnum)]
+impl Cli {
+    /// Returns the target given on the command line in place of the selector
+    pub fn direct_target(&self) -> Option<Target> {
+        self.file_glob
+            .clone()
+            .map(Target::FileGlob)
+            .or_else(|| self.prefix.clone().map(Target::Prefix))
+            .or_else(|| {
+                self.in_file
+                    .clone()
+                    .map(|(path, start, end)| Target::LineRange(path, start, end))
+            })
+    }
+
+    /// Returns whether the targets come from the command line rather than the selector
+    pub fn has_direct_targets(&self) -> bool {
+        self.direct_target().is_some() || self.set.is_some() || self.select_first.is_some()
+    }
+
+    /// Returns the compiled `--filter` expression, if any
+    pub fn filter_regex(&self) -> Result<Option<Regex>> {
+        self.filter
+            .as_deref()
+            .map(Regex::new)
+            .transpose()
+            .map_err(TfocusError::RegexError)
+    }
+
+    /// Returns the compiled `--exclude-target` globs, if any
+    ///
+    /// `*` also matches `.`, so `module.vpc*` covers everything in the module.
+    pub fn exclude_targets(&self) -> Result<Option<GlobSet>> {
+        if self.exclude_target.is_empty() {
+            return Ok(None);
+        }
+        let mut builder = GlobSetBuilder::new();
+        for pattern in &self.exclude_target {
+            builder.add(Glob::new(pattern)?);
+        }
+        Ok(Some(builder.build()?))
+    }
+
+    /// Returns the options for discovering and parsing the project
+    pub fn parse_options(&self) -> ParseOptions {
+        ParseOptions {
+            quiet: self.quiet
+                || self.list.is_some()
+                || self.count
+                || self.graph.is_some()
+                || self.print_target_args
+                || self.emit_makefile
+                || self.command.is_some(),
+            opentofu: self.opentofu,
+            skip_submodules: self.skip_submodules,
+            recent_first: self.recent_first,
+            hide_empty_files: self.hide_empty_files,
+        }
+    }
+
+    /// Returns whether colored output is disabled via `--no-color` or `NO_COLOR`
+    pub fn color_disabled(&self) -> bool {
+        self.no_color || std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty())
+    }
+
+    /// Returns the operations chosen without prompting, in the order to run them
+    ///
+    /// `--operation` takes precedence over the `TFOCUS_OPERATION` environment variable.
+    /// Both accept a comma-separated sequence; destroy cannot be part of a sequence.
+    pub fn preset_operations(&self) -> Result<Vec<Operation>> {
+        self.resolve_operations(std::env::var("TFOCUS_OPERATION").ok())
+    }
+
+    /// Resolves the preset operations against the value of `TFOCUS_OPERATION`, if set
+    fn resolve_operations(&self, env_operation: Option<String>) -> Result<Vec<Operation>> {
+        let operations = if !self.operation.is_empty() {
+            self.operation.clone()
+        } else {
+            match env_operation {
+                Some(value) if !value.trim().is_empty() => value
+                    .split(',')
+                    .map(|op| {
+                        Operation::from_str(op.trim(), true)
+                            .map_err(|_| TfocusError::InvalidOperation(value.clone()))
+                    })
+                    .collect::<Result<Vec<_>>>()?,
+                _ => Vec::new(),
+            }
+        };
+
+        if operations.len() > 1 && operations.contains(&Operation::Destroy) {
+            return Err(TfocusError::InvalidOperation(
+                "destroy cannot be chained with other operations".to_string(),
+            ));
+        }
+        Ok(operations)
+    }
+
+    /// Returns how many targets to pass to one terraform command
+    pub fn batch_size(&self) -> usize {
+        match self.batch_size {
+            _ if self.per_target => 1,
+            Some(size) => size.get(),
+            None => usize::MAX,
+        }
+    }
+
+    /// Returns the kinds selected with `--only`; empty means all kinds
+    pub fn only_kinds(&self) -> Vec<ResourceKind> {
+        self.only.iter().map(|&kind| kind.into()).collect()
+    }
+
+    /// Returns whether the resource passes `--only-dynamic` / `--only-static`
+    pub fn matches_dynamism(&self, resource: &Resource) -> bool {
+        if self.only_dynamic {
+            resource.is_dynamic()
+        } else if self.only_static {
+            !resource.is_dynamic()
+        } else {
+            true
+        }
+    }
+
+    /// Returns whether the resource passes `--provider`
+    pub fn matches_provider(&self, resource: &Resource) -> bool {
+        self.provider.is_empty()
+            || resource
+                .provider_name()
+                .is_some_and(|name| self.provider.iter().any(|p| p == name))
+    }
+
+    /// Returns the environment variables to set on the terraform process
+    pub fn env_overrides(&self) -> Vec<(String, String)> {
+        let mut vars = self.env.clone();
+        if let Some(profile) = &self.profile {
+            vars.push(("AWS_PROFILE".to_string(), profile.clone()));
+        }
+        vars
+    }
+
+    /// Returns the auto-approve choice given on the command line, if any
+    pub fn auto_approve_override(&self) -> Option<bool> {
+        if self.auto_approve {
+            Some(true)
+        } else if self.no_auto_approve {
+            Some(false)
+        } else {
+            None
+        }
+    }
+}
+
+/// Parses a `KEY=VALUE` environment variable argument
+fn parse_env_var(s: &str) -> std::result::Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(format!("expected KEY=VALUE, got '{}'", s)),
+    }
+}
+
+/// Parses a `FILE:START-END` (or `FILE:LINE`) line range argument
+fn parse_line_range(s: &str) -> std::result::Result<(PathBuf, usize, usize), String> {
+    let invalid = || format!("expected FILE:START-END, got '{}'", s);
+    let (file, range) = s.rsplit_once(':').ok_or_else(invalid)?;
+    if file.is_empty() {
+        return Err(invalid());
+    }
+    let (start, end) = range.split_once('-').unwrap_or((range, range));
+    let (Ok(start), Ok(end)) = (start.trim().parse::<usize>(), end.trim().parse::<usize>()) else {
+        return Err(invalid());
+    };
+    if start == 0 || start > end {
+        return Err(format!(
+            "invalid line range '{}': lines start at 1 and the start must not exceed the end",
+            range
+        ));
+    }
+    Ok((PathBuf::from(file), start, end))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    Human,
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OnlyKind {
+    Modules,
+    Resources,
+    Data,
+}
+
+impl From<OnlyKind> for ResourceKind {
+    fn from(kind: OnlyKind) -> Self {
+        match kind {
+            OnlyKind::Modules => ResourceKind::Module,
+            OnlyKind::Resources => ResourceKind::Resource,
+            OnlyKind::Data => ResourceKind::Data,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Subcommand)]
+pub enum Command {
+    /// Report likely mistakes in the configuration without running terraform
+    Audit,
+    /// Print state addresses that no longer match a block in the configuration
+    Orphans,
+    /// Print the providers required by the configuration and the local modules it calls,
+    /// with their version constraints merged
+    Providers,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum GraphFormat {
+    Dot,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ListKind {
+    Resources,
+    Files,
+    /// A JSON array of the resources with their kind, file and line
+    Json,
+    /// Sorted resource addresses as `terraform state list` prints them
+    Addresses,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
 pub enum Operation {
     Plan,
+    /// `plan -destroy`: preview a destroy without changing anything
+    PlanDestroy,
     Apply,
+    Destroy,
+}
+
+impl Operation {
+    /// Returns whether this operation runs `terraform plan`
+    pub fn is_plan(&self) -> bool {
+        matches!(self, Operation::Plan | Operation::PlanDestroy)
+    }
 }
 
 impl std::fmt::Display for Operation {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Operation::Plan => write!(f, "plan"),
+            Operation::PlanDestroy => write!(f, "plan-destroy"),
             Operation::Apply => write!(f, "apply"),
+            Operation::Destroy => write!(f, "destroy"),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preset_operation_precedence() {
+        let env = |value: &str| Some(value.to_string());
+        let cli = Cli::parse_from(["tfocus"]);
+        assert_eq!(
+            cli.resolve_operations(env("destroy")).unwrap(),
+            vec![Operation::Destroy]
+        );
+        assert!(matches!(
+            cli.resolve_operations(env("deploy")),
+            Err(TfocusError::InvalidOperation(_))
+        ));
+        assert_eq!(
+            cli.resolve_operations(env("plan, apply")).unwrap(),
+            vec![Operation::Plan, Operation::Apply]
+        );
+        assert_eq!(cli.resolve_operations(env(" ")).unwrap(), vec![]);
+        assert_eq!(cli.resolve_operations(None).unwrap(), vec![]);
+
+        let cli = Cli::parse_from(["tfocus", "--operation", "plan"]);
+        assert_eq!(
+            cli.resolve_operations(env("destroy")).unwrap(),
+            vec![Operation::Plan]
+        );
+    }
+
+    #[test]
+    fn test_operation_sequence() {
+        let cli = Cli::parse_from(["tfocus", "--operation", "plan,apply"]);
+        assert_eq!(
+            cli.preset_operations().unwrap(),
+            vec![Operation::Plan, Operation::Apply]
+        );
+
+        let cli = Cli::parse_from(["tfocus", "-o", "plan-destroy"]);
+        assert_eq!(
+            cli.preset_operations().unwrap(),
+            vec![Operation::PlanDestroy]
+        );
+
+        let cli = Cli::parse_from(["tfocus", "-o", "plan,destroy"]);
+        assert!(matches!(
+            cli.preset_operations(),
+            Err(TfocusError::InvalidOperation(_))
+        ));
+    }
+
+    #[test]
+    fn test_env_overrides() {
+        let cli = Cli::parse_from([
+            "tfocus",
+            "--env",
+            "GOOGLE_CREDENTIALS=/tmp/creds.json",
+            "--env",
+            "TF_LOG=DEBUG",
+            "--profile",
+            "staging",
+        ]);
+        assert_eq!(
+            cli.env_overrides(),
+            vec![
+                (
+                    "GOOGLE_CREDENTIALS".to_string(),
+                    "/tmp/creds.json".to_string()
+                ),
+                ("TF_LOG".to_string(), "DEBUG".to_string()),
+                ("AWS_PROFILE".to_string(), "staging".to_string()),
+            ]
+        );
+
+        assert!(Cli::try_parse_from(["tfocus", "--env", "NOVALUE"]).is_err());
+    }
+
+    #[test]
+    fn test_workspace_name_is_optional() {
+        let cli = Cli::parse_from(["tfocus", "--workspace", "staging"]);
+        assert_eq!(cli.workspace, Some(Some("staging".to_string())));
+
+        let cli = Cli::parse_from(["tfocus", "--workspace"]);
+        assert_eq!(cli.workspace, Some(None));
+
+        let cli = Cli::parse_from(["tfocus"]);
+        assert_eq!(cli.workspace, None);
+    }
+
+    #[test]
+    fn test_matches_dynamism() {
+        let resource = Resource {
+            has_for_each: true,
+            ..Resource::for_test("aws_instance", "web")
+        };
+        let fixed = Resource {
+            has_for_each: false,
+            ..resource.clone()
+        };
+
+        let cli = Cli::parse_from(["tfocus", "--only-dynamic"]);
+        assert!(cli.matches_dynamism(&resource));
+        assert!(!cli.matches_dynamism(&fixed));
+
+        let cli = Cli::parse_from(["tfocus", "--only-static"]);
+        assert!(!cli.matches_dynamism(&resource));
+        assert!(cli.matches_dynamism(&fixed));
+
+        let cli = Cli::parse_from(["tfocus"]);
+        assert!(cli.matches_dynamism(&resource) && cli.matches_dynamism(&fixed));
+
+        assert!(Cli::try_parse_from(["tfocus", "--only-dynamic", "--only-static"]).is_err());
+    }
+
+    #[test]
+    fn test_matches_provider() {
+        let resource = Resource::for_test("aws_instance", "web");
+        let beta = Resource {
+            resource_type: "google_compute_instance".to_string(),
+            provider: Some("google-beta.west".to_string()),
+            ..resource.clone()
+        };
+        let module = Resource {
+            is_module: true,
+            ..resource.clone()
+        };
+
+        let cli = Cli::parse_from(["tfocus", "--provider", "aws"]);
+        assert!(cli.matches_provider(&resource));
+        assert!(!cli.matches_provider(&beta));
+        assert!(!cli.matches_provider(&module));
+
+        let cli = Cli::parse_from(["tfocus", "--provider", "aws", "--provider", "google-beta"]);
+        assert!(cli.matches_provider(&resource) && cli.matches_provider(&beta));
+
+        let cli = Cli::parse_from(["tfocus"]);
+        assert!(cli.matches_provider(&module));
+    }
+
+    #[test]
+    fn test_exclude_targets_after_include() {
+        use crate::project::TerraformProject;
+
+        let resource = |resource_type: &str, name: &str, index: Option<&str>| Resource {
+            is_module: false,
+            has_count: index.is_some(),
+            index: index.map(str::to_string),
+            ..Resource::for_test(resource_type, name)
+        };
+        let project = TerraformProject::from_resources(vec![
+            resource("aws_instance", "web", Some("0")),
+            resource("aws_instance", "web", Some("1")),
+            resource("aws_db_instance", "main", None),
+            resource("aws_db_instance", "replica", None),
+            resource("google_sql_database", "main", None),
+        ]);
+        let targets = |cli: &Cli| -> Vec<String> {
+            let exclude = cli.exclude_targets().unwrap().unwrap();
+            project
+                .get_resources_by_target(&cli.direct_target().unwrap())
+                .unwrap()
+                .into_iter()
+                .filter(|r| !r.is_excluded(&exclude))
+                .map(|r| r.target_string())
+                .collect()
+        };
+
+        let cli = Cli::parse_from([
+            "tfocus",
+            "--prefix",
+            "aws_",
+            "--exclude-target",
+            "aws_db_instance.*",
+        ]);
+        assert_eq!(
+            targets(&cli),
+            vec!["aws_instance.web[0]", "aws_instance.web[1]"]
+        );
+
+        // A block address excludes all of its instances; patterns combine
+        let cli = Cli::parse_from([
+            "tfocus",
+            "--prefix",
+            "aws_",
+            "--exclude-target",
+            "aws_instance.web",
+            "--exclude-target",
+            "*.replica",
+        ]);
+        assert_eq!(targets(&cli), vec!["aws_db_instance.main"]);
+
+        assert!(Cli::parse_from(["tfocus"])
+            .exclude_targets()
+            .unwrap()
+            .is_none());
+        assert!(Cli::parse_from(["tfocus", "--exclude-target", "a[b"])
+            .exclude_targets()
+            .is_err());
+    }
+
+    #[test]
+    fn test_parse_line_range() {
+        assert_eq!(
+            parse_line_range("main.tf:10-40").unwrap(),
+            (PathBuf::from("main.tf"), 10, 40)
+        );
+        assert_eq!(
+            parse_line_range("C:/infra/main.tf:12").unwrap(),
+            (PathBuf::from("C:/infra/main.tf"), 12, 12)
+        );
+        assert!(parse_line_range("main.tf").is_err());
+        assert!(parse_line_range("main.tf:40-10").is_err());
+        assert!(parse_line_range("main.tf:0-3").is_err());
+        assert!(parse_line_range(":1-3").is_err());
+
+        let cli = Cli::parse_from(["tfocus", "--in-file", "main.tf:1-5"]);
+        assert_eq!(
+            cli.direct_target(),
+            Some(Target::LineRange(PathBuf::from("main.tf"), 1, 5))
+        );
+    }
+}
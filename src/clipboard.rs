@@ -0,0 +1,45 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use crate::error::{Result, TfocusError};
+
+/// Clipboard commands to try, in order, with their arguments
+#[cfg(target_os = "macos")]
+const COMMANDS: &[(&str, &[&str])] = &[("pbcopy", &[])];
+#[cfg(windows)]
+const COMMANDS: &[(&str, &[&str])] = &[("clip", &[])];
+#[cfg(not(any(target_os = "macos", windows)))]
+const COMMANDS: &[(&str, &[&str])] = &[
+    ("wl-copy", &[]),
+    ("xclip", &["-selection", "clipboard"]),
+    ("xsel", &["--clipboard", "--input"]),
+];
+
+/// Copies `text` to the system clipboard with the platform's copy command
+///
+/// The first command that is installed and succeeds wins.
+pub fn copy(text: &str) -> Result<()> {
+    for (program, args) in COMMANDS {
+        let Ok(mut child) = Command::new(program)
+            .args(*args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        else {
+            continue;
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes())?;
+        }
+        if child.wait()?.success() {
+            return Ok(());
+        }
+    }
+
+    let tried: Vec<&str> = COMMANDS.iter().map(|(program, _)| *program).collect();
+    Err(TfocusError::CommandExecutionError(format!(
+        "no clipboard command worked (tried {})",
+        tried.join(", ")
+    )))
+}
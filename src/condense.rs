@@ -0,0 +1,104 @@
+use regex::{Regex, RegexSet};
+
+use crate::cli::Cli;
+use crate::config::Config;
+use crate::error::{Result, TfocusError};
+
+/// Progress lines hidden by `--condense`, unless `condense.patterns` replaces them
+pub const DEFAULT_PATTERNS: &[&str] = &[
+    r": Refreshing state\.\.\.",
+    r": Reading\.\.\.",
+    r": Read complete after ",
+    r": Still [a-z]+\.\.\. \[",
+];
+
+/// Lines that are never hidden: diagnostics and the plan or apply summary
+const KEEP_PATTERN: &str = r"^\s*[│╷╵]|^\s*(Error|Warning):|Plan:|No changes\.|(Apply|Destroy) complete!|Changes to Outputs:";
+
+/// Filters noise out of terraform's output for `--condense`
+pub struct Condenser {
+    noise: RegexSet,
+    keep: Regex,
+    color: Regex,
+    hidden: usize,
+}
+
+impl Condenser {
+    /// Returns a condenser with `--condense`, or `None` to pass output through
+    pub fn from_cli(cli: &Cli, config: &Config) -> Result<Option<Condenser>> {
+        if !cli.condense {
+            return Ok(None);
+        }
+        let patterns = config.get_list("condense.patterns")?.unwrap_or_else(|| {
+            DEFAULT_PATTERNS
+                .iter()
+                .map(|pattern| pattern.to_string())
+                .collect()
+        });
+        Condenser::new(&patterns).map(Some)
+    }
+
+    fn new(patterns: &[String]) -> Result<Condenser> {
+        let noise = RegexSet::new(patterns)
+            .map_err(|e| TfocusError::ConfigError(format!("condense.patterns: {}", e)))?;
+        Ok(Condenser {
+            noise,
+            keep: Regex::new(KEEP_PATTERN)?,
+            color: Regex::new(r"\x1b\[[0-9;]*m")?,
+            hidden: 0,
+        })
+    }
+
+    /// Returns whether to print `line`, counting the lines hidden
+    ///
+    /// Color codes are ignored when matching.
+    pub fn keep(&mut self, line: &str) -> bool {
+        let plain = self.color.replace_all(line, "");
+        if self.keep.is_match(&plain) || !self.noise.is_match(&plain) {
+            return true;
+        }
+        self.hidden += 1;
+        false
+    }
+
+    /// Returns the number of lines hidden since the last call
+    pub fn take_hidden(&mut self) -> usize {
+        std::mem::take(&mut self.hidden)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_condense_hides_progress_and_keeps_summary() {
+        let patterns: Vec<String> = DEFAULT_PATTERNS.iter().map(|p| p.to_string()).collect();
+        let mut condenser = Condenser::new(&patterns).unwrap();
+        let lines = [
+            "aws_instance.web: Refreshing state... [id=i-0123]",
+            "\x1b[0m\x1b[1mdata.aws_ami.ubuntu: Reading...\x1b[0m",
+            "data.aws_ami.ubuntu: Read complete after 1s [id=ami-1]",
+            "aws_instance.web: Still creating... [10s elapsed]",
+            "  # aws_instance.web will be created",
+            "\x1b[1mPlan:\x1b[0m 1 to add, 0 to change, 0 to destroy.",
+            "│ Error: aws_instance.web: Refreshing state... failed",
+        ];
+        let kept: Vec<&str> = lines
+            .into_iter()
+            .filter(|line| condenser.keep(line))
+            .collect();
+        assert_eq!(
+            kept,
+            vec![
+                "  # aws_instance.web will be created",
+                "\x1b[1mPlan:\x1b[0m 1 to add, 0 to change, 0 to destroy.",
+                "│ Error: aws_instance.web: Refreshing state... failed",
+            ]
+        );
+        assert_eq!(condenser.take_hidden(), 4);
+        assert_eq!(condenser.take_hidden(), 0);
+
+        assert!(Condenser::new(&["(".to_string()]).is_err());
+    }
+}
@@ -0,0 +1,354 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::{Result, TfocusError};
+
+/// Name of the project-local configuration file
+pub const CONFIG_FILE_NAME: &str = ".tfocus.toml";
+
+/// Search aliases available without configuration; `[aliases]` entries override these
+const DEFAULT_ALIASES: &[(&str, &str)] = &[
+    ("ec2", "aws_instance"),
+    ("s3", "aws_s3_bucket"),
+    ("rds", "aws_db_instance"),
+    ("sg", "aws_security_group"),
+    ("lambda", "aws_lambda_function"),
+    ("gce", "google_compute_instance"),
+    ("gcs", "google_storage_bucket"),
+    ("gke", "google_container_cluster"),
+];
+
+/// Target flag format used when `target_flag` is not configured
+const DEFAULT_TARGET_FLAG: &str = "-target={}";
+
+/// tfocus configuration loaded from a small TOML subset
+///
+/// Supports `[section]` headers, `key = value` pairs, and `#` comments.
+/// Keys inside a section are stored as `section.key`.
+#[derive(Debug, Default, Clone)]
+pub struct Config {
+    values: HashMap<String, String>,
+}
+
+impl Config {
+    /// Loads the configuration for a project path
+    ///
+    /// `TFOCUS_CONFIG` takes precedence; otherwise `.tfocus.toml` is read from
+    /// the project directory. A missing file yields an empty configuration.
+    pub fn load(project_path: &Path) -> Result<Self> {
+        let path = match std::env::var_os("TFOCUS_CONFIG") {
+            Some(path) => PathBuf::from(path),
+            None => {
+                let dir = if project_path.is_file() {
+                    project_path.parent().unwrap_or(Path::new("."))
+                } else {
+                    project_path
+                };
+                dir.join(CONFIG_FILE_NAME)
+            }
+        };
+
+        if !path.is_file() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(&path)?;
+        Self::parse(&content)
+            .map_err(|e| TfocusError::ConfigError(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Parses configuration content
+    pub fn parse(content: &str) -> std::result::Result<Self, String> {
+        let mut values = HashMap::new();
+        let mut section = String::new();
+
+        for (i, raw) in content.lines().enumerate() {
+            let line = strip_comment(raw).trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                section = name.trim().to_string();
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(format!("line {}: expected `key = value`", i + 1));
+            };
+            let key = key.trim();
+            if key.is_empty() {
+                return Err(format!("line {}: missing key", i + 1));
+            }
+
+            let full_key = if section.is_empty() {
+                key.to_string()
+            } else {
+                format!("{}.{}", section, key)
+            };
+            values.insert(full_key, value.trim().to_string());
+        }
+
+        Ok(Self { values })
+    }
+
+    /// Returns a boolean value, if set
+    pub fn get_bool(&self, key: &str) -> Result<Option<bool>> {
+        match self.values.get(key).map(String::as_str) {
+            None => Ok(None),
+            Some("true") => Ok(Some(true)),
+            Some("false") => Ok(Some(false)),
+            Some(other) => Err(TfocusError::ConfigError(format!(
+                "{}: expected true or false, got {}",
+                key, other
+            ))),
+        }
+    }
+
+    /// Returns a non-negative integer value, if set
+    pub fn get_usize(&self, key: &str) -> Result<Option<usize>> {
+        self.values
+            .get(key)
+            .map(|value| {
+                value.parse().map_err(|_| {
+                    TfocusError::ConfigError(format!(
+                        "{}: expected a non-negative integer, got {}",
+                        key, value
+                    ))
+                })
+            })
+            .transpose()
+    }
+
+    /// Returns a string value, if set, without surrounding quotes
+    pub fn get_string(&self, key: &str) -> Option<String> {
+        self.values.get(key).map(|value| unquote(value).to_string())
+    }
+
+    /// Returns a list of strings, if set, e.g. `["-refresh=false", "-lock=false"]`
+    pub fn get_list(&self, key: &str) -> Result<Option<Vec<String>>> {
+        let Some(value) = self.values.get(key) else {
+            return Ok(None);
+        };
+        let invalid = || {
+            TfocusError::ConfigError(format!(
+                "{}: expected a list of quoted strings, got {}",
+                key, value
+            ))
+        };
+        let inner = value
+            .strip_prefix('[')
+            .and_then(|v| v.strip_suffix(']'))
+            .ok_or_else(invalid)?;
+        split_list(inner).map(Some).ok_or_else(invalid)
+    }
+
+    /// Returns the `key = value` pairs of a section, keyed without the section prefix
+    pub fn section(&self, name: &str) -> HashMap<String, String> {
+        let prefix = format!("{}.", name);
+        self.values
+            .iter()
+            .filter_map(|(key, value)| {
+                key.strip_prefix(&prefix)
+                    .map(|k| (k.to_string(), unquote(value).to_string()))
+            })
+            .collect()
+    }
+
+    /// Returns the search aliases: the defaults merged with `[aliases]`
+    pub fn aliases(&self) -> HashMap<String, String> {
+        let mut aliases: HashMap<String, String> = DEFAULT_ALIASES
+            .iter()
+            .map(|(alias, expansion)| (alias.to_string(), expansion.to_string()))
+            .collect();
+        aliases.extend(self.section("aliases"));
+        aliases
+    }
+
+    /// Returns the target flag format, where `{}` stands for the address
+    ///
+    /// Defaults to `-target={}`; `target_flag` overrides it for wrappers
+    /// that spell the flag differently. The flag is passed as one argument,
+    /// so it cannot contain whitespace.
+    pub fn target_flag(&self) -> Result<String> {
+        let Some(format) = self.get_string("target_flag") else {
+            return Ok(DEFAULT_TARGET_FLAG.to_string());
+        };
+        if !format.contains("{}") {
+            return Err(TfocusError::ConfigError(format!(
+                "target_flag: expected a {{}} placeholder for the address, got {}",
+                format
+            )));
+        }
+        if format.contains(char::is_whitespace) {
+            return Err(TfocusError::ConfigError(format!(
+                "target_flag: '{}' would be passed as one argument; join the flag and \
+                 the address without whitespace, e.g. --resource={{}}",
+                format
+            )));
+        }
+        Ok(format)
+    }
+}
+
+/// Removes surrounding double quotes from a value
+fn unquote(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .unwrap_or(value)
+}
+
+/// Splits the inside of a list into its quoted strings, e.g. `"a,b", "c"`
+///
+/// Commas inside quotes belong to the string; a trailing comma is allowed.
+/// Returns `None` for an unquoted item, a missing comma or an unterminated quote.
+fn split_list(inner: &str) -> Option<Vec<String>> {
+    let mut items = Vec::new();
+    let mut rest = inner.trim_start();
+    while !rest.is_empty() {
+        let (item, after) = rest.strip_prefix('"')?.split_once('"')?;
+        items.push(item.to_string());
+        rest = after.trim_start();
+        if let Some(after_comma) = rest.strip_prefix(',') {
+            rest = after_comma.trim_start();
+        } else if !rest.is_empty() {
+            return None;
+        }
+    }
+    Some(items)
+}
+
+/// Removes a trailing `#` comment that is not inside a quoted string
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            '#' if !in_string => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sections_and_dotted_keys() {
+        let config = Config::parse(
+            r#"
+            # tfocus settings
+            auto_approve.apply = true
+
+            [auto_approve]
+            destroy = false # always ask
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.get_bool("auto_approve.apply").unwrap(), Some(true));
+        assert_eq!(
+            config.get_bool("auto_approve.destroy").unwrap(),
+            Some(false)
+        );
+        assert_eq!(config.get_bool("auto_approve.plan").unwrap(), None);
+    }
+
+    #[test]
+    fn test_aliases_merge_defaults_and_config() {
+        let config = Config::parse(
+            r#"
+            [aliases]
+            ec2 = "aws_spot_instance_request"
+            vm = "azurerm_linux_virtual_machine"
+            "#,
+        )
+        .unwrap();
+
+        let aliases = config.aliases();
+        assert_eq!(aliases["ec2"], "aws_spot_instance_request");
+        assert_eq!(aliases["vm"], "azurerm_linux_virtual_machine");
+        assert_eq!(aliases["s3"], "aws_s3_bucket");
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed_line() {
+        assert!(Config::parse("auto_approve.apply").is_err());
+    }
+
+    #[test]
+    fn test_get_bool_rejects_non_bool() {
+        let config = Config::parse("auto_approve.apply = yes").unwrap();
+        assert!(config.get_bool("auto_approve.apply").is_err());
+    }
+
+    #[test]
+    fn test_get_list() {
+        let config = Config::parse(
+            r#"
+            [extra_args]
+            apply = ["-refresh=false", "-lock-timeout=5m"]
+            plan = []
+            destroy = -refresh=false
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.get_list("extra_args.apply").unwrap(),
+            Some(vec![
+                "-refresh=false".to_string(),
+                "-lock-timeout=5m".to_string()
+            ])
+        );
+        assert_eq!(config.get_list("extra_args.plan").unwrap(), Some(vec![]));
+        assert_eq!(config.get_list("extra_args.missing").unwrap(), None);
+        assert!(config.get_list("extra_args.destroy").is_err());
+    }
+
+    #[test]
+    fn test_get_list_keeps_commas_inside_quotes() {
+        let config = Config::parse(
+            r#"
+            [condense]
+            patterns = ["a,b", "c",]
+            missing_comma = ["a" "b"]
+            unterminated = ["a]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.get_list("condense.patterns").unwrap(),
+            Some(vec!["a,b".to_string(), "c".to_string()])
+        );
+        assert!(config.get_list("condense.missing_comma").is_err());
+        assert!(config.get_list("condense.unterminated").is_err());
+    }
+
+    #[test]
+    fn test_target_flag() {
+        assert_eq!(Config::default().target_flag().unwrap(), "-target={}");
+        let config = Config::parse(r#"target_flag = "--resource={}""#).unwrap();
+        assert_eq!(config.target_flag().unwrap(), "--resource={}");
+        let config = Config::parse(r#"target_flag = "--resource {}""#).unwrap();
+        assert!(config.target_flag().is_err());
+        let config = Config::parse(r#"target_flag = "-target=""#).unwrap();
+        assert!(config.target_flag().is_err());
+    }
+
+    #[test]
+    fn test_get_usize() {
+        let config = Config::parse(
+            "[safety]
+max_targets = 20
+bad = -1",
+        )
+        .unwrap();
+        assert_eq!(config.get_usize("safety.max_targets").unwrap(), Some(20));
+        assert_eq!(config.get_usize("safety.missing").unwrap(), None);
+        assert!(config.get_usize("safety.bad").is_err());
+    }
+}
@@ -1,33 +1,128 @@
-use crate::types::Resource;
+use crate::error::{Result, TfocusError};
+use crate::theme::Theme;
+use crate::types::{Resource, ResourceKind};
 use colored::*;
+use std::path::{Path, PathBuf};
 
 pub struct Display;
 
 impl Display {
     pub fn print_header(text: &str) {
-        println!("\n{}", text.bright_blue().bold());
+        println!("\n{}", Self::header_style(text));
+    }
+
+    /// Styles header text with the theme's header color
+    pub fn header_style(text: &str) -> ColoredString {
+        match Theme::current().header {
+            Some(color) => text.color(color).bold(),
+            None => text.bold(),
+        }
     }
 
     pub fn print_resource(resource: &Resource) {
-        let prefix = if resource.is_module {
-            format!("[{}]", "Module".green())
-        } else {
-            format!("[{}]", "Resource".blue())
+        let prefix = match resource.kind() {
+            ResourceKind::Module => format!("[{}]", "Module".green()),
+            ResourceKind::Resource => format!("[{}]", "Resource".blue()),
+            ResourceKind::Data => format!("[{}]", "Data".cyan()),
         };
 
+        let action = resource
+            .planned_action
+            .map(|action| format!(" [{}]", action))
+            .unwrap_or_default();
+
         println!(
-            "- {} {} ({})",
+            "- {} {}{} ({})",
             prefix,
-            resource.full_name().yellow(),
-            resource.file_path.display().to_string().dimmed()
+            resource.target_string().yellow(),
+            action.bold(),
+            resource.location().dimmed()
         );
     }
 
+    /// Prints one `target<TAB>file` line per resource for piping into other tools
+    pub fn print_resource_list(resources: &[Resource], header: bool) {
+        if header {
+            println!("TARGET\tFILE");
+        }
+        for resource in resources {
+            println!(
+                "{}\t{}",
+                resource.target_string(),
+                resource.file_path.display()
+            );
+        }
+    }
+
+    /// Renders the resources as a JSON array of `address`, `kind`, `file` and `line`
+    ///
+    /// Compact by default for machine consumers; `pretty` indents it for reading.
+    pub fn resource_json(resources: &[Resource], pretty: bool) -> Result<String> {
+        let inventory: Vec<serde_json::Value> = resources
+            .iter()
+            .map(|resource| {
+                serde_json::json!({
+                    "address": resource.target_string(),
+                    "kind": match resource.kind() {
+                        ResourceKind::Module => "module",
+                        ResourceKind::Resource => "resource",
+                        ResourceKind::Data => "data",
+                    },
+                    "file": resource.file_path.display().to_string(),
+                    "line": resource.line,
+                })
+            })
+            .collect();
+        let json = if pretty {
+            serde_json::to_string_pretty(&inventory)
+        } else {
+            serde_json::to_string(&inventory)
+        };
+        json.map_err(|e| TfocusError::ParseError(e.to_string()))
+    }
+
+    /// Prints one file path per line, relative to `root`, for hooks and scripts
+    pub fn print_file_list(files: &[PathBuf], root: &Path) {
+        for file in files {
+            println!("{}", file.strip_prefix(root).unwrap_or(file).display());
+        }
+    }
+
     pub fn print_command(command: &str) {
         println!("\n{} {}", "Executing:".bright_blue(), command.white());
     }
 
+    pub fn print_info(message: &str) {
+        println!("{} {}", "Info:".bright_blue().bold(), message);
+    }
+
+    pub fn print_warning(message: &str) {
+        println!("{} {}", "Warning:".yellow().bold(), message);
+    }
+
     pub fn print_success(message: &str) {
         println!("{} {}", "Success:".green().bold(), message);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resource_json() {
+        let resource = Resource {
+            line: 3,
+            ..Resource::for_test("aws_instance", "web")
+        };
+
+        assert_eq!(
+            Display::resource_json(std::slice::from_ref(&resource), false).unwrap(),
+            r#"[{"address":"aws_instance.web","file":"main.tf","kind":"resource","line":3}]"#
+        );
+        assert_eq!(
+            Display::resource_json(&[resource], true).unwrap(),
+            "[\n  {\n    \"address\": \"aws_instance.web\",\n    \"file\": \"main.tf\",\n    \"kind\": \"resource\",\n    \"line\": 3\n  }\n]"
+        );
+    }
+}
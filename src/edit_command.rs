@@ -0,0 +1,194 @@
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::Command;
+
+use crate::error::{Result, TfocusError};
+
+/// Instructions written above the command in the edited file
+const HEADER: &str = "\
+# Edit the command below; it runs once the editor exits.
+# Lines starting with '#' are ignored, and an empty command aborts.
+";
+
+/// Opens `command_line` in `$EDITOR` and returns the edited command split into words
+///
+/// Falls back to `vi` (`notepad` on Windows) when `EDITOR` is unset. The first
+/// word is the program to run. Fails when the edit leaves no command, more than
+/// one line, or an unterminated quote.
+pub fn edit(command_line: &str) -> Result<Vec<String>> {
+    let editor = env::var("EDITOR")
+        .ok()
+        .filter(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| if cfg!(windows) { "notepad" } else { "vi" }.to_string());
+    edit_with(&editor, command_line)
+}
+
+/// Opens `command_line` in `editor` and returns the edited command split into words
+fn edit_with(editor: &str, command_line: &str) -> Result<Vec<String>> {
+    // A new file with a random name that only this user can open, so another
+    // user cannot plant a symlink or their own command in its place
+    let mut file = tempfile::Builder::new()
+        .prefix("tfocus-command-")
+        .suffix(".sh")
+        .tempfile()?;
+    writeln!(file, "{}{}", HEADER, command_line)?;
+    file.flush()?;
+    run_editor(editor, file.path())?;
+    // Read by path, as some editors replace the file instead of writing to it
+    let edited = fs::read_to_string(file.path())?;
+    parse(&edited).map_err(TfocusError::InvalidEditedCommand)
+}
+
+/// Runs `editor` on `path`
+fn run_editor(editor: &str, path: &Path) -> Result<()> {
+    // EDITOR may carry arguments, e.g. "code --wait"
+    let words = split(editor).map_err(|e| {
+        TfocusError::ConfigError(format!("cannot parse EDITOR '{}': {}", editor, e))
+    })?;
+    let (program, args) = words
+        .split_first()
+        .ok_or_else(|| TfocusError::ConfigError("EDITOR is empty".to_string()))?;
+
+    let status = Command::new(program)
+        .args(args)
+        .arg(path)
+        .status()
+        .map_err(|e| {
+            TfocusError::CommandExecutionError(format!("cannot run editor '{}': {}", editor, e))
+        })?;
+    if !status.success() {
+        return Err(TfocusError::CommandExecutionError(format!(
+            "editor '{}' exited with status: {}",
+            editor, status
+        )));
+    }
+    Ok(())
+}
+
+/// Extracts the single command line from the edited file and splits it
+fn parse(content: &str) -> std::result::Result<Vec<String>, String> {
+    let lines: Vec<&str> = content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect();
+    match lines.as_slice() {
+        [] => Err("the command is empty".to_string()),
+        [line] => split(line),
+        lines => Err(format!("expected one command line, found {}", lines.len())),
+    }
+}
+
+/// Splits a command line into words like a POSIX shell, without expansions
+///
+/// Single quotes keep everything literally; in double quotes a backslash only
+/// escapes `"`, `\`, `$` and `` ` ``; elsewhere it escapes any character.
+fn split(line: &str) -> std::result::Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {
+                words.extend(word.take());
+            }
+            '\'' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => word.push(c),
+                        None => return Err("unterminated single quote".to_string()),
+                    }
+                }
+            }
+            '"' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\' | '$' | '`')) => word.push(c),
+                            Some(c) => {
+                                word.push('\\');
+                                word.push(c);
+                            }
+                            None => return Err("unterminated double quote".to_string()),
+                        },
+                        Some(c) => word.push(c),
+                        None => return Err("unterminated double quote".to_string()),
+                    }
+                }
+            }
+            '\\' => match chars.next() {
+                Some(c) => word.get_or_insert_with(String::new).push(c),
+                None => return Err("trailing backslash".to_string()),
+            },
+            c => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_shell_words() {
+        assert_eq!(
+            split(r#"terraform plan '-target=aws_instance.web["a b"]' -var "x=\"y\"" a\ b ''"#)
+                .unwrap(),
+            vec![
+                "terraform",
+                "plan",
+                r#"-target=aws_instance.web["a b"]"#,
+                "-var",
+                r#"x="y""#,
+                "a b",
+                ""
+            ]
+        );
+        assert_eq!(split(r"'it'\''s'").unwrap(), vec!["it's"]);
+        assert!(split("plan 'open").is_err());
+        assert!(split("plan \"open").is_err());
+        assert!(split("plan \\").is_err());
+    }
+
+    #[test]
+    fn test_parse_edited_file() {
+        assert_eq!(
+            parse(&format!("{}  terraform apply -target=a.b  \n\n", HEADER)).unwrap(),
+            vec!["terraform", "apply", "-target=a.b"]
+        );
+        assert!(parse(HEADER).is_err());
+        assert!(parse("terraform plan\nterraform apply\n").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_edit_with_stub_editor() {
+        // The stub replaces the file, as editors that save atomically do
+        let replace = r#"sh -c 'sed s/plan/apply/ "$0" > "$0.new" && mv "$0.new" "$0"'"#;
+        assert_eq!(
+            edit_with(
+                replace,
+                "terraform plan '-target=aws_instance.web[\"a b\"]'"
+            )
+            .unwrap(),
+            vec!["terraform", "apply", r#"-target=aws_instance.web["a b"]"#]
+        );
+
+        assert!(matches!(
+            edit_with(r#"sh -c ': > "$0"'"#, "terraform plan"),
+            Err(TfocusError::InvalidEditedCommand(_))
+        ));
+        assert!(matches!(
+            edit_with("false", "terraform plan"),
+            Err(TfocusError::CommandExecutionError(_))
+        ));
+    }
+}
@@ -1,3 +1,4 @@
+use serde::ser::{Serialize, SerializeStruct, Serializer};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -17,14 +18,165 @@ pub enum TfocusError {
     #[error("Terraform command failed: {0}")]
     TerraformError(String),
 
+    #[error(
+        "Terraform binary '{0}' not found. Install terraform or set TFOCUS_BINARY to its path"
+    )]
+    TerraformNotFound(String),
+
+    #[error("No resource found for {target}{}", format_suggestions(.suggestions))]
+    NoMatchingTarget {
+        target: String,
+        suggestions: Vec<String>,
+    },
+
     #[error("No terraform files found in directory")]
     NoTerraformFiles,
 
+    #[error("Path '{0}' does not exist (check --path)")]
+    PathNotFound(String),
+
+    #[error("Cannot read '{path}': {reason}")]
+    PathNotAccessible { path: String, reason: String },
+
+    #[error("No Terraform files in '{path}' or its subdirectories; {hint}")]
+    EmptyDirectory { path: String, hint: String },
+
     #[error("Regular expression error: {0}")]
     RegexError(#[from] regex::Error),
 
+    #[error("Invalid configuration: {0}")]
+    ConfigError(String),
+
+    #[error("Invalid glob pattern: {0}")]
+    GlobError(#[from] globset::Error),
+
     #[error("Failed to execute terraform command: {0}")]
     CommandExecutionError(String),
+
+    #[error("Terraform plan reported changes (exit code 2)")]
+    ChangesPresent,
+
+    #[error("Policy forbids targeting {target} ({rule})")]
+    PolicyViolation { target: String, rule: String },
+
+    #[error("Audit found {0} issue(s)")]
+    AuditFailed(usize),
+
+    #[error(
+        "Refusing to apply with uncommitted changes to {} (pass --allow-dirty to apply anyway)",
+        .0.join(", ")
+    )]
+    UncommittedChanges(Vec<String>),
+
+    #[error("Terraform {version} does not satisfy required_version \"{constraint}\" in {file}")]
+    VersionMismatch {
+        version: String,
+        constraint: String,
+        file: String,
+    },
+
+    #[error("Terraform {version} differs from {pinned} pinned in {file}")]
+    PinnedVersionMismatch {
+        version: String,
+        pinned: String,
+        file: String,
+    },
+
+    #[error("Focus set '{0}' not found (save one with Ctrl+S in the selector)")]
+    FocusSetNotFound(String),
+
+    #[error("Edited command is invalid: {0}")]
+    InvalidEditedCommand(String),
+
+    #[error("--select-first {requested} is out of range: {available} resource(s) found")]
+    SelectionOutOfRange { requested: usize, available: usize },
+}
+
+impl TfocusError {
+    /// Returns a stable tag naming the error category, for machine-readable output
+    pub fn kind(&self) -> &'static str {
+        match self {
+            TfocusError::Io(_) => "Io",
+            TfocusError::ParseError(_) => "ParseError",
+            TfocusError::InvalidTargetSelection => "InvalidTargetSelection",
+            TfocusError::InvalidOperation(_) => "InvalidOperation",
+            TfocusError::TerraformError(_) => "TerraformError",
+            TfocusError::TerraformNotFound(_) => "TerraformNotFound",
+            TfocusError::NoMatchingTarget { .. } => "NoMatchingTarget",
+            TfocusError::NoTerraformFiles => "NoTerraformFiles",
+            TfocusError::PathNotFound(_) => "PathNotFound",
+            TfocusError::PathNotAccessible { .. } => "PathNotAccessible",
+            TfocusError::EmptyDirectory { .. } => "EmptyDirectory",
+            TfocusError::RegexError(_) => "RegexError",
+            TfocusError::ConfigError(_) => "ConfigError",
+            TfocusError::GlobError(_) => "GlobError",
+            TfocusError::CommandExecutionError(_) => "CommandExecutionError",
+            TfocusError::ChangesPresent => "ChangesPresent",
+            TfocusError::PolicyViolation { .. } => "PolicyViolation",
+            TfocusError::AuditFailed(_) => "AuditFailed",
+            TfocusError::UncommittedChanges(_) => "UncommittedChanges",
+            TfocusError::VersionMismatch { .. } => "VersionMismatch",
+            TfocusError::PinnedVersionMismatch { .. } => "PinnedVersionMismatch",
+            TfocusError::FocusSetNotFound(_) => "FocusSetNotFound",
+            TfocusError::InvalidEditedCommand(_) => "InvalidEditedCommand",
+            TfocusError::SelectionOutOfRange { .. } => "SelectionOutOfRange",
+        }
+    }
+
+    /// Returns the process exit code for this error
+    ///
+    /// Changes reported by `-detailed-exitcode` keep terraform's exit code 2.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            TfocusError::ChangesPresent => 2,
+            _ => 1,
+        }
+    }
+}
+
+/// Serializes as `{"error": "<kind>", "message": "<display message>"}`
+impl Serialize for TfocusError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("TfocusError", 2)?;
+        state.serialize_field("error", self.kind())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
+/// Formats "did you mean" suggestions for an unmatched target
+fn format_suggestions(suggestions: &[String]) -> String {
+    if suggestions.is_empty() {
+        String::new()
+    } else {
+        let quoted: Vec<String> = suggestions.iter().map(|s| format!("'{}'", s)).collect();
+        format!(" (did you mean {}?)", quoted.join(", "))
+    }
 }
 
 pub type Result<T> = std::result::Result<T, TfocusError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialize_uses_stable_tag() {
+        let json = serde_json::to_string(&TfocusError::NoTerraformFiles).unwrap();
+        assert_eq!(
+            json,
+            r#"{"error":"NoTerraformFiles","message":"No terraform files found in directory"}"#
+        );
+
+        let err = TfocusError::NoMatchingTarget {
+            target: "module 'vcp'".to_string(),
+            suggestions: vec!["vpc".to_string()],
+        };
+        let value: serde_json::Value = serde_json::to_value(&err).unwrap();
+        assert_eq!(value["error"], "NoMatchingTarget");
+        assert_eq!(
+            value["message"],
+            "No resource found for module 'vcp' (did you mean 'vpc'?)"
+        );
+    }
+}
@@ -1,41 +1,324 @@
 use log::{debug, error};
 use std::env;
-use std::path::Path;
-use std::process::Command;
+use std::io::{BufRead, BufReader, ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Stdio};
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 
-use crate::cli::Operation;
+use crate::cli::{Cli, Operation};
+use crate::condense::Condenser;
+use crate::config::Config;
 use crate::display::Display;
+use crate::edit_command;
 use crate::error::{Result, TfocusError};
-use crate::selector::{SelectItem, Selector};
-use crate::types::Resource;
+use crate::history::{self, HistoryLog};
+use crate::input::InputHandler;
+use crate::selector::{SelectItem, Selection, Selector};
+use crate::summary::{self, PlanCounts, PlanLineParser, STEP_SUMMARY_ENV};
+use crate::types::{ImportBlock, OutputBlock, Resource};
+use crate::version::Version;
 
 /// Stores the child process ID for signal handling
 static mut CHILD_PID: Option<u32> = None;
 
 /// Main entry point for executing Terraform commands on selected resources
-pub fn execute_with_resources(resources: &[Resource]) -> Result<()> {
+///
+/// Returns `false` when the run was cancelled rather than completed.
+pub fn execute_with_resources(
+    resources: &[Resource],
+    operation: Operation,
+    cli: &Cli,
+    config: &Config,
+) -> Result<bool> {
+    let target_flag = config.target_flag()?;
     let running = setup_signal_handler()?;
-    let target_options = create_target_options(resources)?;
-    let operation = select_operation()?;
-    let working_dir = get_working_directory(resources)?;
+    let ordered = order_resources(resources, cli.reverse);
+    if ordered.len() > 1 {
+        print_target_order(&ordered, cli.reverse);
+    }
+    if let Some(max) = resolve_max_targets(cli, config)? {
+        if ordered.len() > max && !confirm_target_count(ordered.len(), max)? {
+            println!("\nOperation cancelled");
+            return Ok(false);
+        }
+    }
+    let groups = group_by_working_directory(&ordered)?;
+    let multiple_roots = groups.len() > 1;
+
+    // Capture plan counts when there is a step summary to write them to, or
+    // a would-destroy summary to print
+    let step_summary = env::var_os(STEP_SUMMARY_ENV)
+        .filter(|path| !path.is_empty() && operation.is_plan())
+        .map(PathBuf::from);
+    let mut stdout = StdoutHandling {
+        plan_counts: (step_summary.is_some() || operation == Operation::PlanDestroy)
+            .then(PlanCounts::default),
+        plan_line: PlanLineParser::new()?,
+        condenser: Condenser::from_cli(cli, config)?,
+    };
 
-    let result =
-        execute_terraform_command(&operation, &target_options, working_dir, running.clone())?;
+    let history_log = history::history_log(project_dir(&cli.path), cli.no_history, config)?;
 
-    // If plan was successful, suggest terraform apply with the same targets
-    if result && matches!(operation, Operation::Plan) {
-        Display::print_header("\nTo apply these changes, run:");
-        let terraform_command = format!("terraform apply {}", target_options.join(" "));
-        println!("  {}", terraform_command);
+    // Unless destroy is auto-approved, preview it first; the wizard's
+    // confirmation then stands in for terraform's own prompt
+    let mut destroy_confirmed = false;
+    if operation == Operation::Destroy && !resolve_auto_approve(&operation, cli, config)? {
+        if !destroy_wizard(
+            &ordered,
+            &groups,
+            &target_flag,
+            cli,
+            config,
+            running.clone(),
+            history_log.as_ref(),
+        )? {
+            println!("\nDestroy cancelled");
+            return Ok(false);
+        }
+        destroy_confirmed = true;
     }
 
+    let mut result = true;
+    // With --detailed-exitcode, changes found in one batch do not stop the others
+    let mut changes_present = false;
+    let mut suggestions = Vec::new();
+    'groups: for (working_dir, group) in &groups {
+        if multiple_roots {
+            Display::print_header(&format!("\nRunning in {}", working_dir.display()));
+        }
+        let target_options = create_target_options(group, &target_flag)?;
+        let batches = target_batches(&target_options, cli.batch_size());
+        if cli.batch_size.is_some() && batches.len() > 1 {
+            warn_about_batches(&operation, batches.len());
+        }
+
+        for batch in &batches {
+            let mut args = build_terraform_args(&operation, batch, cli, config)?;
+            if destroy_confirmed && !args.contains(&"-auto-approve".to_string()) {
+                args.push("-auto-approve".to_string());
+            }
+            match execute_terraform_command(
+                &operation,
+                &args,
+                working_dir,
+                cli,
+                running.clone(),
+                &mut stdout,
+                history_log.as_ref(),
+            )? {
+                RunOutcome::Completed => {}
+                RunOutcome::ChangesPresent => changes_present = true,
+                RunOutcome::Cancelled => {
+                    result = false;
+                    break 'groups;
+                }
+            }
+        }
+
+        let suggested_command = if operation == Operation::PlanDestroy {
+            "destroy"
+        } else {
+            "apply"
+        };
+        // Batched runs suggest one command per batch, to stay within the same limits
+        let suggested = if cli.batch_size.is_some() {
+            batches
+        } else {
+            vec![target_options]
+        };
+        for targets in suggested {
+            suggestions.push(if multiple_roots {
+                format!(
+                    "terraform {} {} {}",
+                    shell_quote(&format!("-chdir={}", working_dir.display())),
+                    suggested_command,
+                    shell_join(&targets)
+                )
+            } else {
+                format!("terraform {} {}", suggested_command, shell_join(&targets))
+            });
+        }
+    }
+
+    let plan_counts = stdout.plan_counts;
+    if let (true, Some(path), Some(counts)) = (result, &step_summary, plan_counts) {
+        let targets: Vec<String> = ordered.iter().map(Resource::target_string).collect();
+        summary::append_step_summary(path, &targets, counts)?;
+    }
+
+    // If plan was successful, suggest terraform apply (or destroy) with the same targets
+    if result && operation.is_plan() {
+        if operation == Operation::PlanDestroy {
+            let destroy = plan_counts.map_or(0, |counts| counts.destroy);
+            Display::print_header(&format!(
+                "\nWould destroy {} resource(s). To destroy them, run:",
+                destroy
+            ));
+        } else {
+            Display::print_header("\nTo apply these changes, run:");
+        }
+        for terraform_command in suggestions {
+            println!("  {}", terraform_command);
+        }
+    }
+
+    if result && changes_present && !cli.changes_ok {
+        return Err(TfocusError::ChangesPresent);
+    }
+    Ok(result)
+}
+
+/// Runs `plan -generate-config-out` for import blocks that have no configuration yet
+///
+/// Each working directory gets its own plan, targeting only its imports, and
+/// `out` is resolved against it as terraform does.
+pub fn generate_config(
+    imports: &[&ImportBlock],
+    out: &Path,
+    cli: &Cli,
+    config: &Config,
+) -> Result<()> {
+    if imports.is_empty() {
+        return Err(TfocusError::ConfigError(
+            "--generate-config-out: no import blocks without configuration".to_string(),
+        ));
+    }
+
+    let target_flag = config.target_flag()?;
+    let mut groups: Vec<(PathBuf, Vec<String>)> = Vec::new();
+    for import in imports {
+        let dir = import
+            .file_path
+            .parent()
+            .unwrap_or(Path::new("."))
+            .to_path_buf();
+        let target = target_flag.replace("{}", &import.to);
+        match groups.iter_mut().find(|(d, _)| *d == dir) {
+            Some((_, targets)) => targets.push(target),
+            None => groups.push((dir, vec![target])),
+        }
+    }
+    for (working_dir, _) in &groups {
+        check_generate_config_out(working_dir, out)?;
+    }
+
+    let running = setup_signal_handler()?;
+    let history_log = history::history_log(project_dir(&cli.path), cli.no_history, config)?;
+    let mut stdout = StdoutHandling {
+        plan_counts: None,
+        plan_line: PlanLineParser::new()?,
+        condenser: Condenser::from_cli(cli, config)?,
+    };
+    for (working_dir, targets) in &groups {
+        let mut args = vec![
+            "plan".to_string(),
+            format!("-generate-config-out={}", out.display()),
+        ];
+        args.extend(targets.iter().cloned());
+        args.extend(passthrough_flags(cli));
+        if execute_terraform_command(
+            &Operation::Plan,
+            &args,
+            working_dir,
+            cli,
+            running.clone(),
+            &mut stdout,
+            history_log.as_ref(),
+        )? == RunOutcome::Cancelled
+        {
+            break;
+        }
+    }
     Ok(())
 }
 
+/// Checks that terraform can write generated configuration to `out` in `working_dir`
+///
+/// Terraform refuses to overwrite an existing file, so that is caught up front.
+fn check_generate_config_out(working_dir: &Path, out: &Path) -> Result<()> {
+    let invalid = |reason: String| {
+        Err(TfocusError::ConfigError(format!(
+            "--generate-config-out {}: {}",
+            out.display(),
+            reason
+        )))
+    };
+    if !out.extension().is_some_and(|ext| ext == "tf") {
+        return invalid("the file must have a .tf extension".to_string());
+    }
+    let path = working_dir.join(out);
+    if path.exists() {
+        return invalid(format!(
+            "{} already exists and terraform will not overwrite it",
+            path.display()
+        ));
+    }
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() && !parent.is_dir() => {
+            invalid(format!("directory {} does not exist", parent.display()))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Walks through a destroy: the targets, a `plan -destroy` preview, then a confirmation
+///
+/// Returns whether the user approved the destroy.
+fn destroy_wizard(
+    resources: &[Resource],
+    groups: &[(PathBuf, Vec<Resource>)],
+    target_flag: &str,
+    cli: &Cli,
+    config: &Config,
+    running: Arc<AtomicBool>,
+    history_log: Option<&HistoryLog>,
+) -> Result<bool> {
+    Display::print_header("\nStep 1/3: Targets to destroy");
+    for resource in resources {
+        Display::print_resource(resource);
+    }
+
+    Display::print_header("\nStep 2/3: Destroy plan");
+    let mut stdout = StdoutHandling {
+        plan_counts: None,
+        plan_line: PlanLineParser::new()?,
+        condenser: Condenser::from_cli(cli, config)?,
+    };
+    for (working_dir, group) in groups {
+        let args =
+            build_destroy_plan_args(&create_target_options(group, target_flag)?, cli, config)?;
+        if execute_terraform_command(
+            &Operation::Plan,
+            &args,
+            working_dir,
+            cli,
+            running.clone(),
+            &mut stdout,
+            history_log,
+        )? == RunOutcome::Cancelled
+        {
+            return Ok(false);
+        }
+    }
+
+    Display::print_header("\nStep 3/3: Confirm");
+    let mut input = InputHandler::new()?;
+    let answer = input.read_line("Destroy the resources above? Only 'yes' will be accepted: ")?;
+    Ok(answer.trim() == "yes")
+}
+
 /// Sets up the Ctrl+C signal handler
+///
+/// The handler is installed once per process; later calls (e.g. the second
+/// operation of a sequence) reuse it and reset the running flag.
 fn setup_signal_handler() -> Result<Arc<AtomicBool>> {
+    static RUNNING: OnceLock<Arc<AtomicBool>> = OnceLock::new();
+    if let Some(running) = RUNNING.get() {
+        running.store(true, Ordering::SeqCst);
+        return Ok(running.clone());
+    }
+
     let running = Arc::new(AtomicBool::new(true));
     let r = running.clone();
 
@@ -61,15 +344,41 @@ fn setup_signal_handler() -> Result<Arc<AtomicBool>> {
     })
     .map_err(|e| TfocusError::CommandExecutionError(e.to_string()))?;
 
-    Ok(running)
+    Ok(RUNNING.get_or_init(|| running).clone())
+}
+
+/// Orders resources for targeting, reversing the natural order if requested
+///
+/// Reversing puts later-declared resources, which usually depend on earlier
+/// ones, first. This helps destroy runs within the limits of `-target`.
+fn order_resources(resources: &[Resource], reverse: bool) -> Vec<Resource> {
+    let mut ordered = resources.to_vec();
+    if reverse {
+        ordered.reverse();
+    }
+    ordered
+}
+
+/// Prints the order in which targets will be passed to Terraform
+fn print_target_order(resources: &[Resource], reverse: bool) {
+    let label = if reverse { "reversed" } else { "natural" };
+    Display::print_header(&format!("Target order ({}):", label));
+    for (i, resource) in resources.iter().enumerate() {
+        println!("  {}. {}", i + 1, resource.target_string());
+    }
 }
 
 /// Creates target options for the Terraform command
-fn create_target_options(resources: &[Resource]) -> Result<Vec<String>> {
-    let target_options: Vec<String> = resources
-        .iter()
-        .map(|r| format!("-target={}", r.target_string()))
-        .collect();
+///
+/// Count index ranges such as `[0-2]` are expanded into one `-target` per index.
+/// Each flag is `target_flag` with its `{}` replaced by the address.
+fn create_target_options(resources: &[Resource], target_flag: &str) -> Result<Vec<String>> {
+    let mut target_options = Vec::new();
+    for resource in resources {
+        for target in resource.target_strings()? {
+            target_options.push(target_flag.replace("{}", &target));
+        }
+    }
 
     if target_options.is_empty() {
         return Err(TfocusError::ParseError("No targets specified".to_string()));
@@ -78,77 +387,867 @@ fn create_target_options(resources: &[Resource]) -> Result<Vec<String>> {
     Ok(target_options)
 }
 
-/// Prompts the user to select an operation (plan or apply)
-fn select_operation() -> Result<Operation> {
+/// Returns the `-target` flags for the resources as one shell-quoted line
+///
+/// The flags are the ones terraform would run with, in the same order.
+pub fn target_args(resources: &[Resource], reverse: bool, config: &Config) -> Result<String> {
+    Ok(shell_join(&create_target_options(
+        &order_resources(resources, reverse),
+        &config.target_flag()?,
+    )?))
+}
+
+/// Returns a Makefile with one `tfocus-<operation>` target per operation
+///
+/// Each recipe runs the terraform command tfocus would run, one line per
+/// working directory, so the selection can be committed as a reproducible task.
+/// Batching is not applied; every target is passed in a single run.
+pub fn makefile(
+    resources: &[Resource],
+    operations: &[Operation],
+    cli: &Cli,
+    config: &Config,
+) -> Result<String> {
+    let target_flag = config.target_flag()?;
+    let groups = group_by_working_directory(&order_resources(resources, cli.reverse))?;
+    let names: Vec<String> = operations
+        .iter()
+        .map(|operation| format!("tfocus-{}", operation))
+        .collect();
+
+    let mut makefile = format!("# Generated by tfocus\n.PHONY: {}\n", names.join(" "));
+    for (operation, name) in operations.iter().zip(&names) {
+        makefile.push_str(&format!("\n{}:\n", name));
+        for (working_dir, group) in &groups {
+            let targets = create_target_options(group, &target_flag)?;
+            let mut command = vec![
+                terraform_binary(),
+                format!("-chdir={}", working_dir.display()),
+            ];
+            command.extend(build_terraform_args(operation, &targets, cli, config)?);
+            let line = command
+                .iter()
+                .map(|arg| shell_quote(arg).replace('$', "$$"))
+                .collect::<Vec<_>>()
+                .join(" \\\n\t\t");
+            makefile.push_str(&format!("\t{}\n", line));
+        }
+    }
+    Ok(makefile)
+}
+
+/// Runs a sequence of operations, e.g. plan then apply, against one confirmed selection
+///
+/// The selection is not re-resolved between operations, so each one targets
+/// exactly the same resources. Before every operation after the first,
+/// `confirm_next` may stop the sequence; `run` returns whether an operation
+/// completed, and the sequence stops at the first that did not.
+pub fn run_sequence(
+    resources: &[Resource],
+    operations: &[Operation],
+    mut confirm_next: impl FnMut(Operation) -> Result<bool>,
+    mut run: impl FnMut(Operation, &[Resource]) -> Result<bool>,
+) -> Result<bool> {
+    for (i, &operation) in operations.iter().enumerate() {
+        if i > 0 && !confirm_next(operation)? {
+            println!("\nStopped before {}", operation);
+            return Ok(false);
+        }
+        if !run(operation, resources)? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Asks whether to continue with the next operation of a sequence
+pub fn confirm_next_operation(operation: Operation) -> Result<bool> {
+    let mut input = InputHandler::new()?;
+    let answer = input.read_line(&format!("\nContinue with {}? [y/N]: ", operation))?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Asks before running against more resources than the `--max-targets` cap
+pub fn confirm_target_count(count: usize, max: usize) -> Result<bool> {
+    Display::print_warning(&format!(
+        "{} resources are selected, more than the limit of {}.",
+        count, max
+    ));
+    let mut input = InputHandler::new()?;
+    let answer = input.read_line("Target them all anyway? [y/N]: ")?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Asks the user to type the directory name before destroying every resource
+///
+/// Returns `true` only when the typed name matches.
+pub fn confirm_full_destroy(project_path: &Path) -> Result<bool> {
+    let dir_name = directory_name(project_path);
+    Display::print_warning("this destroy targets every resource in the project.");
+    println!("It is effectively a full `terraform destroy`.");
+
+    let mut input = InputHandler::new()?;
+    let typed = input.read_line(&format!(
+        "Type the directory name '{}' to confirm: ",
+        dir_name
+    ))?;
+    Ok(typed.trim() == dir_name)
+}
+
+/// Returns the directory of the project: the path itself, or a single file's parent
+pub fn project_dir(project_path: &Path) -> &Path {
+    if project_path.is_file() {
+        project_path.parent().unwrap_or(Path::new("."))
+    } else {
+        project_path
+    }
+}
+
+/// Returns the name of the directory containing the project
+fn directory_name(project_path: &Path) -> String {
+    let dir = project_dir(project_path);
+    dir.canonicalize()
+        .ok()
+        .and_then(|p| p.file_name().map(|n| n.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| dir.display().to_string())
+}
+
+/// Splits target options into the batches passed to each terraform run
+///
+/// With `per_target`, each target runs on its own, preserving selection order.
+fn target_batches(target_options: &[String], batch_size: usize) -> Vec<Vec<String>> {
+    target_options
+        .chunks(batch_size.max(1))
+        .map(<[String]>::to_vec)
+        .collect()
+}
+
+/// Explains what running one operation as several terraform commands means
+fn warn_about_batches(operation: &Operation, batches: usize) {
+    match operation {
+        Operation::Plan | Operation::PlanDestroy => Display::print_warning(&format!(
+            "planning in {} batches; each plan only shows the changes for its own targets",
+            batches
+        )),
+        _ => Display::print_warning(&format!(
+            "running {} in {} batches is not atomic; if a batch fails, earlier batches stay applied",
+            operation, batches
+        )),
+    }
+}
+
+/// Prompts the user to select an operation (plan, apply or destroy)
+///
+/// Returns `None` when the user presses Esc to go back to target selection.
+pub fn select_operation() -> Result<Option<Operation>> {
     Display::print_header("Select operation:");
 
     let items = vec![
         SelectItem {
-            display: "plan  - Show changes to be made".to_string(),
+            display: "plan          - Show changes to be made".to_string(),
             search_text: "plan terraform show changes".to_string(),
             data: "1".to_string(),
+            resource: None,
+            address: None,
+        },
+        SelectItem {
+            display: "plan -destroy - Preview destroying the targeted resources".to_string(),
+            search_text: "plan destroy preview delete remove".to_string(),
+            data: "4".to_string(),
+            resource: None,
+            address: None,
         },
         SelectItem {
-            display: "apply - Execute the planned changes".to_string(),
+            display: "apply         - Execute the planned changes".to_string(),
             search_text: "apply terraform execute changes".to_string(),
             data: "2".to_string(),
+            resource: None,
+            address: None,
+        },
+        SelectItem {
+            display: "destroy       - Destroy the targeted resources".to_string(),
+            search_text: "destroy terraform delete remove".to_string(),
+            data: "3".to_string(),
+            resource: None,
+            address: None,
         },
     ];
 
-    let mut selector = Selector::new(items);
+    let mut selector = Selector::new(items).with_back();
     match selector.run()? {
-        Some(input) => match input.as_str() {
-            "1" => Ok(Operation::Plan),
-            "2" => Ok(Operation::Apply),
+        Selection::Selected(input) => match input.as_str() {
+            "1" => Ok(Some(Operation::Plan)),
+            "2" => Ok(Some(Operation::Apply)),
+            "3" => Ok(Some(Operation::Destroy)),
+            "4" => Ok(Some(Operation::PlanDestroy)),
             _ => Err(TfocusError::InvalidOperation(input)),
         },
-        None => {
+        // Multi-select is not enabled for the operation selector
+        Selection::SelectedMany(inputs) => Err(TfocusError::InvalidOperation(inputs.join(","))),
+        Selection::Back => Ok(None),
+        Selection::Cancelled => {
             println!("\nOperation cancelled");
             std::process::exit(0);
         }
     }
 }
 
-/// Gets the working directory from the first resource
-fn get_working_directory(resources: &[Resource]) -> Result<&Path> {
-    resources
-        .first()
-        .map(|r| r.file_path.parent().unwrap_or(Path::new(".")))
-        .ok_or_else(|| TfocusError::ParseError("No resources specified".to_string()))
+/// Groups resources by the directory terraform must run in
+///
+/// Groups keep the order in which their directories first appear, and
+/// resources keep their order within each group.
+fn group_by_working_directory(resources: &[Resource]) -> Result<Vec<(PathBuf, Vec<Resource>)>> {
+    if resources.is_empty() {
+        return Err(TfocusError::ParseError(
+            "No resources specified".to_string(),
+        ));
+    }
+
+    let mut groups: Vec<(PathBuf, Vec<Resource>)> = Vec::new();
+    for resource in resources {
+        let dir = resource
+            .file_path
+            .parent()
+            .unwrap_or(Path::new("."))
+            .to_path_buf();
+        match groups.iter_mut().find(|(d, _)| *d == dir) {
+            Some((_, group)) => group.push(resource.clone()),
+            None => groups.push((dir, vec![resource.clone()])),
+        }
+    }
+    Ok(groups)
 }
 
-/// Executes the Terraform command with the specified options
-fn execute_terraform_command(
+/// Returns the cap on targeted resources, or `None` when there is none
+///
+/// `--max-targets` takes precedence over `safety.max_targets` in the config
+/// file; a cap of 0 means unlimited, which is also the default.
+fn resolve_max_targets(cli: &Cli, config: &Config) -> Result<Option<usize>> {
+    let max = match cli.max_targets {
+        Some(max) => max,
+        None => config.get_usize("safety.max_targets")?.unwrap_or(0),
+    };
+    Ok((max > 0).then_some(max))
+}
+
+/// Decides whether `-auto-approve` is appended for an operation
+///
+/// Resolution order: `--auto-approve`/`--no-auto-approve`, then
+/// `auto_approve.<operation>` in the config file, then the default
+/// (apply is auto-approved, destroy is not). Plan never takes `-auto-approve`.
+fn resolve_auto_approve(operation: &Operation, cli: &Cli, config: &Config) -> Result<bool> {
+    if operation.is_plan() {
+        return Ok(false);
+    }
+    if let Some(value) = cli.auto_approve_override() {
+        return Ok(value);
+    }
+    let key = format!("auto_approve.{}", operation);
+    let default = matches!(operation, Operation::Apply);
+    Ok(config.get_bool(&key)?.unwrap_or(default))
+}
+
+/// Builds the arguments passed to the terraform binary
+fn build_terraform_args(
     operation: &Operation,
     target_options: &[String],
-    working_dir: &Path,
-    running: Arc<AtomicBool>,
-) -> Result<bool> {
-    // read `TERRAFORM_BINARY_NAME` env, fallback to "terraform"
-    let terraform_binary =
-        env::var("TERRAFORM_BINARY_NAME").unwrap_or_else(|_| "terraform".to_string());
+    cli: &Cli,
+    config: &Config,
+) -> Result<Vec<String>> {
+    let mut args = match operation {
+        Operation::PlanDestroy => vec!["plan".to_string(), "-destroy".to_string()],
+        _ => vec![operation.to_string()],
+    };
+    args.extend(target_options.iter().cloned());
+
+    if resolve_auto_approve(operation, cli, config)? {
+        args.push("-auto-approve".to_string());
+    }
+    args.extend(passthrough_flags(cli));
+    args.extend(extra_args(operation, cli, config)?);
+    if cli.detailed_exitcode && operation.is_plan() {
+        args.push("-detailed-exitcode".to_string());
+    }
+
+    Ok(args)
+}
+
+/// Builds the `plan -destroy` arguments previewing a targeted destroy
+fn build_destroy_plan_args(
+    target_options: &[String],
+    cli: &Cli,
+    config: &Config,
+) -> Result<Vec<String>> {
+    let mut args = vec!["plan".to_string(), "-destroy".to_string()];
+    args.extend(target_options.iter().cloned());
+    args.extend(passthrough_flags(cli));
+    args.extend(extra_args(&Operation::PlanDestroy, cli, config)?);
+    Ok(args)
+}
+
+/// Returns the extra arguments scoped to the terraform command an operation runs
+///
+/// `extra_args.<command>` from the config comes first, then `--plan-arg`,
+/// `--apply-arg` or `--destroy-arg`. Both plan operations run `terraform plan`,
+/// so they share the plan arguments.
+fn extra_args(operation: &Operation, cli: &Cli, config: &Config) -> Result<Vec<String>> {
+    let (command, flags) = match operation {
+        Operation::Plan | Operation::PlanDestroy => ("plan", &cli.plan_arg),
+        Operation::Apply => ("apply", &cli.apply_arg),
+        Operation::Destroy => ("destroy", &cli.destroy_arg),
+    };
+    let mut args = config
+        .get_list(&format!("extra_args.{}", command))?
+        .unwrap_or_default();
+    args.extend(flags.iter().cloned());
+    Ok(args)
+}
+
+/// Returns the output flags passed to every terraform run
+fn passthrough_flags(cli: &Cli) -> Vec<String> {
+    let mut flags = Vec::new();
+    if cli.color_disabled() {
+        flags.push("-no-color".to_string());
+    }
+    if cli.compact_warnings {
+        flags.push("-compact-warnings".to_string());
+    }
+    for var_file in &cli.var_file {
+        flags.push(format!("-var-file={}", var_file.display()));
+    }
+    flags
+}
+
+/// Quotes an argument for a POSIX shell so printed commands can be pasted verbatim
+///
+/// Arguments made only of characters the shell leaves alone are returned as is;
+/// anything else (e.g. `[`, `]`, `"`) is wrapped in single quotes.
+fn shell_quote(arg: &str) -> String {
+    let is_safe = |c: char| c.is_ascii_alphanumeric() || "-_=./:,@%+".contains(c);
+    if !arg.is_empty() && arg.chars().all(is_safe) {
+        return arg.to_string();
+    }
+    format!("'{}'", arg.replace('\'', r"'\''"))
+}
+
+/// Joins arguments into a shell-quoted command line fragment
+fn shell_join(args: &[String]) -> String {
+    args.iter()
+        .map(|arg| shell_quote(arg))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Returns the terraform binary to run
+///
+/// Reads `TFOCUS_BINARY`, then `TERRAFORM_BINARY_NAME`, falling back to "terraform".
+fn terraform_binary() -> String {
+    env::var("TFOCUS_BINARY")
+        .or_else(|_| env::var("TERRAFORM_BINARY_NAME"))
+        .unwrap_or_else(|_| "terraform".to_string())
+}
+
+/// Spawns the terraform command, reporting a missing binary distinctly
+fn spawn_terraform(command: &mut Command, terraform_binary: &str) -> Result<Child> {
+    command.spawn().map_err(|e| match e.kind() {
+        ErrorKind::NotFound => TfocusError::TerraformNotFound(terraform_binary.to_string()),
+        _ => TfocusError::CommandExecutionError(e.to_string()),
+    })
+}
+
+/// Runs `terraform validate` in each working directory of the selection
+///
+/// Fails on the first directory whose configuration is invalid, after printing
+/// terraform's diagnostics.
+pub fn validate(resources: &[Resource], cli: &Cli) -> Result<()> {
+    let terraform_binary = terraform_binary();
+    let mut args = vec!["validate".to_string()];
+    if cli.color_disabled() {
+        args.push("-no-color".to_string());
+    }
+
+    for (working_dir, _) in group_by_working_directory(resources)? {
+        let mut command = Command::new(&terraform_binary);
+        command.args(&args).current_dir(&working_dir);
+        for (key, value) in cli.env_overrides() {
+            command.env(key, value);
+        }
+
+        Display::print_command(&format!("{} {}", terraform_binary, shell_join(&args)));
+        let result = spawn_terraform(
+            command.stdout(Stdio::piped()).stderr(Stdio::piped()),
+            &terraform_binary,
+        )?
+        .wait_with_output()
+        .map_err(|e| TfocusError::CommandExecutionError(e.to_string()))?;
+
+        if !result.status.success() {
+            print!("{}", String::from_utf8_lossy(&result.stdout));
+            eprint!("{}", String::from_utf8_lossy(&result.stderr));
+            return Err(TfocusError::TerraformError(format!(
+                "terraform validate failed in {}",
+                working_dir.display()
+            )));
+        }
+        Display::print_success(&format!(
+            "Configuration in {} is valid",
+            working_dir.display()
+        ));
+    }
+    Ok(())
+}
+
+/// Runs `terraform output` for a single output and prints its value
+///
+/// Outputs that are not yet in the state are reported rather than treated as errors.
+pub fn show_output(output: &OutputBlock, cli: &Cli) -> Result<()> {
+    let terraform_binary = terraform_binary();
+    let working_dir = output.file_path.parent().unwrap_or(Path::new("."));
+
+    let mut args = vec!["output".to_string()];
+    if cli.output_json {
+        args.push("-json".to_string());
+    }
+    if cli.color_disabled() {
+        args.push("-no-color".to_string());
+    }
+    args.push(output.name.clone());
+
     let mut command = Command::new(&terraform_binary);
-    command.arg(operation.to_string()).current_dir(working_dir);
+    command.args(&args).current_dir(working_dir);
+    for (key, value) in cli.env_overrides() {
+        command.env(key, value);
+    }
+
+    Display::print_command(&format!("{} {}", terraform_binary, shell_join(&args)));
+    let result = command.output().map_err(|e| match e.kind() {
+        ErrorKind::NotFound => TfocusError::TerraformNotFound(terraform_binary.clone()),
+        _ => TfocusError::CommandExecutionError(e.to_string()),
+    })?;
 
-    for target in target_options {
-        command.arg(target);
+    if result.status.success() {
+        print!("{}", String::from_utf8_lossy(&result.stdout));
+        return Ok(());
     }
 
-    if matches!(operation, Operation::Apply) {
-        command.arg("-auto-approve");
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    if stderr.contains("not found") || stderr.contains("No outputs found") {
+        Display::print_warning(&format!(
+            "output '{}' is not in the state yet; run apply first",
+            output.name
+        ));
+        return Ok(());
     }
 
-    let command_str = format!(
-        "{} {} {}",
-        terraform_binary,
-        operation,
-        target_options.join(" "),
-    );
-    let command_str = if matches!(operation, Operation::Apply) {
-        format!("{} -auto-approve", command_str)
+    eprint!("{}", stderr);
+    Err(TfocusError::TerraformError(format!(
+        "terraform output failed with status: {}",
+        result.status
+    )))
+}
+
+/// Returns the addresses of tainted resource instances in the state
+///
+/// Runs `terraform show -json` once in the project directory. When the state
+/// cannot be read (not initialized, no state yet), this warns and reports none.
+pub fn tainted_addresses(project_path: &Path, cli: &Cli) -> Result<Vec<String>> {
+    let terraform_binary = terraform_binary();
+    let working_dir = project_dir(project_path);
+
+    let mut command = Command::new(&terraform_binary);
+    command
+        .args(["show", "-json", "-no-color"])
+        .current_dir(working_dir);
+    for (key, value) in cli.env_overrides() {
+        command.env(key, value);
+    }
+
+    debug!("Inspecting state in {:?}", working_dir);
+    let result = command.output().map_err(|e| match e.kind() {
+        ErrorKind::NotFound => TfocusError::TerraformNotFound(terraform_binary.clone()),
+        _ => TfocusError::CommandExecutionError(e.to_string()),
+    })?;
+
+    if !result.status.success() {
+        Display::print_warning("could not read the state; skipping the tainted check");
+        return Ok(Vec::new());
+    }
+    parse_tainted_addresses(&String::from_utf8_lossy(&result.stdout))
+}
+
+/// Returns the addresses printed by `terraform state list` in the project directory
+///
+/// Fails with [`TfocusError::TerraformError`] when the state cannot be read
+/// (not initialized, backend or credentials errors). A project that has not
+/// been applied yet has an empty state and no addresses.
+pub fn state_addresses(project_path: &Path, cli: &Cli) -> Result<Vec<String>> {
+    let terraform_binary = terraform_binary();
+    let working_dir = project_dir(project_path);
+
+    let mut command = Command::new(&terraform_binary);
+    command.args(["state", "list"]).current_dir(working_dir);
+    for (key, value) in cli.env_overrides() {
+        command.env(key, value);
+    }
+
+    debug!("Listing state in {:?}", working_dir);
+    let result = command.output().map_err(|e| match e.kind() {
+        ErrorKind::NotFound => TfocusError::TerraformNotFound(terraform_binary.clone()),
+        _ => TfocusError::CommandExecutionError(e.to_string()),
+    })?;
+
+    if !result.status.success() {
+        let stderr = String::from_utf8_lossy(&result.stderr);
+        let reason = stderr
+            .lines()
+            .map(|line| line.trim_matches(|c: char| c.is_whitespace() || "│╷╵".contains(c)))
+            .find(|line| !line.is_empty())
+            .unwrap_or("run terraform init first");
+        return Err(TfocusError::TerraformError(format!(
+            "could not read the state in {}: {}",
+            working_dir.display(),
+            reason
+        )));
+    }
+    Ok(String::from_utf8_lossy(&result.stdout)
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+/// Warns about targets that `terraform state list` does not know, before running terraform
+///
+/// This is best-effort: directories whose state cannot be read or is still
+/// empty are skipped, and unknown targets only produce warnings. Targets of
+/// resources that have not been applied yet are reported as unknown too.
+pub fn validate_targets(resources: &[Resource], cli: &Cli) -> Result<()> {
+    for (working_dir, group) in group_by_working_directory(resources)? {
+        let state = match state_addresses(&working_dir, cli) {
+            Ok(state) => state,
+            Err(TfocusError::TerraformError(e)) => {
+                Display::print_warning(&format!("{}; skipping target validation", e));
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+        if state.is_empty() {
+            Display::print_warning(&format!(
+                "no state in {}; skipping target validation",
+                working_dir.display()
+            ));
+            continue;
+        }
+        let mut targets = Vec::new();
+        for resource in &group {
+            targets.extend(resource.target_strings()?);
+        }
+        for target in unknown_targets(&targets, &state) {
+            Display::print_warning(&format!(
+                "{} is not in the state of {}; check the address for typos",
+                target,
+                working_dir.display()
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Returns the targets that match no state address
+///
+/// A target matches its own address and every address nested under it, so
+/// `module.vpc` matches `module.vpc.aws_subnet.a` and `aws_instance.web`
+/// matches `aws_instance.web[0]`.
+fn unknown_targets<'a>(targets: &'a [String], state: &[String]) -> Vec<&'a String> {
+    targets
+        .iter()
+        .filter(|target| {
+            !state.iter().any(|address| {
+                address
+                    .strip_prefix(target.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with(['[', '.']))
+            })
+        })
+        .collect()
+}
+
+/// A workspace reported by `terraform workspace list`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Workspace {
+    pub name: String,
+    /// Whether this is the selected workspace
+    pub current: bool,
+}
+
+/// Returns the workspaces of the project, as `terraform workspace list` reports them
+pub fn list_workspaces(project_path: &Path, cli: &Cli) -> Result<Vec<Workspace>> {
+    let terraform_binary = terraform_binary();
+    let mut command = Command::new(&terraform_binary);
+    command
+        .args(["workspace", "list"])
+        .current_dir(project_dir(project_path));
+    for (key, value) in cli.env_overrides() {
+        command.env(key, value);
+    }
+
+    let result = command.output().map_err(|e| match e.kind() {
+        ErrorKind::NotFound => TfocusError::TerraformNotFound(terraform_binary.clone()),
+        _ => TfocusError::CommandExecutionError(e.to_string()),
+    })?;
+    if !result.status.success() {
+        eprint!("{}", String::from_utf8_lossy(&result.stderr));
+        return Err(TfocusError::TerraformError(format!(
+            "terraform workspace list failed with status: {}",
+            result.status
+        )));
+    }
+    Ok(parse_workspace_list(&String::from_utf8_lossy(
+        &result.stdout,
+    )))
+}
+
+/// Runs `terraform workspace select` in the project directory
+pub fn select_workspace(project_path: &Path, name: &str, cli: &Cli) -> Result<()> {
+    let terraform_binary = terraform_binary();
+    let args = vec![
+        "workspace".to_string(),
+        "select".to_string(),
+        name.to_string(),
+    ];
+    let mut command = Command::new(&terraform_binary);
+    command.args(&args).current_dir(project_dir(project_path));
+    for (key, value) in cli.env_overrides() {
+        command.env(key, value);
+    }
+
+    Display::print_command(&format!("{} {}", terraform_binary, shell_join(&args)));
+    let status = spawn_terraform(&mut command, &terraform_binary)?
+        .wait()
+        .map_err(|e| TfocusError::CommandExecutionError(e.to_string()))?;
+    if !status.success() {
+        return Err(TfocusError::TerraformError(format!(
+            "could not select workspace '{}'",
+            name
+        )));
+    }
+    Ok(())
+}
+
+/// Reads `terraform workspace list` output, where `*` marks the current workspace
+fn parse_workspace_list(output: &str) -> Vec<Workspace> {
+    output
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| match line.strip_prefix('*') {
+            Some(name) => Workspace {
+                name: name.trim().to_string(),
+                current: true,
+            },
+            None => Workspace {
+                name: line.to_string(),
+                current: false,
+            },
+        })
+        .collect()
+}
+
+/// Returns the version reported by `terraform version -json`
+///
+/// Returns `None` when the binary is missing or its output has no version.
+pub fn terraform_version(project_path: &Path) -> Option<Version> {
+    let output = Command::new(terraform_binary())
+        .args(["version", "-json"])
+        .current_dir(project_dir(project_path))
+        .output()
+        .ok()?;
+    parse_terraform_version(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Returns the selected workspace as `terraform workspace show` reports it
+///
+/// `None` when terraform cannot be run or fails, e.g. before `terraform init`.
+pub fn current_workspace(project_path: &Path, cli: &Cli) -> Option<String> {
+    let mut command = Command::new(terraform_binary());
+    command
+        .args(["workspace", "show"])
+        .current_dir(project_dir(project_path));
+    for (key, value) in cli.env_overrides() {
+        command.env(key, value);
+    }
+    let output = command.output().ok()?;
+    let name = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !name.is_empty()).then_some(name)
+}
+
+/// Reads the version from `version -json` output, or from a `Terraform v1.6.3` line
+fn parse_terraform_version(output: &str) -> Option<Version> {
+    if let Ok(json) = serde_json::from_str::<serde_json::Value>(output) {
+        return json
+            .get("terraform_version")
+            .and_then(|v| v.as_str())
+            .and_then(Version::parse);
+    }
+    output
+        .split_whitespace()
+        .filter(|word| word.starts_with('v'))
+        .find_map(Version::parse)
+}
+
+/// Collects tainted addresses from `terraform show -json` output
+fn parse_tainted_addresses(json: &str) -> Result<Vec<String>> {
+    fn collect(module: &serde_json::Value, addresses: &mut Vec<String>) {
+        let resources = module.get("resources").and_then(|r| r.as_array());
+        for resource in resources.into_iter().flatten() {
+            if resource.get("tainted").and_then(|t| t.as_bool()) == Some(true) {
+                if let Some(address) = resource.get("address").and_then(|a| a.as_str()) {
+                    addresses.push(address.to_string());
+                }
+            }
+        }
+        let children = module.get("child_modules").and_then(|c| c.as_array());
+        for child in children.into_iter().flatten() {
+            collect(child, addresses);
+        }
+    }
+
+    let state: serde_json::Value = serde_json::from_str(json)
+        .map_err(|e| TfocusError::ParseError(format!("terraform show -json: {}", e)))?;
+    let mut addresses = Vec::new();
+    if let Some(root) = state.pointer("/values/root_module") {
+        collect(root, &mut addresses);
+    }
+    Ok(addresses)
+}
+
+/// What happens to terraform's stdout on its way to the terminal
+struct StdoutHandling {
+    /// Adds up the counts from `Plan:` lines
+    plan_counts: Option<PlanCounts>,
+    plan_line: PlanLineParser,
+    /// Hides noise lines with `--condense`
+    condenser: Option<Condenser>,
+}
+
+impl StdoutHandling {
+    /// Returns whether stdout must be read line by line rather than inherited
+    fn is_piped(&self) -> bool {
+        self.plan_counts.is_some() || self.condenser.is_some()
+    }
+}
+
+/// How a terraform run ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RunOutcome {
+    Completed,
+    /// Plan exited with 2 under `--detailed-exitcode`
+    ChangesPresent,
+    Cancelled,
+}
+
+/// Copies terraform's stdout to `out`, condensing and counting complete lines
+///
+/// A line that is still incomplete once the pipe is drained, such as the
+/// `Enter a value: ` prompt, is written at once so the user sees it while
+/// terraform waits for input; the rest of that line is then passed through as is.
+fn pass_through(input: impl Read, stdout: &mut StdoutHandling, out: &mut impl Write) -> Result<()> {
+    let mut reader = BufReader::new(input);
+    let mut line = Vec::new();
+    // Bytes of `line` already written as a partial line
+    let mut written = 0;
+    loop {
+        let buf = reader.fill_buf().map_err(TfocusError::Io)?;
+        if buf.is_empty() {
+            break;
+        }
+        let newline = buf.iter().position(|&b| b == b'\n');
+        let len = newline.map_or(buf.len(), |pos| pos + 1);
+        line.extend_from_slice(&buf[..len]);
+        reader.consume(len);
+
+        if newline.is_some() {
+            write_line(&line, written, stdout, out)?;
+            line.clear();
+            written = 0;
+        } else {
+            out.write_all(&line[written..]).map_err(TfocusError::Io)?;
+            out.flush().map_err(TfocusError::Io)?;
+            written = line.len();
+        }
+    }
+    if !line.is_empty() {
+        write_line(&line, written, stdout, out)?;
+        // Like every other line, the last one ends with a newline
+        if written > 0 {
+            writeln!(out).map_err(TfocusError::Io)?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes one complete line of terraform's stdout, unless `--condense` hides it
+///
+/// The first `written` bytes were already written as a partial line.
+fn write_line(
+    line: &[u8],
+    written: usize,
+    stdout: &mut StdoutHandling,
+    out: &mut impl Write,
+) -> Result<()> {
+    let text = String::from_utf8_lossy(line);
+    let text = text.trim_end_matches(['\n', '\r']);
+    if written > 0 {
+        out.write_all(&line[written..]).map_err(TfocusError::Io)?;
+    } else if !stdout
+        .condenser
+        .as_mut()
+        .is_some_and(|condenser| !condenser.keep(text))
+    {
+        writeln!(out, "{}", text).map_err(TfocusError::Io)?;
+    }
+    if let (Some(counts), Some(parsed)) =
+        (stdout.plan_counts.as_mut(), stdout.plan_line.parse(text))
+    {
+        counts.accumulate(parsed);
+    }
+    Ok(())
+}
+
+/// Executes the Terraform command with the given arguments
+///
+/// When `stdout` counts plans or condenses output, stdout is passed through
+/// line by line.
+fn execute_terraform_command(
+    operation: &Operation,
+    args: &[String],
+    working_dir: &Path,
+    cli: &Cli,
+    running: Arc<AtomicBool>,
+    stdout: &mut StdoutHandling,
+    history_log: Option<&HistoryLog>,
+) -> Result<RunOutcome> {
+    let (terraform_binary, args) = if cli.edit_command {
+        let mut words =
+            edit_command::edit(&format!("{} {}", terraform_binary(), shell_join(args)))?;
+        let program = words.remove(0);
+        (program, words)
     } else {
-        command_str
+        (terraform_binary(), args.to_vec())
     };
+    let mut command = Command::new(&terraform_binary);
+    command.args(&args).current_dir(working_dir);
+
+    let env_overrides = cli.env_overrides();
+    if !env_overrides.is_empty() {
+        Display::print_header("Environment overrides:");
+        for (key, value) in &env_overrides {
+            println!("  {}=****", key);
+            command.env(key, value);
+        }
+    }
+
+    let command_str = format!("{} {}", terraform_binary, shell_join(&args));
 
     Display::print_command(&command_str);
     debug!(
@@ -157,23 +1256,50 @@ fn execute_terraform_command(
     );
     debug!("Full command: {:?}", command);
 
-    let mut child = command
-        .spawn()
-        .map_err(|e| TfocusError::CommandExecutionError(e.to_string()))?;
+    if stdout.is_piped() {
+        command.stdout(Stdio::piped());
+    }
+    let mut child = spawn_terraform(&mut command, &terraform_binary)?;
 
     unsafe {
         CHILD_PID = Some(child.id());
     }
 
-    match child.wait() {
-        Ok(status) if status.success() => {
-            if running.load(Ordering::SeqCst) {
-                debug!("Terraform command executed successfully");
+    if let Some(child_stdout) = child.stdout.take() {
+        pass_through(child_stdout, stdout, &mut std::io::stdout())?;
+    }
+    if let Some(condenser) = stdout.condenser.as_mut() {
+        let hidden = condenser.take_hidden();
+        if hidden > 0 {
+            println!("({} line(s) hidden by --condense)", hidden);
+        }
+    }
+
+    // With -detailed-exitcode, plan exits with 2 when there are changes
+    let changes_present = |status: &ExitStatus| {
+        cli.detailed_exitcode && operation.is_plan() && status.code() == Some(2)
+    };
+
+    let exit = child.wait();
+    if let (Some(log), Ok(status)) = (history_log, &exit) {
+        if let Err(e) = history::record(log, working_dir, &args, status) {
+            Display::print_warning(&format!("could not write the history log: {}", e));
+        }
+    }
+
+    match exit {
+        Ok(status) if status.success() || changes_present(&status) => {
+            if !running.load(Ordering::SeqCst) {
+                Display::print_header("\nOperation cancelled by user");
+                Ok(RunOutcome::Cancelled)
+            } else if changes_present(&status) {
+                debug!("Terraform plan found changes");
                 Display::print_success("Operation completed successfully");
-                Ok(true)
+                Ok(RunOutcome::ChangesPresent)
             } else {
-                Display::print_header("\nOperation cancelled by user");
-                Ok(false)
+                debug!("Terraform command executed successfully");
+                Display::print_success("Operation completed successfully");
+                Ok(RunOutcome::Completed)
             }
         }
         Ok(status) => {
@@ -192,33 +1318,669 @@ fn execute_terraform_command(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::path::PathBuf;
+
+    #[test]
+    fn test_parse_terraform_version() {
+        let json = r#"{"terraform_version":"1.7.5","platform":"linux_amd64"}"#;
+        assert_eq!(
+            parse_terraform_version(json).map(|v| v.to_string()),
+            Some("1.7.5".to_string())
+        );
+        assert_eq!(
+            parse_terraform_version("OpenTofu v1.6.2\non linux_amd64\n").map(|v| v.to_string()),
+            Some("1.6.2".to_string())
+        );
+        assert_eq!(parse_terraform_version("command not found"), None);
+    }
+
+    #[test]
+    fn test_check_generate_config_out() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("existing.tf"), "").unwrap();
+
+        assert!(check_generate_config_out(dir.path(), Path::new("generated.tf")).is_ok());
+        assert!(check_generate_config_out(dir.path(), Path::new("generated.json")).is_err());
+        assert!(check_generate_config_out(dir.path(), Path::new("existing.tf")).is_err());
+        assert!(check_generate_config_out(dir.path(), Path::new("missing/generated.tf")).is_err());
+    }
+
+    #[test]
+    fn test_unknown_targets() {
+        let state: Vec<String> = [
+            "aws_instance.web[0]",
+            "aws_instance.web[1]",
+            "module.vpc.aws_subnet.a",
+            "data.aws_ami.ubuntu",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        let targets: Vec<String> = [
+            "aws_instance.web",
+            "aws_instance.web[1]",
+            "aws_instance.web[2]",
+            "aws_instance.we",
+            "module.vpc",
+            "module.vp",
+            "data.aws_ami.ubuntu",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+
+        assert_eq!(
+            unknown_targets(&targets, &state),
+            vec!["aws_instance.web[2]", "aws_instance.we", "module.vp"]
+        );
+    }
+
+    #[test]
+    fn test_parse_workspace_list() {
+        let output = "  default\n* staging\n  production\n\n";
+        assert_eq!(
+            parse_workspace_list(output),
+            vec![
+                Workspace {
+                    name: "default".to_string(),
+                    current: false
+                },
+                Workspace {
+                    name: "staging".to_string(),
+                    current: true
+                },
+                Workspace {
+                    name: "production".to_string(),
+                    current: false
+                },
+            ]
+        );
+        assert!(parse_workspace_list("").is_empty());
+    }
+
+    #[test]
+    fn test_parse_tainted_addresses() {
+        let json = r#"{
+          "format_version": "1.0",
+          "values": {
+            "root_module": {
+              "resources": [
+                {"address": "aws_instance.web[0]", "tainted": true},
+                {"address": "aws_instance.web[1]"}
+              ],
+              "child_modules": [{
+                "address": "module.vpc",
+                "resources": [{"address": "module.vpc.aws_subnet.a", "tainted": true}]
+              }]
+            }
+          }
+        }"#;
+        assert_eq!(
+            parse_tainted_addresses(json).unwrap(),
+            vec!["aws_instance.web[0]", "module.vpc.aws_subnet.a"]
+        );
+        assert!(parse_tainted_addresses(r#"{"format_version": "1.0"}"#)
+            .unwrap()
+            .is_empty());
+    }
 
     #[test]
     fn test_create_target_options() {
         let resources = vec![
+            Resource::for_test("aws_instance", "web"),
             Resource {
-                resource_type: "aws_instance".to_string(),
-                name: "web".to_string(),
-                is_module: false,
-                file_path: PathBuf::from("main.tf"),
-                has_count: false,
-                has_for_each: false,
-                index: None,
-            },
-            Resource {
-                resource_type: "aws_instance".to_string(),
-                name: "app".to_string(),
-                is_module: false,
-                file_path: PathBuf::from("main.tf"),
                 has_count: true,
-                has_for_each: false,
                 index: Some("0".to_string()),
+                ..Resource::for_test("aws_instance", "app")
             },
         ];
 
-        let options = create_target_options(&resources).unwrap();
+        let options = create_target_options(&resources, "-target={}").unwrap();
         assert_eq!(options[0], "-target=aws_instance.web");
         assert_eq!(options[1], "-target=aws_instance.app[0]");
+        let options = create_target_options(&resources, "--resource={}").unwrap();
+        assert_eq!(options[1], "--resource=aws_instance.app[0]");
+
+        let config = Config::default();
+        assert_eq!(
+            target_args(&resources, false, &config).unwrap(),
+            "-target=aws_instance.web '-target=aws_instance.app[0]'"
+        );
+        assert_eq!(
+            target_args(&resources, true, &config).unwrap(),
+            "'-target=aws_instance.app[0]' -target=aws_instance.web"
+        );
+    }
+
+    #[test]
+    fn test_makefile() {
+        use clap::Parser;
+
+        let resource = |name: &str, file: &str| Resource {
+            file_path: PathBuf::from(file),
+            has_for_each: true,
+            index: Some("a".to_string()),
+            ..Resource::for_test("aws_instance", name)
+        };
+        let cli = Cli::parse_from(["tfocus", "--var-file", "prod.tfvars"]);
+        let makefile = makefile(
+            &[
+                resource("web", "infra/main.tf"),
+                resource("db", "data/main.tf"),
+            ],
+            &[Operation::Plan, Operation::Apply],
+            &cli,
+            &Config::default(),
+        )
+        .unwrap();
+
+        assert!(makefile.starts_with("# Generated by tfocus\n.PHONY: tfocus-plan tfocus-apply\n"));
+        assert!(makefile.contains(concat!(
+            "\ntfocus-plan:\n",
+            "\tterraform \\\n",
+            "\t\t-chdir=infra \\\n",
+            "\t\tplan \\\n",
+            "\t\t'-target=aws_instance.web[\"a\"]' \\\n",
+            "\t\t-var-file=prod.tfvars\n",
+            "\tterraform \\\n",
+            "\t\t-chdir=data \\\n",
+        )));
+        assert!(makefile.contains("\t\t-auto-approve \\\n"));
+    }
+
+    #[test]
+    fn test_build_terraform_args_passthrough_flags() {
+        use clap::Parser;
+
+        let targets = vec!["-target=aws_instance.web".to_string()];
+
+        let config = Config::default();
+
+        let cli = Cli::parse_from(["tfocus"]);
+        let args = build_terraform_args(&Operation::Apply, &targets, &cli, &config).unwrap();
+        assert_eq!(
+            args,
+            vec!["apply", "-target=aws_instance.web", "-auto-approve"]
+        );
+
+        let cli = Cli::parse_from(["tfocus", "--no-color", "--compact-warnings"]);
+        let args = build_terraform_args(&Operation::Plan, &targets, &cli, &config).unwrap();
+        assert_eq!(
+            args,
+            vec![
+                "plan",
+                "-target=aws_instance.web",
+                "-no-color",
+                "-compact-warnings"
+            ]
+        );
+
+        let cli = Cli::parse_from(["tfocus", "--detailed-exitcode", "--changes-ok"]);
+        let args = build_terraform_args(&Operation::Plan, &targets, &cli, &config).unwrap();
+        assert_eq!(
+            args,
+            vec!["plan", "-target=aws_instance.web", "-detailed-exitcode"]
+        );
+        let args = build_terraform_args(&Operation::Apply, &targets, &cli, &config).unwrap();
+        assert!(!args.contains(&"-detailed-exitcode".to_string()));
+        let cli = Cli::parse_from(["tfocus", "--auto-approve"]);
+        let args = build_terraform_args(&Operation::PlanDestroy, &targets, &cli, &config).unwrap();
+        assert_eq!(args, vec!["plan", "-destroy", "-target=aws_instance.web"]);
+    }
+
+    #[test]
+    fn test_run_sequence_reuses_the_selection() {
+        let resources: Vec<Resource> = ["web", "db"]
+            .iter()
+            .map(|name| Resource::for_test("aws_instance", name))
+            .collect();
+        let mut runs: Vec<(Operation, Vec<String>)> = Vec::new();
+        let completed = run_sequence(
+            &resources,
+            &[Operation::Plan, Operation::Apply],
+            |_| Ok(true),
+            |operation, selection| {
+                runs.push((operation, create_target_options(selection, "-target={}")?));
+                Ok(true)
+            },
+        )
+        .unwrap();
+        assert!(completed);
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].1, runs[1].1);
+        assert_eq!(
+            runs[1],
+            (
+                Operation::Apply,
+                vec![
+                    "-target=aws_instance.web".to_string(),
+                    "-target=aws_instance.db".to_string()
+                ]
+            )
+        );
+
+        // Declining the next operation, or a failed one, ends the sequence
+        let mut count = 0;
+        let declined = run_sequence(
+            &resources,
+            &[Operation::Plan, Operation::Apply],
+            |_| Ok(false),
+            |_, _| {
+                count += 1;
+                Ok(true)
+            },
+        );
+        assert!(!declined.unwrap());
+        assert_eq!(count, 1);
+        let failed = run_sequence(
+            &resources,
+            &[Operation::Plan, Operation::Apply],
+            |_| Ok(true),
+            |_, _| Ok(false),
+        );
+        assert!(!failed.unwrap());
+    }
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(
+            shell_quote("-target=aws_instance.web"),
+            "-target=aws_instance.web"
+        );
+        assert_eq!(
+            shell_quote("-target=aws_instance.web[0]"),
+            "'-target=aws_instance.web[0]'"
+        );
+        assert_eq!(
+            shell_quote(r#"-target=module.foo["key"]"#),
+            r#"'-target=module.foo["key"]'"#
+        );
+        assert_eq!(
+            shell_quote(r#"-target=module.foo["it's"]"#),
+            r#"'-target=module.foo["it'\''s"]'"#
+        );
+        assert_eq!(shell_quote(""), "''");
+        assert_eq!(
+            shell_quote(r#"-target=aws_instance.web["東京🚀"]"#),
+            r#"'-target=aws_instance.web["東京🚀"]'"#
+        );
+        assert_eq!(
+            shell_join(&["apply".to_string(), "-target=a.b[1]".to_string()]),
+            "apply '-target=a.b[1]'"
+        );
+    }
+
+    #[test]
+    fn test_build_destroy_plan_args() {
+        use clap::Parser;
+
+        let targets = vec!["-target=aws_instance.web".to_string()];
+        let cli = Cli::parse_from(["tfocus", "--no-color", "--detailed-exitcode"]);
+        assert_eq!(
+            build_destroy_plan_args(&targets, &cli, &Config::default()).unwrap(),
+            vec!["plan", "-destroy", "-target=aws_instance.web", "-no-color"]
+        );
+    }
+
+    #[test]
+    fn test_extra_args_are_scoped_to_the_operation() {
+        use clap::Parser;
+
+        let targets = vec!["-target=aws_instance.web".to_string()];
+        let config = Config::parse(
+            "[extra_args]
+apply = [\"-refresh=false\"]",
+        )
+        .unwrap();
+        let cli = Cli::parse_from([
+            "tfocus",
+            "--no-auto-approve",
+            "--apply-arg",
+            "-lock-timeout=5m",
+            "--plan-arg=-parallelism=2",
+        ]);
+
+        assert_eq!(
+            build_terraform_args(&Operation::Apply, &targets, &cli, &config).unwrap(),
+            vec![
+                "apply",
+                "-target=aws_instance.web",
+                "-refresh=false",
+                "-lock-timeout=5m"
+            ]
+        );
+        assert_eq!(
+            build_terraform_args(&Operation::Plan, &targets, &cli, &config).unwrap(),
+            vec!["plan", "-target=aws_instance.web", "-parallelism=2"]
+        );
+        assert_eq!(
+            build_destroy_plan_args(&targets, &cli, &config).unwrap(),
+            vec![
+                "plan",
+                "-destroy",
+                "-target=aws_instance.web",
+                "-parallelism=2"
+            ]
+        );
+        assert_eq!(
+            build_terraform_args(&Operation::Destroy, &targets, &cli, &config).unwrap(),
+            vec!["destroy", "-target=aws_instance.web"]
+        );
+    }
+
+    #[test]
+    fn test_resolve_auto_approve() {
+        use clap::Parser;
+
+        let defaults = Config::default();
+        let config = Config::parse("[auto_approve]\napply = false").unwrap();
+
+        let cli = Cli::parse_from(["tfocus"]);
+        assert!(resolve_auto_approve(&Operation::Apply, &cli, &defaults).unwrap());
+        assert!(!resolve_auto_approve(&Operation::Apply, &cli, &config).unwrap());
+        assert!(!resolve_auto_approve(&Operation::Plan, &cli, &defaults).unwrap());
+        assert!(!resolve_auto_approve(&Operation::Destroy, &cli, &defaults).unwrap());
+
+        let cli = Cli::parse_from(["tfocus", "--auto-approve"]);
+        assert!(resolve_auto_approve(&Operation::Apply, &cli, &config).unwrap());
+
+        let cli = Cli::parse_from(["tfocus", "--no-auto-approve"]);
+        assert!(!resolve_auto_approve(&Operation::Apply, &cli, &defaults).unwrap());
+    }
+
+    #[test]
+    fn test_resolve_max_targets() {
+        use clap::Parser;
+
+        let config = Config::parse("[safety]\nmax_targets = 20").unwrap();
+
+        let cli = Cli::parse_from(["tfocus"]);
+        assert_eq!(resolve_max_targets(&cli, &Config::default()).unwrap(), None);
+        assert_eq!(resolve_max_targets(&cli, &config).unwrap(), Some(20));
+
+        let cli = Cli::parse_from(["tfocus", "--max-targets", "5"]);
+        assert_eq!(resolve_max_targets(&cli, &config).unwrap(), Some(5));
+
+        let cli = Cli::parse_from(["tfocus", "--max-targets", "0"]);
+        assert_eq!(resolve_max_targets(&cli, &config).unwrap(), None);
+    }
+
+    #[test]
+    fn test_target_batches_chunks_by_batch_size() {
+        let options: Vec<String> = (0..5)
+            .map(|i| format!("-target=aws_instance.web[{}]", i))
+            .collect();
+        let batches = target_batches(&options, 2);
+        assert_eq!(
+            batches.iter().map(Vec::len).collect::<Vec<_>>(),
+            vec![2, 2, 1]
+        );
+        assert_eq!(batches.concat(), options);
+    }
+
+    #[test]
+    fn test_target_batches_per_target_preserves_order() {
+        let options = vec![
+            "-target=module.vpc".to_string(),
+            "-target=aws_instance.web".to_string(),
+        ];
+
+        assert_eq!(target_batches(&options, usize::MAX), vec![options.clone()]);
+        assert_eq!(
+            target_batches(&options, 1),
+            vec![
+                vec!["-target=module.vpc".to_string()],
+                vec!["-target=aws_instance.web".to_string()],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_group_by_working_directory() {
+        let resource = |name: &str, path: &str| Resource {
+            file_path: PathBuf::from(path),
+            ..Resource::for_test("aws_instance", name)
+        };
+        let resources = vec![
+            resource("a", "envs/prod/main.tf"),
+            resource("b", "envs/dev/main.tf"),
+            resource("c", "envs/prod/network.tf"),
+        ];
+
+        let groups = group_by_working_directory(&resources).unwrap();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].0, PathBuf::from("envs/prod"));
+        let names: Vec<&str> = groups[0].1.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["a", "c"]);
+        assert_eq!(groups[1].0, PathBuf::from("envs/dev"));
+
+        assert!(group_by_working_directory(&[]).is_err());
+    }
+
+    #[test]
+    fn test_directory_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join("prod-network");
+        std::fs::create_dir(&project).unwrap();
+        std::fs::write(project.join("main.tf"), "").unwrap();
+
+        assert_eq!(directory_name(&project), "prod-network");
+        assert_eq!(directory_name(&project.join("main.tf")), "prod-network");
+    }
+
+    #[test]
+    fn test_spawn_missing_binary() {
+        let binary = "/nonexistent/tfocus-test/terraform";
+        let mut command = Command::new(binary);
+        let err = spawn_terraform(&mut command, binary).unwrap_err();
+        assert!(
+            matches!(err, TfocusError::TerraformNotFound(ref b) if b == binary),
+            "Expected TerraformNotFound, got {:?}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_order_resources_reverse() {
+        let resources: Vec<Resource> = ["a", "b", "c"]
+            .iter()
+            .map(|name| Resource::for_test("aws_instance", name))
+            .collect();
+
+        let natural = order_resources(&resources, false);
+        assert_eq!(natural, resources);
+
+        let reversed = order_resources(&resources, true);
+        let names: Vec<&str> = reversed.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["c", "b", "a"]);
+    }
+
+    #[test]
+    fn test_create_target_options_expands_range() {
+        let resources = vec![Resource {
+            has_count: true,
+            index: Some("0-1".to_string()),
+            ..Resource::for_test("aws_instance", "app")
+        }];
+
+        let options = create_target_options(&resources, "-target={}").unwrap();
+        assert_eq!(
+            options,
+            vec!["-target=aws_instance.app[0]", "-target=aws_instance.app[1]"]
+        );
+    }
+
+    /// Yields one chunk per read, noting what had been written before each read
+    struct Chunks {
+        chunks: std::collections::VecDeque<&'static str>,
+        out: std::rc::Rc<std::cell::RefCell<Vec<u8>>>,
+        written_before_read: Vec<String>,
+    }
+
+    impl Read for Chunks {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.written_before_read
+                .push(String::from_utf8_lossy(&self.out.borrow()).into_owned());
+            let Some(chunk) = self.chunks.pop_front() else {
+                return Ok(0);
+            };
+            buf[..chunk.len()].copy_from_slice(chunk.as_bytes());
+            Ok(chunk.len())
+        }
+    }
+
+    struct SharedOut(std::rc::Rc<std::cell::RefCell<Vec<u8>>>);
+
+    impl Write for SharedOut {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_pass_through_shows_prompt_before_input() {
+        use clap::Parser;
+        let cli = Cli::parse_from(["tfocus", "--condense"]);
+        let mut stdout = StdoutHandling {
+            plan_counts: Some(PlanCounts::default()),
+            plan_line: PlanLineParser::new().unwrap(),
+            condenser: Condenser::from_cli(&cli, &Config::default()).unwrap(),
+        };
+        let out = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+        let mut input = Chunks {
+            chunks: [
+                "aws_instance.web: Refreshing state... [id=i-1]\nPlan: 1 to add, 0 to change, 0 to destroy.\n",
+                "  Enter a value: ",
+                "\naws_instance.web: Still creating... [10s elapsed]\n",
+                "Apply complete!",
+            ]
+            .into(),
+            out: out.clone(),
+            written_before_read: Vec::new(),
+        };
+
+        pass_through(&mut input, &mut stdout, &mut SharedOut(out.clone())).unwrap();
+
+        // terraform blocks on stdin after the prompt, so it must be out before the next read
+        assert_eq!(
+            input.written_before_read[2],
+            "Plan: 1 to add, 0 to change, 0 to destroy.\n  Enter a value: "
+        );
+        assert_eq!(
+            String::from_utf8_lossy(&out.borrow()),
+            "Plan: 1 to add, 0 to change, 0 to destroy.\n  Enter a value: \nApply complete!\n"
+        );
+        assert_eq!(stdout.plan_counts.unwrap().add, 1);
+        assert_eq!(stdout.condenser.unwrap().take_hidden(), 2);
+    }
+
+    /// Points `TFOCUS_BINARY` at a shell script until dropped
+    ///
+    /// The variable is process-wide, so tests holding a stub run one at a time.
+    #[cfg(unix)]
+    struct StubTerraform {
+        _lock: std::sync::MutexGuard<'static, ()>,
+    }
+
+    #[cfg(unix)]
+    impl StubTerraform {
+        fn new(dir: &Path, body: &str) -> StubTerraform {
+            use std::os::unix::fs::PermissionsExt;
+            static LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+            let lock = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+            let stub = dir.join("terraform");
+            std::fs::write(&stub, format!("#!/bin/sh\n{}", body)).unwrap();
+            std::fs::set_permissions(&stub, std::fs::Permissions::from_mode(0o755)).unwrap();
+            env::set_var("TFOCUS_BINARY", &stub);
+            StubTerraform { _lock: lock }
+        }
+    }
+
+    #[cfg(unix)]
+    impl Drop for StubTerraform {
+        fn drop(&mut self) {
+            env::remove_var("TFOCUS_BINARY");
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_changes_in_one_batch_do_not_stop_the_others() {
+        use clap::Parser;
+
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("runs.log");
+        let stub = StubTerraform::new(
+            dir.path(),
+            &format!(
+                "echo \"$@\" >> '{}'\necho 'Plan: 1 to add, 0 to change, 0 to destroy.'\nexit 2\n",
+                log.display()
+            ),
+        );
+        let step_summary = dir.path().join("summary.md");
+        env::set_var(STEP_SUMMARY_ENV, &step_summary);
+
+        let resources: Vec<Resource> = ["a", "b", "c"]
+            .into_iter()
+            .map(|name| Resource {
+                file_path: dir.path().join("main.tf"),
+                ..Resource::for_test("null_resource", name)
+            })
+            .collect();
+        let cli = Cli::parse_from([
+            "tfocus",
+            "--batch-size",
+            "1",
+            "--detailed-exitcode",
+            "--no-history",
+        ]);
+        let result = execute_with_resources(&resources, Operation::Plan, &cli, &Config::default());
+        env::remove_var(STEP_SUMMARY_ENV);
+        drop(stub);
+
+        assert!(matches!(result, Err(TfocusError::ChangesPresent)));
+        let runs = std::fs::read_to_string(&log).unwrap();
+        assert_eq!(runs.lines().count(), 3);
+        assert!(runs
+            .lines()
+            .last()
+            .unwrap()
+            .contains("-target=null_resource.c"));
+        assert!(std::fs::read_to_string(&step_summary)
+            .unwrap()
+            .contains("null_resource.c"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unreadable_state_is_an_error() {
+        use clap::Parser;
+
+        let dir = tempfile::tempdir().unwrap();
+        let cli = Cli::parse_from(["tfocus"]);
+        let stub = StubTerraform::new(
+            dir.path(),
+            "echo '╷' >&2\necho '│ Error: Backend initialization required' >&2\nexit 1\n",
+        );
+        match state_addresses(dir.path(), &cli) {
+            Err(TfocusError::TerraformError(message)) => {
+                assert!(message.ends_with(": Error: Backend initialization required"))
+            }
+            other => panic!("expected a terraform error, got {:?}", other),
+        }
+
+        // Target validation stays best-effort
+        let resource = Resource {
+            file_path: dir.path().join("main.tf"),
+            ..Resource::for_test("null_resource", "a")
+        };
+        assert!(validate_targets(&[resource], &cli).is_ok());
+        drop(stub);
+
+        let _stub = StubTerraform::new(dir.path(), "exit 0\n");
+        assert!(state_addresses(dir.path(), &cli).unwrap().is_empty());
     }
 }
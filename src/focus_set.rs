@@ -0,0 +1,158 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::{Result, TfocusError};
+use crate::types::Resource;
+
+/// Focus set location, relative to the project directory
+pub const FOCUS_SET_DIR: &str = ".tfocus/sets";
+
+/// Returns the file of a named focus set
+///
+/// Names may contain letters, digits, `-` and `_`, so a name cannot point
+/// outside the sets directory.
+pub fn set_path(project_dir: &Path, name: &str) -> Result<PathBuf> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(TfocusError::ConfigError(format!(
+            "invalid focus set name '{}': use letters, digits, '-' and '_'",
+            name
+        )));
+    }
+    Ok(project_dir
+        .join(FOCUS_SET_DIR)
+        .join(format!("{}.json", name)))
+}
+
+/// Saves the target addresses of `resources` as a named focus set
+pub fn save(project_dir: &Path, name: &str, resources: &[Resource]) -> Result<PathBuf> {
+    let path = set_path(project_dir, name)?;
+    let targets: Vec<String> = resources.iter().map(|r| r.target_string()).collect();
+    let json = serde_json::json!({ "targets": targets });
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let content =
+        serde_json::to_string_pretty(&json).map_err(|e| TfocusError::ConfigError(e.to_string()))?;
+    fs::write(&path, content + "\n")?;
+    Ok(path)
+}
+
+/// Loads the target addresses of a named focus set
+pub fn load(project_dir: &Path, name: &str) -> Result<Vec<String>> {
+    let path = set_path(project_dir, name)?;
+    if !path.is_file() {
+        return Err(TfocusError::FocusSetNotFound(name.to_string()));
+    }
+    let content = fs::read_to_string(&path)?;
+    parse(&content).map_err(|e| TfocusError::ConfigError(format!("{}: {}", path.display(), e)))
+}
+
+/// Parses `{"targets": ["address", ...]}`
+fn parse(content: &str) -> std::result::Result<Vec<String>, String> {
+    let json: serde_json::Value =
+        serde_json::from_str(content).map_err(|e| format!("invalid JSON: {}", e))?;
+    let targets = json
+        .get("targets")
+        .and_then(|v| v.as_array())
+        .ok_or("expected a \"targets\" array")?;
+    targets
+        .iter()
+        .map(|t| {
+            t.as_str()
+                .filter(|t| !t.is_empty())
+                .map(str::to_string)
+                .ok_or_else(|| "targets must be non-empty strings".to_string())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::{ParseOptions, TerraformProject};
+    use crate::types::Target;
+
+    #[test]
+    fn test_save_and_load_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = save(
+            dir.path(),
+            "web-tier",
+            &[
+                Resource::for_test("aws_instance", "web"),
+                Resource::for_test("", "vpc"),
+            ],
+        )
+        .unwrap();
+        assert_eq!(path, dir.path().join(".tfocus/sets/web-tier.json"));
+        assert_eq!(
+            load(dir.path(), "web-tier").unwrap(),
+            vec!["aws_instance.web", "module.vpc"]
+        );
+        assert!(matches!(
+            load(dir.path(), "missing"),
+            Err(TfocusError::FocusSetNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_indexed_targets_resolve_after_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("main.tf"),
+            r#"
+resource "aws_instance" "web" {
+  for_each = toset(["blue", "green"])
+}
+
+resource "aws_eip" "ip" {
+  count = 2
+}
+"#,
+        )
+        .unwrap();
+        let options = ParseOptions {
+            quiet: true,
+            ..ParseOptions::default()
+        };
+        let project = TerraformProject::parse_path(dir.path(), &options).unwrap();
+        let instances: Vec<Resource> = project
+            .get_all_resources()
+            .iter()
+            .map(|r| {
+                r.instance(if r.has_count { "1" } else { r#""blue""# })
+                    .unwrap()
+            })
+            .collect();
+
+        save(dir.path(), "indexed", &instances).unwrap();
+        let targets = load(dir.path(), "indexed").unwrap();
+        assert_eq!(
+            targets,
+            vec!["aws_eip.ip[1]", r#"aws_instance.web["blue"]"#]
+        );
+
+        let resolved: Vec<Resource> = targets
+            .into_iter()
+            .flat_map(|t| {
+                project
+                    .get_resources_by_target(&Target::Address(t))
+                    .unwrap()
+            })
+            .collect();
+        assert_eq!(resolved, instances);
+    }
+
+    #[test]
+    fn test_set_name_and_content_validation() {
+        assert!(set_path(Path::new("."), "../escape").is_err());
+        assert!(set_path(Path::new("."), "").is_err());
+        assert!(parse(r#"{"targets": ["a.b", ""]}"#).is_err());
+        assert!(parse(r#"{"resources": []}"#).is_err());
+    }
+}
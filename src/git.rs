@@ -0,0 +1,86 @@
+use log::debug;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::process::Command;
+
+use crate::error::{Result, TfocusError};
+use crate::project::ParseOptions;
+
+/// Refuses to continue when Terraform files under `dir` have uncommitted changes
+///
+/// Runs `git status --porcelain` in `dir`. Outside a git repository, or without
+/// git installed, the check is skipped.
+pub fn ensure_committed(dir: &Path, options: &ParseOptions) -> Result<()> {
+    let output = match Command::new("git")
+        .args(["status", "--porcelain", "--", "."])
+        .current_dir(dir)
+        .output()
+    {
+        Ok(output) => output,
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            debug!("git not found; skipping the uncommitted changes check");
+            return Ok(());
+        }
+        Err(e) => return Err(TfocusError::Io(e)),
+    };
+    if !output.status.success() {
+        debug!(
+            "{} is not in a git repository; skipping the check",
+            dir.display()
+        );
+        return Ok(());
+    }
+
+    let dirty = dirty_config_files(&String::from_utf8_lossy(&output.stdout), options);
+    if dirty.is_empty() {
+        return Ok(());
+    }
+    Err(TfocusError::UncommittedChanges(dirty))
+}
+
+/// Returns the configuration files listed in `git status --porcelain` output
+fn dirty_config_files(porcelain: &str, options: &ParseOptions) -> Vec<String> {
+    porcelain
+        .lines()
+        .filter_map(|line| line.get(3..))
+        .map(|path| {
+            // Renames are listed as `old -> new`
+            let path = path.rsplit(" -> ").next().unwrap_or(path);
+            path.trim_matches('"').to_string()
+        })
+        .filter(|path| options.is_config_file(Path::new(path)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dirty_config_files() {
+        let porcelain = " M main.tf\n?? notes.md\nA  modules/vpc/variables.tf\n\
+                         R  old.tf -> network.tf\n?? \"with space.tf\"\n M stack.tofu\n";
+        assert_eq!(
+            dirty_config_files(porcelain, &ParseOptions::default()),
+            vec![
+                "main.tf",
+                "modules/vpc/variables.tf",
+                "network.tf",
+                "with space.tf"
+            ]
+        );
+
+        let options = ParseOptions {
+            opentofu: true,
+            ..ParseOptions::default()
+        };
+        assert!(dirty_config_files(porcelain, &options).contains(&"stack.tofu".to_string()));
+    }
+
+    #[test]
+    fn test_ensure_committed_outside_git_repo() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("main.tf"), "").unwrap();
+        assert!(ensure_committed(dir.path(), &ParseOptions::default()).is_ok());
+    }
+}
@@ -0,0 +1,72 @@
+use crate::types::{Resource, ResourceKind};
+
+/// Renders resources and their `depends_on` edges as a Graphviz DOT graph
+///
+/// Every resource becomes a node. Dependencies outside `resources` still get
+/// an edge, so a filtered graph shows what the selection relies on.
+pub fn render_dot(resources: &[Resource]) -> String {
+    let mut dot = String::from("digraph tfocus {\n  rankdir=LR;\n");
+
+    for resource in resources {
+        let shape = match resource.kind() {
+            ResourceKind::Module => "component",
+            ResourceKind::Resource => "box",
+            ResourceKind::Data => "ellipse",
+        };
+        dot.push_str(&format!(
+            "  {} [shape={}];\n",
+            quote_id(&resource.full_name()),
+            shape
+        ));
+    }
+
+    for resource in resources {
+        for dependency in &resource.depends_on {
+            dot.push_str(&format!(
+                "  {} -> {};\n",
+                quote_id(&resource.full_name()),
+                quote_id(dependency)
+            ));
+        }
+    }
+
+    dot.push_str("}\n");
+    dot
+}
+
+/// Quotes a node ID for DOT
+fn quote_id(id: &str) -> String {
+    format!("\"{}\"", id.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resource(resource_type: &str, name: &str, depends_on: &[&str]) -> Resource {
+        Resource {
+            depends_on: depends_on.iter().map(|d| d.to_string()).collect(),
+            ..Resource::for_test(resource_type, name)
+        }
+    }
+
+    #[test]
+    fn test_render_dot() {
+        let resources = vec![
+            resource("aws_vpc", "main", &[]),
+            resource("aws_instance", "web", &["aws_vpc.main", "module.network"]),
+            resource("", "network", &[]),
+        ];
+
+        assert_eq!(
+            render_dot(&resources),
+            "digraph tfocus {\n  rankdir=LR;\n  \
+             \"aws_vpc.main\" [shape=box];\n  \
+             \"aws_instance.web\" [shape=box];\n  \
+             \"module.network\" [shape=component];\n  \
+             \"aws_instance.web\" -> \"aws_vpc.main\";\n  \
+             \"aws_instance.web\" -> \"module.network\";\n\
+             }\n"
+        );
+    }
+}
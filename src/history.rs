@@ -0,0 +1,198 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::ExitStatus;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::config::Config;
+use crate::error::{Result, TfocusError};
+
+/// History log location, relative to the project directory
+pub const DEFAULT_HISTORY_PATH: &str = ".tfocus/history.log";
+
+/// Where executed commands are logged
+#[derive(Debug, PartialEq, Eq)]
+pub struct HistoryLog {
+    pub path: PathBuf,
+    /// The configured `target_flag` format, to tell targets from other arguments
+    pub target_flag: String,
+}
+
+/// Returns the history log, or `None` when history is disabled
+///
+/// `--no-history` and `history.enabled = false` turn the log off;
+/// `history.path` overrides the location. Relative paths are resolved
+/// against the project directory.
+pub fn history_log(
+    project_dir: &Path,
+    no_history: bool,
+    config: &Config,
+) -> Result<Option<HistoryLog>> {
+    if no_history || config.get_bool("history.enabled")? == Some(false) {
+        return Ok(None);
+    }
+    let path = config
+        .get_string("history.path")
+        .unwrap_or_else(|| DEFAULT_HISTORY_PATH.to_string());
+    Ok(Some(HistoryLog {
+        path: project_dir.join(path),
+        target_flag: config.target_flag()?,
+    }))
+}
+
+/// Appends one executed command to the history log
+///
+/// Each line is tab-separated: UTC timestamp, working directory, operation,
+/// targets, and exit status.
+pub fn record(
+    log: &HistoryLog,
+    working_dir: &Path,
+    args: &[String],
+    status: &ExitStatus,
+) -> Result<()> {
+    if let Some(parent) = log.path.parent() {
+        fs::create_dir_all(parent).map_err(TfocusError::Io)?;
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&log.path)
+        .map_err(TfocusError::Io)?;
+
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let exit = status
+        .code()
+        .map_or_else(|| "signal".to_string(), |code| code.to_string());
+    let entry = format_entry(seconds, working_dir, args, &log.target_flag, &exit);
+    writeln!(file, "{}", entry).map_err(TfocusError::Io)
+}
+
+/// Formats one history line
+///
+/// Targets are the arguments that fit `target_flag`, e.g. `-target={}`.
+fn format_entry(
+    seconds: u64,
+    working_dir: &Path,
+    args: &[String],
+    target_flag: &str,
+    exit: &str,
+) -> String {
+    let (prefix, suffix) = target_flag.split_once("{}").unwrap_or((target_flag, ""));
+    let target = |arg: &String| -> Option<String> {
+        arg.strip_prefix(prefix)
+            .and_then(|rest| rest.strip_suffix(suffix))
+            .filter(|address| !address.is_empty())
+            .map(str::to_string)
+    };
+    let targets: Vec<String> = args.iter().filter_map(target).collect();
+    let flags: Vec<&String> = args.iter().filter(|arg| target(arg).is_none()).collect();
+    let operation = match flags.first() {
+        Some(op) if flags.iter().any(|f| f.as_str() == "-destroy") => format!("{} -destroy", op),
+        Some(op) => op.to_string(),
+        None => String::new(),
+    };
+
+    format!(
+        "{}\t{}\t{}\t{}\texit={}",
+        format_timestamp(seconds),
+        working_dir.display(),
+        operation,
+        targets.join(" "),
+        exit
+    )
+}
+
+/// Formats seconds since the Unix epoch as an RFC 3339 UTC timestamp
+pub fn format_timestamp(seconds: u64) -> String {
+    let days = (seconds / 86_400) as i64;
+    let secs_of_day = seconds % 86_400;
+
+    // Civil-from-days conversion (proleptic Gregorian calendar)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        secs_of_day / 3_600,
+        secs_of_day % 3_600 / 60,
+        secs_of_day % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_timestamp(951_782_400), "2000-02-29T00:00:00Z");
+        assert_eq!(format_timestamp(1_792_065_723), "2026-10-15T12:02:03Z");
+    }
+
+    #[test]
+    fn test_format_entry() {
+        let args: Vec<String> = [
+            "plan",
+            "-destroy",
+            "-target=aws_instance.web",
+            "-target=module.vpc",
+            "-no-color",
+        ]
+        .iter()
+        .map(|a| a.to_string())
+        .collect();
+        assert_eq!(
+            format_entry(0, Path::new("envs/prod"), &args, "-target={}", "0"),
+            "1970-01-01T00:00:00Z\tenvs/prod\tplan -destroy\taws_instance.web module.vpc\texit=0"
+        );
+
+        let args: Vec<String> = ["apply", "--resource=aws_instance.web", "-target=ignored"]
+            .iter()
+            .map(|a| a.to_string())
+            .collect();
+        assert_eq!(
+            format_entry(0, Path::new("."), &args, "--resource={}", "1"),
+            "1970-01-01T00:00:00Z\t.\tapply\taws_instance.web\texit=1"
+        );
+    }
+
+    #[test]
+    fn test_history_log() {
+        let dir = Path::new("project");
+        assert_eq!(
+            history_log(dir, false, &Config::default()).unwrap(),
+            Some(HistoryLog {
+                path: dir.join(".tfocus/history.log"),
+                target_flag: "-target={}".to_string(),
+            })
+        );
+        assert_eq!(history_log(dir, true, &Config::default()).unwrap(), None);
+
+        let config =
+            Config::parse("target_flag = \"--resource={}\"\n[history]\npath = \"logs/tfocus.log\"")
+                .unwrap();
+        assert_eq!(
+            history_log(dir, false, &config).unwrap(),
+            Some(HistoryLog {
+                path: dir.join("logs/tfocus.log"),
+                target_flag: "--resource={}".to_string(),
+            })
+        );
+        let config = Config::parse("[history]\nenabled = false").unwrap();
+        assert_eq!(history_log(dir, false, &config).unwrap(), None);
+    }
+}
@@ -0,0 +1,92 @@
+use std::path::Path;
+use std::process::Command;
+
+use crate::cli::{Cli, Operation};
+use crate::display::Display;
+use crate::executor;
+
+/// Runs `--after-success` or `--after-failure` once an operation has finished
+///
+/// The hook runs through the shell in the project directory. Its failures are
+/// only reported, so they never replace the result of terraform.
+pub fn after_operation(cli: &Cli, operation: Operation, target_count: usize, succeeded: bool) {
+    let hook = if succeeded {
+        &cli.after_success
+    } else {
+        &cli.after_failure
+    };
+    let Some(hook) = hook else {
+        return;
+    };
+
+    Display::print_command(hook);
+    let dir = executor::project_dir(&cli.path);
+    match hook_command(hook, dir, operation, target_count, succeeded).status() {
+        Ok(status) if status.success() => {}
+        Ok(status) => {
+            Display::print_warning(&format!("hook '{}' failed with status: {}", hook, status))
+        }
+        Err(e) => Display::print_warning(&format!("could not run hook '{}': {}", hook, e)),
+    }
+}
+
+/// Builds the shell command for a hook, with the run described in `TFOCUS_HOOK_*` variables
+fn hook_command(
+    hook: &str,
+    dir: &Path,
+    operation: Operation,
+    target_count: usize,
+    succeeded: bool,
+) -> Command {
+    let mut command = if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.args(["/C", hook]);
+        command
+    } else {
+        let mut command = Command::new("sh");
+        command.args(["-c", hook]);
+        command
+    };
+    command
+        .current_dir(dir)
+        .env("TFOCUS_HOOK_OPERATION", operation.to_string())
+        .env("TFOCUS_HOOK_TARGET_COUNT", target_count.to_string())
+        .env(
+            "TFOCUS_HOOK_STATUS",
+            if succeeded { "success" } else { "failure" },
+        );
+    command
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::OsStr;
+
+    #[test]
+    fn test_hook_command_environment() {
+        let command = hook_command(
+            "notify",
+            Path::new("infra"),
+            Operation::PlanDestroy,
+            3,
+            false,
+        );
+
+        let env: Vec<(&OsStr, Option<&OsStr>)> = command.get_envs().collect();
+        assert!(env.contains(&(
+            OsStr::new("TFOCUS_HOOK_OPERATION"),
+            Some(OsStr::new("plan-destroy"))
+        )));
+        assert!(env.contains(&(
+            OsStr::new("TFOCUS_HOOK_TARGET_COUNT"),
+            Some(OsStr::new("3"))
+        )));
+        assert!(env.contains(&(
+            OsStr::new("TFOCUS_HOOK_STATUS"),
+            Some(OsStr::new("failure"))
+        )));
+        assert_eq!(command.get_current_dir(), Some(Path::new("infra")));
+        assert!(command.get_args().any(|arg| arg == "notify"));
+    }
+}
@@ -34,24 +34,4 @@ impl InputHandler {
             Err(err) => Err(TfocusError::CommandExecutionError(err.to_string())),
         }
     }
-
-    pub fn read_number(&mut self, prompt: &str, max: usize) -> Result<usize> {
-        loop {
-            let input = self.read_line(prompt)?;
-            match input.trim().parse::<usize>() {
-                Ok(num) if num > 0 && num <= max => return Ok(num),
-                _ => println!("Please enter a number between 1 and {}", max),
-            }
-        }
-    }
-
-    pub fn read_operation(&mut self) -> Result<String> {
-        loop {
-            let input = self.read_line("\nEnter option (1 or 2): ")?;
-            match input.trim() {
-                "1" | "2" => return Ok(input),
-                _ => println!("Please enter 1 for plan or 2 for apply"),
-            }
-        }
-    }
 }
@@ -0,0 +1,203 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use crate::error::{Result, TfocusError};
+use crate::project::TerraformProject;
+use crate::types::Resource;
+
+/// Inventory location, relative to the project directory
+pub const INVENTORY_PATH: &str = ".tfocus/inventory.json";
+
+/// The addresses and file modification times seen by one run
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Inventory {
+    pub addresses: BTreeSet<String>,
+    /// Modification time of each Terraform file, in seconds since the epoch
+    pub files: BTreeMap<String, u64>,
+}
+
+impl Inventory {
+    /// Records the addresses of `project` and the modification times of its files
+    ///
+    /// Files whose modification time cannot be read are left out.
+    pub fn capture(project: &TerraformProject) -> Inventory {
+        let files = project
+            .get_unique_files()
+            .into_iter()
+            .filter_map(|file| {
+                let modified = fs::metadata(&file).and_then(|m| m.modified()).ok()?;
+                let seconds = modified.duration_since(UNIX_EPOCH).ok()?.as_secs();
+                Some((file.display().to_string(), seconds))
+            })
+            .collect();
+        Inventory {
+            addresses: project
+                .resources_iter()
+                .map(|r| r.target_string())
+                .collect(),
+            files,
+        }
+    }
+}
+
+/// What changed between the previous inventory and the current one
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct InventoryDiff {
+    /// Addresses that were not in the previous inventory
+    pub added: BTreeSet<String>,
+    /// Addresses of the previous inventory that are gone
+    pub removed: Vec<String>,
+    /// Files that existed before and have been modified since
+    pub changed_files: BTreeSet<String>,
+}
+
+impl InventoryDiff {
+    /// Returns the selector marker of a resource: `new`, `changed` or none
+    pub fn marker(&self, resource: &Resource) -> Option<&'static str> {
+        if self.added.contains(&resource.target_string()) {
+            Some("new")
+        } else if self.file_changed(&resource.file_path) {
+            Some("changed")
+        } else {
+            None
+        }
+    }
+
+    /// Returns whether `file` was modified since the previous run
+    pub fn file_changed(&self, file: &Path) -> bool {
+        self.changed_files.contains(&file.display().to_string())
+    }
+}
+
+/// Compares the current inventory with the previous one
+pub fn diff(previous: &Inventory, current: &Inventory) -> InventoryDiff {
+    InventoryDiff {
+        added: current
+            .addresses
+            .difference(&previous.addresses)
+            .cloned()
+            .collect(),
+        removed: previous
+            .addresses
+            .difference(&current.addresses)
+            .cloned()
+            .collect(),
+        changed_files: current
+            .files
+            .iter()
+            .filter(|(file, modified)| previous.files.get(*file).is_some_and(|m| m != *modified))
+            .map(|(file, _)| file.clone())
+            .collect(),
+    }
+}
+
+/// Loads the inventory of the previous run, or `None` when there is none yet
+pub fn load(project_dir: &Path) -> Result<Option<Inventory>> {
+    let path = project_dir.join(INVENTORY_PATH);
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(&path)?;
+    parse(&content)
+        .map(Some)
+        .map_err(|e| TfocusError::ConfigError(format!("{}: {}", path.display(), e)))
+}
+
+/// Saves `inventory` for the next run
+pub fn save(project_dir: &Path, inventory: &Inventory) -> Result<PathBuf> {
+    let path = project_dir.join(INVENTORY_PATH);
+    let json = serde_json::json!({
+        "addresses": inventory.addresses,
+        "files": inventory.files,
+    });
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let content =
+        serde_json::to_string_pretty(&json).map_err(|e| TfocusError::ConfigError(e.to_string()))?;
+    fs::write(&path, content + "\n")?;
+    Ok(path)
+}
+
+/// Parses `{"addresses": ["address", ...], "files": {"path": mtime, ...}}`
+fn parse(content: &str) -> std::result::Result<Inventory, String> {
+    let json: serde_json::Value =
+        serde_json::from_str(content).map_err(|e| format!("invalid JSON: {}", e))?;
+    let addresses = json
+        .get("addresses")
+        .and_then(|v| v.as_array())
+        .ok_or("expected an \"addresses\" array")?
+        .iter()
+        .map(|a| {
+            a.as_str()
+                .map(str::to_string)
+                .ok_or_else(|| "addresses must be strings".to_string())
+        })
+        .collect::<std::result::Result<_, _>>()?;
+    let files = json
+        .get("files")
+        .and_then(|v| v.as_object())
+        .ok_or("expected a \"files\" object")?
+        .iter()
+        .map(|(file, modified)| {
+            modified
+                .as_u64()
+                .map(|m| (file.clone(), m))
+                .ok_or_else(|| format!("modification time of {} must be a number", file))
+        })
+        .collect::<std::result::Result<_, _>>()?;
+    Ok(Inventory { addresses, files })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inventory(addresses: &[&str], files: &[(&str, u64)]) -> Inventory {
+        Inventory {
+            addresses: addresses.iter().map(|a| a.to_string()).collect(),
+            files: files.iter().map(|(f, m)| (f.to_string(), *m)).collect(),
+        }
+    }
+
+    #[test]
+    fn test_diff_marks_new_removed_and_changed() {
+        let previous = inventory(
+            &["aws_instance.web", "aws_instance.old", "module.vpc"],
+            &[("main.tf", 100), ("vpc.tf", 100)],
+        );
+        let current = inventory(
+            &["aws_instance.web", "aws_instance.db", "module.vpc"],
+            &[("main.tf", 200), ("vpc.tf", 100), ("db.tf", 300)],
+        );
+        let changes = diff(&previous, &current);
+        assert_eq!(
+            changes.added,
+            BTreeSet::from(["aws_instance.db".to_string()])
+        );
+        assert_eq!(changes.removed, vec!["aws_instance.old"]);
+        assert_eq!(
+            changes.changed_files,
+            BTreeSet::from(["main.tf".to_string()])
+        );
+        assert!(changes.file_changed(Path::new("main.tf")));
+        assert!(!changes.file_changed(Path::new("db.tf")));
+    }
+
+    #[test]
+    fn test_save_and_load_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(load(dir.path()).unwrap(), None);
+
+        let saved = inventory(&["aws_instance.web"], &[("main.tf", 42)]);
+        let path = save(dir.path(), &saved).unwrap();
+        assert_eq!(path, dir.path().join(".tfocus/inventory.json"));
+        assert_eq!(load(dir.path()).unwrap(), Some(saved));
+
+        assert!(parse(r#"{"addresses": [1], "files": {}}"#).is_err());
+        assert!(parse(r#"{"addresses": [], "files": {"main.tf": "x"}}"#).is_err());
+    }
+}
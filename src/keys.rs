@@ -0,0 +1,105 @@
+use std::fs;
+use std::path::Path;
+
+use crate::error::{Result, TfocusError};
+use crate::types::Resource;
+
+/// Loads for_each keys from a `--keys` file
+pub fn load(path: &Path) -> Result<Vec<String>> {
+    let content = fs::read_to_string(path)?;
+    parse(&content).map_err(|e| TfocusError::ConfigError(format!("{}: {}", path.display(), e)))
+}
+
+/// Parses one key per line; blank lines are skipped and duplicates dropped
+///
+/// A key may be wrapped in double quotes, e.g. to keep surrounding spaces.
+fn parse(content: &str) -> std::result::Result<Vec<String>, String> {
+    let mut keys: Vec<String> = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let key = line
+            .strip_prefix('"')
+            .and_then(|l| l.strip_suffix('"'))
+            .unwrap_or(line);
+        if key.is_empty() {
+            return Err(format!("line {}: key is empty", i + 1));
+        }
+        if !keys.iter().any(|k| k == key) {
+            keys.push(key.to_string());
+        }
+    }
+    if keys.is_empty() {
+        return Err("no keys found".to_string());
+    }
+    Ok(keys)
+}
+
+/// Expands each for_each resource without an index into one instance per key
+///
+/// Other resources are kept as they are.
+pub fn expand(resources: Vec<Resource>, keys: &[String]) -> Vec<Resource> {
+    resources
+        .into_iter()
+        .flat_map(|resource| {
+            if resource.has_for_each && resource.index.is_none() {
+                keys.iter()
+                    .map(|key| Resource {
+                        index: Some(key.clone()),
+                        ..resource.clone()
+                    })
+                    .collect()
+            } else {
+                vec![resource]
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resource(name: &str, has_for_each: bool) -> Resource {
+        Resource {
+            has_for_each,
+            ..Resource::for_test("aws_instance", name)
+        }
+    }
+
+    #[test]
+    fn test_parse_keys() {
+        assert_eq!(
+            parse("blue\n\n  green  \n\" spaced \"\nblue\n").unwrap(),
+            vec!["blue", "green", " spaced "]
+        );
+        assert_eq!(
+            parse("東京\n\"🚀 launch\"\n").unwrap(),
+            vec!["東京", "🚀 launch"]
+        );
+        assert!(parse("blue\n\"\"\n").is_err());
+        assert!(parse("\n  \n").is_err());
+    }
+
+    #[test]
+    fn test_expand_for_each_resources() {
+        let keys = vec!["blue".to_string(), "green".to_string()];
+        let targets: Vec<String> = expand(
+            vec![resource("web", true), resource("bastion", false)],
+            &keys,
+        )
+        .iter()
+        .map(|r| r.target_string())
+        .collect();
+        assert_eq!(
+            targets,
+            vec![
+                r#"aws_instance.web["blue"]"#,
+                r#"aws_instance.web["green"]"#,
+                "aws_instance.bastion"
+            ]
+        );
+    }
+}
@@ -0,0 +1,79 @@
+use std::io::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use log::kv::{Error, Key, Value, VisitSource};
+
+use crate::cli::Format;
+use crate::history::format_timestamp;
+
+/// Installs the logger; `RUST_LOG` selects the level as before
+///
+/// With `--log-format json`, each log line is a JSON object for log aggregators,
+/// carrying the record's key-values (e.g. the file being parsed) as fields.
+pub fn init(format: Format) {
+    let mut builder = env_logger::Builder::from_default_env();
+    if format == Format::Json {
+        builder.format(|buf, record| {
+            let seconds = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs());
+            writeln!(buf, "{}", json_line(seconds, record))
+        });
+    }
+    builder.init();
+}
+
+/// Formats one log record as a single-line JSON object
+fn json_line(seconds: u64, record: &log::Record) -> String {
+    let mut fields = Fields(serde_json::Map::new());
+    // Visiting a map cannot fail, so a partial set of fields is never dropped
+    let _ = record.key_values().visit(&mut fields);
+    serde_json::json!({
+        "timestamp": format_timestamp(seconds),
+        "level": record.level().as_str(),
+        "target": record.target(),
+        "message": record.args().to_string(),
+        "fields": fields.0,
+    })
+    .to_string()
+}
+
+/// Collects log key-values into a JSON object
+struct Fields(serde_json::Map<String, serde_json::Value>);
+
+impl<'kvs> VisitSource<'kvs> for Fields {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+        self.0.insert(
+            key.to_string(),
+            serde_json::Value::String(value.to_string()),
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_line() {
+        let fields = [("file", "main.tf")];
+        let line = json_line(
+            0,
+            &log::Record::builder()
+                .level(log::Level::Debug)
+                .target("tfocus::project")
+                .args(format_args!("Parsing file: {:?}", "main.tf"))
+                .key_values(&fields)
+                .build(),
+        );
+
+        let json: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(json["timestamp"], "1970-01-01T00:00:00Z");
+        assert_eq!(json["level"], "DEBUG");
+        assert_eq!(json["target"], "tfocus::project");
+        assert_eq!(json["message"], r#"Parsing file: "main.tf""#);
+        assert_eq!(json["fields"]["file"], "main.tf");
+        assert!(!line.contains('\n'));
+    }
+}
@@ -1,60 +1,140 @@
+mod audit;
 mod cli;
+#[cfg(feature = "clipboard")]
+mod clipboard;
+mod condense;
+mod config;
 mod display;
+mod edit_command;
 mod error;
 mod executor;
+mod focus_set;
+mod git;
+mod graph;
+mod history;
+mod hook;
+mod input;
+mod inventory;
+mod keys;
+mod logging;
+mod metrics;
+mod orphans;
+mod plan;
+mod policy;
 mod project;
+mod providers;
 mod selector;
+mod summary;
+mod target_file;
+mod theme;
 mod types;
+mod version;
 
 use clap::Parser;
+use std::cell::{OnceCell, RefCell};
 use std::path::Path;
+use std::rc::Rc;
 
-use crate::cli::Cli;
+use crate::cli::{Cli, Command, Format, GraphFormat, ListKind, Operation};
+use crate::config::Config;
 use crate::display::Display;
 use crate::error::{Result, TfocusError};
-use crate::project::TerraformProject;
-use crate::selector::{SelectItem, Selector};
-use crate::types::{Resource, Target};
+use crate::input::InputHandler;
+use crate::inventory::{Inventory, InventoryDiff};
+use crate::metrics::{Metrics, Phase};
+use crate::policy::Policy;
+use crate::project::{resource_matches, ParseOptions, TerraformProject};
+use crate::selector::{SelectItem, Selection, Selector};
+use crate::theme::Theme;
+use crate::types::{Resource, ResourceKind, Target};
+use crate::version::Version;
+
+/// Stages of the interactive flow; later stages can step back to earlier ones
+enum Stage {
+    SelectTarget,
+    SelectOperation(Vec<Resource>),
+    /// Runs the operations in order against the same resources
+    Execute(Vec<Resource>, Vec<Operation>),
+}
 
 #[derive(Debug)]
 enum SelectionItem {
     File(usize, std::path::PathBuf),
+    Directory(usize, std::path::PathBuf),
     Module(usize, String),
-    Resource(usize, Resource),
+    Resource(usize, Box<Resource>),
+    Output(usize, String),
 }
 
-fn create_selection_items(selection_items: &[SelectionItem]) -> Vec<SelectItem> {
+/// Builds selector items; with `short_names`, resources inside modules are
+/// shown without their module prefix but still target the full address
+fn create_selection_items(
+    selection_items: &[SelectionItem],
+    short_names: bool,
+    changes: Option<&InventoryDiff>,
+) -> Vec<SelectItem> {
     selection_items
         .iter()
         .map(|item| {
             let (display, search_text) = match item {
                 SelectionItem::File(idx, path) => {
                     let path_str = path.display().to_string();
+                    let changed = changes.is_some_and(|changes| changes.file_changed(path));
                     (
-                        format!("{:4} {:15} {}", idx, "[File]", path_str),
+                        format!(
+                            "{:4} {:15} {}{}",
+                            idx,
+                            "[File]",
+                            path_str,
+                            if changed { "  (changed)" } else { "" }
+                        ),
                         path_str.clone(),
                     )
                 }
+                SelectionItem::Directory(idx, path) => {
+                    let path_str = path.display().to_string();
+                    (
+                        format!(
+                            "{:4} {:15} all resources in {}/",
+                            idx, "[Directory]", path_str
+                        ),
+                        path_str,
+                    )
+                }
                 SelectionItem::Module(idx, name) => (
                     format!("{:4} {:15} {}", idx, "[Module]", name),
                     name.clone(),
                 ),
+                SelectionItem::Output(idx, name) => (
+                    format!("{:4} {:15} output.{}", idx, "[Output]", name),
+                    format!("output.{}", name),
+                ),
                 SelectionItem::Resource(idx, resource) => {
-                    let resource_str = if resource.is_module {
-                        format!("module.{}", resource.name)
+                    let resource_str = resource.full_name();
+                    let shown = if short_names {
+                        resource.short_name()
                     } else {
-                        format!("{}.{}", resource.resource_type, resource.name)
+                        resource_str.clone()
                     };
                     (
                         format!(
-                            "{:4} {:15} {}",
+                            "{:4} {:15} {}{}{}{}",
                             idx,
-                            if resource.is_module {
-                                "[Module]"
-                            } else {
-                                "[Resource]"
+                            match resource.kind() {
+                                ResourceKind::Module => "[Module]",
+                                ResourceKind::Resource => "[Resource]",
+                                ResourceKind::Data => "[Data]",
                             },
-                            resource_str
+                            shown,
+                            resource
+                                .planned_action
+                                .map(|action| format!("  ({})", action))
+                                .unwrap_or_default(),
+                            if resource.tainted { "  (tainted)" } else { "" },
+                            changes
+                                .and_then(|changes| changes.marker(resource))
+                                .map(|marker| format!("  ({})", marker))
+                                .unwrap_or_default()
                         ),
                         resource_str,
                     )
@@ -67,112 +147,830 @@ fn create_selection_items(selection_items: &[SelectionItem]) -> Vec<SelectItem>
                     SelectionItem::File(_, path) => {
                         format!("f:{}", path.display())
                     }
+                    SelectionItem::Directory(_, path) => {
+                        format!("p:{}", path.display())
+                    }
                     SelectionItem::Module(_, name) => {
                         format!("m:{}", name)
                     }
-                    SelectionItem::Resource(_, resource) => {
-                        if resource.is_module {
-                            format!("m:{}", resource.name)
-                        } else {
+                    SelectionItem::Output(_, name) => {
+                        format!("o:{}", name)
+                    }
+                    // Plan resources are instances, so they are selected by exact address
+                    SelectionItem::Resource(_, resource) if resource.planned_action.is_some() => {
+                        format!("a:{}", resource.target_string())
+                    }
+                    SelectionItem::Resource(_, resource) => match resource.kind() {
+                        ResourceKind::Module => format!("m:{}", resource.name),
+                        ResourceKind::Resource => {
                             format!("r:{}.{}", resource.resource_type, resource.name)
                         }
-                    }
+                        ResourceKind::Data => {
+                            format!("d:{}.{}", resource.resource_type, resource.name)
+                        }
+                    },
+                },
+                resource: match item {
+                    SelectionItem::Resource(_, resource) => Some(resource.as_ref().clone()),
+                    _ => None,
+                },
+                address: match item {
+                    SelectionItem::Resource(_, resource) => Some(resource.target_string()),
+                    SelectionItem::Module(_, name) => Some(format!("module.{}", name)),
+                    _ => None,
                 },
             }
         })
         .collect()
 }
 
-fn main() -> Result<()> {
-    // setting env
-    env_logger::init();
-    let cli = Cli::parse();
-
-    if cli.verbose {
-        std::env::set_var("RUST_LOG", "debug");
-    }
-
-    // Parse the Terraform project
-    let project = match TerraformProject::parse_directory(Path::new(&cli.path)) {
-        Ok(project) => project,
-        Err(TfocusError::NoTerraformFiles) => {
-            eprintln!("Error: No Terraform files found in the current directory or its children.");
-            eprintln!("Please run this command from a directory containing Terraform files.");
-            std::process::exit(1);
-        }
-        Err(e) => return Err(e),
-    };
+/// Builds selector items for all directories, files, modules, resources and outputs
+///
+/// Directories are only offered when the files span more than one.
+/// With `--only`, just the chosen kinds of blocks are offered. Given the
+/// inventory of the previous run, changes since then are marked.
+fn build_select_items(
+    project: &TerraformProject,
+    cli: &Cli,
+    previous: Option<&Inventory>,
+) -> Result<Vec<SelectItem>> {
+    let kinds = cli.only_kinds();
+    let exclude = cli.exclude_targets()?;
 
     // Collect all targets
     let mut selection_items = Vec::new();
     let mut current_index = 1;
 
+    // add directories
+    let dirs = project.get_unique_dirs();
+    if kinds.is_empty() && dirs.len() > 1 {
+        for dir in dirs {
+            selection_items.push(SelectionItem::Directory(current_index, dir));
+            current_index += 1;
+        }
+    }
+
     // add files
-    for file in project.get_unique_files() {
-        selection_items.push(SelectionItem::File(current_index, file));
-        current_index += 1;
+    if kinds.is_empty() {
+        for file in project.get_unique_files() {
+            selection_items.push(SelectionItem::File(current_index, file));
+            current_index += 1;
+        }
     }
 
     // add modules
-    for module in project.get_modules() {
-        selection_items.push(SelectionItem::Module(current_index, module));
-        current_index += 1;
+    if kinds.is_empty() || kinds.contains(&ResourceKind::Module) {
+        for module in project.get_modules() {
+            selection_items.push(SelectionItem::Module(current_index, module));
+            current_index += 1;
+        }
     }
 
     // add resources
-    for resource in project.get_all_resources() {
-        selection_items.push(SelectionItem::Resource(current_index, resource));
+    for resource in project.get_resources_of_kinds(&kinds) {
+        if exclude
+            .as_ref()
+            .is_some_and(|exclude| resource.is_excluded(exclude))
+        {
+            continue;
+        }
+        selection_items.push(SelectionItem::Resource(current_index, Box::new(resource)));
         current_index += 1;
     }
 
+    // add outputs
+    if kinds.is_empty() {
+        for output in project.get_outputs() {
+            selection_items.push(SelectionItem::Output(current_index, output.name));
+            current_index += 1;
+        }
+    }
+
+    let changes = previous.map(|previous| inventory::diff(previous, &Inventory::capture(project)));
+    Ok(create_selection_items(
+        &selection_items,
+        cli.short_names,
+        changes.as_ref(),
+    ))
+}
+
+/// Parses the project and, with `--check-tainted`, marks tainted resources from the state
+///
+/// With `--from-plan`, the resources come from the plan's changes instead of the configuration.
+/// With `--only-dynamic` or `--only-static`, the other resources are dropped.
+fn load_project(cli: &Cli, options: &ParseOptions) -> Result<TerraformProject> {
+    let mut project = match &cli.from_plan {
+        Some(plan_path) => TerraformProject::from_resources(plan::load(
+            plan_path,
+            executor::project_dir(&cli.path),
+            options.quiet,
+        )?),
+        None => TerraformProject::parse_path(&cli.path, options)?,
+    };
+    if cli.check_tainted {
+        project.mark_tainted(&executor::tainted_addresses(&cli.path, cli)?);
+    }
+    project.retain_resources(|resource| {
+        cli.matches_dynamism(resource) && cli.matches_provider(resource)
+    });
+    Ok(project)
+}
+
+/// Loads the configuration on first use and installs its theme
+fn load_config<'a>(config: &'a OnceCell<Config>, cli: &Cli) -> Result<&'a Config> {
+    if let Some(config) = config.get() {
+        return Ok(config);
+    }
+    let loaded = Config::load(Path::new(&cli.path))?;
+    Theme::from_config(&loaded)?.install();
+    Ok(config.get_or_init(|| loaded))
+}
+
+/// Resource count above which parsing warns, unless configured otherwise
+const DEFAULT_RESOURCE_WARNING: usize = 5000;
+
+/// Warns when parsing found more resources than expected
+///
+/// `--resource-warning` takes precedence over `safety.resource_warning`;
+/// a threshold of 0 turns the warning off. A config file that cannot be loaded
+/// falls back to the default threshold; commands that need the config report it.
+fn warn_about_resource_count(
+    project: &TerraformProject,
+    cli: &Cli,
+    config: &OnceCell<Config>,
+) -> Result<()> {
+    let threshold = match cli.resource_warning {
+        Some(threshold) => threshold,
+        None => match load_config(config, cli) {
+            Ok(config) => config
+                .get_usize("safety.resource_warning")?
+                .unwrap_or(DEFAULT_RESOURCE_WARNING),
+            Err(_) => DEFAULT_RESOURCE_WARNING,
+        },
+    };
+    let count = project.resources_iter().count();
+    if threshold > 0 && count > threshold {
+        Display::print_warning(&format!(
+            "found {} resources under {}, more than the {} expected; check the scan path or mark \
+             stray files with '# tfocus:ignore' (--resource-warning 0 turns this off)",
+            count,
+            cli.path.display(),
+            threshold
+        ));
+    }
+    Ok(())
+}
+
+/// Runs the interactive selector over the project
+///
+/// Ctrl+R re-parses the project; the reparsed project replaces `project` so
+/// the selection resolves against the files as they are now. Also returns
+/// whether the selection was confirmed with Ctrl+S, to be saved as a focus set.
+fn select_target(
+    project: &mut TerraformProject,
+    cli: &Cli,
+    config: &Config,
+    previous: Option<&Inventory>,
+) -> Result<(Vec<Target>, bool)> {
+    let reloaded: Rc<RefCell<Option<TerraformProject>>> = Rc::new(RefCell::new(None));
+    let reload_slot = Rc::clone(&reloaded);
+    let reload_cli = cli.clone();
+    let reload_previous = previous.cloned();
+
     // Initialize and run the selector
-    let selector_items = create_selection_items(&selection_items);
-    let mut selector = Selector::new(selector_items);
+    let mut selector = Selector::new(build_select_items(project, cli, previous)?)
+        .with_multi_select()
+        .with_aliases(config.aliases())
+        .with_save()
+        .with_reload(move || {
+            let options = ParseOptions {
+                quiet: true,
+                ..reload_cli.parse_options()
+            };
+            let fresh = load_project(&reload_cli, &options)?;
+            let items = build_select_items(&fresh, &reload_cli, reload_previous.as_ref())?;
+            *reload_slot.borrow_mut() = Some(fresh);
+            Ok(items)
+        });
 
-    let selected = match selector.run()? {
-        Some(data) => data,
-        None => {
+    let result = selector.run();
+    if let Some(fresh) = reloaded.borrow_mut().take() {
+        *project = fresh;
+    }
+
+    let selected = match result? {
+        Selection::Selected(data) => vec![data],
+        Selection::SelectedMany(data) => data,
+        Selection::Back | Selection::Cancelled => {
             println!("\nOperation cancelled");
             std::process::exit(0);
         }
     };
 
-    // Analysis of the selected item
-    let target = if let Some(stripped) = selected.strip_prefix("f:") {
+    let targets = selected
+        .iter()
+        .map(|data| parse_selection(data))
+        .collect::<Result<_>>()?;
+    Ok((targets, selector.save_requested()))
+}
+
+/// Asks for a name and saves the resources as a focus set; an empty name skips saving
+fn save_focus_set(cli: &Cli, resources: &[Resource]) -> Result<()> {
+    let mut input = InputHandler::new()?;
+    let name = input.read_line("Save focus set as (empty to skip): ")?;
+    let name = name.trim();
+    if name.is_empty() {
+        return Ok(());
+    }
+    let path = focus_set::save(executor::project_dir(&cli.path), name, resources)?;
+    Display::print_success(&format!(
+        "Saved focus set '{}' ({} targets) to {}",
+        name,
+        resources.len(),
+        path.display()
+    ));
+    Ok(())
+}
+
+/// Converts selector item data back into a target
+fn parse_selection(selected: &str) -> Result<Target> {
+    if let Some(stripped) = selected.strip_prefix("f:") {
         let path = Path::new(stripped).to_path_buf();
-        Target::File(path)
+        Ok(Target::File(path))
+    } else if let Some(stripped) = selected.strip_prefix("p:") {
+        Ok(Target::Directory(Path::new(stripped).to_path_buf()))
     } else if let Some(stripped) = selected.strip_prefix("m:") {
-        Target::Module(stripped.to_string())
+        Ok(Target::Module(stripped.to_string()))
+    } else if let Some(stripped) = selected.strip_prefix("a:") {
+        Ok(Target::Address(stripped.to_string()))
+    } else if let Some(stripped) = selected.strip_prefix("o:") {
+        Ok(Target::Output(stripped.to_string()))
     } else if let Some(stripped) = selected.strip_prefix("r:") {
         let parts: Vec<&str> = stripped.split('.').collect();
         if parts.len() != 2 {
             return Err(TfocusError::InvalidTargetSelection);
         }
-        Target::Resource(parts[0].to_string(), parts[1].to_string())
+        Ok(Target::Resource(parts[0].to_string(), parts[1].to_string()))
+    } else if let Some(stripped) = selected.strip_prefix("d:") {
+        let parts: Vec<&str> = stripped.split('.').collect();
+        if parts.len() != 2 {
+            return Err(TfocusError::InvalidTargetSelection);
+        }
+        Ok(Target::Data(parts[0].to_string(), parts[1].to_string()))
     } else {
-        return Err(TfocusError::InvalidTargetSelection);
-    };
+        Err(TfocusError::InvalidTargetSelection)
+    }
+}
+
+fn main() {
+    let cli = Cli::parse();
+    logging::init(cli.log_format);
 
-    // Get the resources for the selected target
-    let resources = match &target {
-        Target::File(path) => project.get_resources_by_target(&Target::File(path.clone())),
-        Target::Module(name) => project.get_resources_by_target(&Target::Module(name.clone())),
-        Target::Resource(resource_type, name) => {
-            project.get_resources_by_target(&Target::Resource(resource_type.clone(), name.clone()))
+    if let Err(e) = run(&cli) {
+        report_error(&e, cli.format);
+        std::process::exit(e.exit_code());
+    }
+}
+
+/// Prints an error to stderr in the requested format
+fn report_error(error: &TfocusError, format: Format) {
+    match (format, error) {
+        (Format::Json, _) => match serde_json::to_string(error) {
+            Ok(json) => eprintln!("{}", json),
+            Err(_) => eprintln!("Error: {}", error),
+        },
+        (Format::Human, TfocusError::NoTerraformFiles) => {
+            eprintln!("Error: No Terraform files found in the current directory or its children.");
+            eprintln!("Please run this command from a directory containing Terraform files.");
         }
-    };
+        (Format::Human, _) => eprintln!("\nError: {}", error),
+    }
+}
+
+fn run(cli: &Cli) -> Result<()> {
+    if cli.verbose {
+        std::env::set_var("RUST_LOG", "debug");
+    }
+
+    if cli.color_disabled() {
+        colored::control::set_override(false);
+        Theme::plain().install();
+    }
+
+    let mut metrics = Metrics::new();
+    let mut scoped = None;
 
-    if resources.is_empty() {
-        println!("\nNo resources found for the selected target.");
+    // In the interactive flow, scope the run to one root when asked or when
+    // the path holds several roots
+    if cli.command.is_none()
+        && cli.list.is_none()
+        && !cli.count
+        && cli.graph.is_none()
+        && !cli.list_workspaces
+        && cli.generate_config_out.is_none()
+        && !cli.has_direct_targets()
+        && cli.from_plan.is_none()
+        && cli.path.is_dir()
+    {
+        let roots = metrics.time(Phase::Parse, || {
+            TerraformProject::find_roots(&cli.path, &cli.parse_options())
+        })?;
+        if roots.len() > 1 || (cli.root_picker && !roots.is_empty()) {
+            if let Some(root) = metrics.time(Phase::Selection, || select_root(&cli.path, &roots))? {
+                scoped = Some(Cli {
+                    path: root,
+                    ..cli.clone()
+                });
+            }
+        }
+    }
+
+    let result = run_in(scoped.as_ref().unwrap_or(cli), &mut metrics);
+    if cli.metrics {
+        metrics.print();
+    }
+    result
+}
+
+/// Lets the user pick one root, or all of them, before selecting resources
+///
+/// Returns `None` when "all roots" is chosen.
+fn select_root(path: &Path, roots: &[std::path::PathBuf]) -> Result<Option<std::path::PathBuf>> {
+    let mut items = vec![SelectItem {
+        display: format!("{:4} {:15} {}", 0, "[All]", path.display()),
+        search_text: "all".to_string(),
+        data: String::new(),
+        resource: None,
+        address: None,
+    }];
+    for (i, root) in roots.iter().enumerate() {
+        let rel = root
+            .strip_prefix(path)
+            .unwrap_or(root)
+            .display()
+            .to_string();
+        items.push(SelectItem {
+            display: format!("{:4} {:15} {}", i + 1, "[Root]", rel),
+            search_text: rel,
+            data: root.display().to_string(),
+            resource: None,
+            address: None,
+        });
+    }
+
+    match Selector::new(items).run()? {
+        Selection::Selected(data) if data.is_empty() => Ok(None),
+        Selection::Selected(data) => Ok(Some(std::path::PathBuf::from(data))),
+        Selection::SelectedMany(_) => Err(TfocusError::InvalidTargetSelection),
+        Selection::Back | Selection::Cancelled => {
+            println!("\nOperation cancelled");
+            std::process::exit(0);
+        }
+    }
+}
+
+/// Lets the user pick a workspace from `terraform workspace list`; the current one is marked
+fn select_workspace(cli: &Cli) -> Result<String> {
+    let items = executor::list_workspaces(&cli.path, cli)?
+        .into_iter()
+        .enumerate()
+        .map(|(i, workspace)| SelectItem {
+            display: format!(
+                "{:4} {:15} {}{}",
+                i + 1,
+                "[Workspace]",
+                workspace.name,
+                if workspace.current { "  (current)" } else { "" }
+            ),
+            search_text: workspace.name.clone(),
+            data: workspace.name,
+            resource: None,
+            address: None,
+        })
+        .collect();
+
+    match Selector::new(items).run()? {
+        Selection::Selected(name) => Ok(name),
+        Selection::SelectedMany(_) => Err(TfocusError::InvalidTargetSelection),
+        Selection::Back | Selection::Cancelled => {
+            println!("\nOperation cancelled");
+            std::process::exit(0);
+        }
+    }
+}
+
+fn run_in(cli: &Cli, metrics: &mut Metrics) -> Result<()> {
+    // Loaded on first use, so a broken config file does not stop --count, --list or audit
+    let config = OnceCell::new();
+
+    if cli.list_workspaces {
+        for workspace in executor::list_workspaces(&cli.path, cli)? {
+            let mark = if workspace.current { "*" } else { " " };
+            println!("{} {}", mark, workspace.name);
+        }
+        return Ok(());
+    }
+    if let Some(name) = &cli.workspace {
+        let name = match name {
+            Some(name) => name.clone(),
+            None => metrics.time(Phase::Selection, || select_workspace(cli))?,
+        };
+        executor::select_workspace(&cli.path, &name, cli)?;
+    }
+
+    if cli.count && cli.command.is_none() {
+        let kinds = cli.only_kinds();
+        let filter = cli.filter_regex()?;
+        let count = metrics.time(Phase::Parse, || -> Result<usize> {
+            Ok(match &cli.from_plan {
+                // Counting needs no project in memory, so stream the resources file by file
+                None => {
+                    let mut count = 0;
+                    for resource in TerraformProject::stream_path(&cli.path, &cli.parse_options())?
+                    {
+                        let resource = resource?;
+                        if resource_matches(&resource, &kinds, filter.as_ref())
+                            && cli.matches_dynamism(&resource)
+                            && cli.matches_provider(&resource)
+                        {
+                            count += 1;
+                        }
+                    }
+                    count
+                }
+                Some(_) => load_project(cli, &cli.parse_options())?
+                    .resources_iter()
+                    .filter(|r| resource_matches(r, &kinds, filter.as_ref()))
+                    .count(),
+            })
+        })?;
+        println!("{}", count);
         return Ok(());
     }
 
+    // Parse the Terraform project
+    let mut project = metrics.time(Phase::Parse, || load_project(cli, &cli.parse_options()))?;
+    if cli.from_plan.is_none() {
+        warn_about_resource_count(&project, cli, &config)?;
+    }
+
+    if let Some(Command::Audit) = cli.command {
+        let findings = audit::audit(&project)?;
+        audit::print_report(&findings);
+        if !findings.is_empty() {
+            return Err(TfocusError::AuditFailed(findings.len()));
+        }
+        return Ok(());
+    }
+
+    if let Some(Command::Providers) = cli.command {
+        let usages = providers::collect(&project, &cli.parse_options())?;
+        providers::print_table(&usages, executor::project_dir(&cli.path));
+        return Ok(());
+    }
+
+    if let Some(Command::Orphans) = cli.command {
+        let state = executor::state_addresses(&cli.path, cli)?;
+        orphans::print_orphans(&orphans::find_orphans(&project, &state));
+        return Ok(());
+    }
+
+    if let Some(out) = &cli.generate_config_out {
+        metrics.time(Phase::Terraform, || {
+            executor::generate_config(
+                &project.unconfigured_imports(),
+                out,
+                cli,
+                load_config(&config, cli)?,
+            )
+        })?;
+        return Ok(());
+    }
+
+    let filter = cli.filter_regex()?;
+    if let Some(GraphFormat::Dot) = cli.graph {
+        let matching = project.get_matching_resources(&cli.only_kinds(), filter.as_ref());
+        print!("{}", graph::render_dot(&matching));
+        return Ok(());
+    }
+
+    match cli.list {
+        Some(ListKind::Resources) => {
+            Display::print_resource_list(
+                &project.get_matching_resources(&cli.only_kinds(), filter.as_ref()),
+                !cli.no_header,
+            );
+            return Ok(());
+        }
+        Some(ListKind::Json) => {
+            println!(
+                "{}",
+                Display::resource_json(
+                    &project.get_matching_resources(&cli.only_kinds(), filter.as_ref()),
+                    cli.json_pretty,
+                )?
+            );
+            return Ok(());
+        }
+        Some(ListKind::Addresses) => {
+            for address in project.get_state_addresses(&cli.only_kinds(), filter.as_ref())? {
+                println!("{}", address);
+            }
+            return Ok(());
+        }
+        Some(ListKind::Files) => {
+            let root = if cli.path.is_file() {
+                cli.path.parent().unwrap_or(Path::new(""))
+            } else {
+                cli.path.as_path()
+            };
+            Display::print_file_list(&project.get_unique_files(), root);
+            return Ok(());
+        }
+        None => {}
+    }
+
+    let config = load_config(&config, cli)?;
+    let policy = match &cli.policy {
+        Some(path) => Policy::load(path)?,
+        None => Policy::default(),
+    };
+    let for_each_keys = cli.keys.as_deref().map(keys::load).transpose()?;
+    let also_targets = cli
+        .also_target_file
+        .as_deref()
+        .map(target_file::load)
+        .transpose()?
+        .unwrap_or_default();
+
+    let previous = track_changes(&project, cli)?;
+
+    let mut stage = Stage::SelectTarget;
+    loop {
+        stage = match stage {
+            Stage::SelectTarget => {
+                let (mut targets, save) = match (&cli.set, cli.direct_target(), cli.select_first) {
+                    (Some(name), _, _) => (
+                        focus_set::load(executor::project_dir(&cli.path), name)?
+                            .into_iter()
+                            .map(Target::Address)
+                            .collect(),
+                        false,
+                    ),
+                    (None, Some(target), _) => (vec![target], false),
+                    (None, None, Some(count)) => (
+                        project
+                            .first_resources(count)?
+                            .iter()
+                            .map(|resource| Target::Address(resource.target_string()))
+                            .collect(),
+                        false,
+                    ),
+                    (None, None, None) => metrics.time(Phase::Selection, || {
+                        select_target(&mut project, cli, config, previous.as_ref())
+                    })?,
+                };
+                if let [Target::Output(name)] = targets.as_slice() {
+                    return executor::show_output(project.get_output(name)?, cli);
+                }
+                // Resolving drops resources already selected, so the union has no duplicates
+                targets.extend(also_targets.iter().cloned().map(Target::Address));
+                let mut resources = resolve_targets(&project, &targets, for_each_keys.as_deref())?;
+                if let Some(exclude) = cli.exclude_targets()? {
+                    let before = resources.len();
+                    resources.retain(|resource| !resource.is_excluded(&exclude));
+                    if resources.is_empty() {
+                        return Err(TfocusError::ConfigError(
+                            "--exclude-target excludes every selected resource".to_string(),
+                        ));
+                    }
+                    if resources.len() < before {
+                        Display::print_warning(&format!(
+                            "--exclude-target left out {} of {} resources",
+                            before - resources.len(),
+                            before
+                        ));
+                    }
+                }
+                // Before either emit path, so emitted commands obey the policy too
+                policy.check(&resources)?;
+                if cli.emit_makefile {
+                    let operations = match cli.preset_operations()? {
+                        operations if operations.is_empty() => vec![Operation::Plan],
+                        operations => operations,
+                    };
+                    print!(
+                        "{}",
+                        executor::makefile(&resources, &operations, cli, config)?
+                    );
+                    return Ok(());
+                }
+                if cli.print_target_args {
+                    println!(
+                        "{}",
+                        executor::target_args(&resources, cli.reverse, config)?
+                    );
+                    return Ok(());
+                }
+                print_selection(&project, &resources);
+                if save {
+                    save_focus_set(cli, &resources)?;
+                }
+                Stage::SelectOperation(resources)
+            }
+            Stage::SelectOperation(resources) => match cli.preset_operations()? {
+                operations if !operations.is_empty() => Stage::Execute(resources, operations),
+                _ => match metrics.time(Phase::Selection, executor::select_operation)? {
+                    Some(operation) => Stage::Execute(resources, vec![operation]),
+                    // Without the selector there is no earlier stage to go back to
+                    None if cli.has_direct_targets() => {
+                        println!("\nOperation cancelled");
+                        std::process::exit(0);
+                    }
+                    None => Stage::SelectTarget,
+                },
+            },
+            Stage::Execute(resources, operations) => {
+                let requirements = project.get_version_requirements();
+                let pinned = version::read_pinned(executor::project_dir(&cli.path))?;
+                let show_context = cli.context && !cli.quiet;
+                let installed = if !requirements.is_empty() || pinned.is_some() || show_context {
+                    metrics.time(Phase::Terraform, || executor::terraform_version(&cli.path))
+                } else {
+                    None
+                };
+                match installed {
+                    Some(installed) => {
+                        version::check(installed, requirements, cli.strict_version)?;
+                        if let Some(pinned) = &pinned {
+                            version::check_pinned(
+                                installed,
+                                pinned,
+                                cli.strict_version,
+                                cli.quiet,
+                            )?;
+                        }
+                    }
+                    None => log::debug!("Could not read the terraform version"),
+                }
+                if show_context {
+                    let workspace = metrics.time(Phase::Terraform, || {
+                        executor::current_workspace(&cli.path, cli)
+                    });
+                    print_context(&project, installed, workspace.as_deref());
+                }
+                if cli.validate {
+                    metrics.time(Phase::Terraform, || executor::validate(&resources, cli))?;
+                }
+                if cli.validate_targets {
+                    metrics.time(Phase::Terraform, || {
+                        executor::validate_targets(&resources, cli)
+                    })?;
+                }
+                if operations.contains(&Operation::Apply) && !cli.allow_dirty {
+                    git::ensure_committed(executor::project_dir(&cli.path), &cli.parse_options())?;
+                }
+                // Later operations run only after the user has seen the previous result
+                executor::run_sequence(
+                    &resources,
+                    &operations,
+                    executor::confirm_next_operation,
+                    |operation, resources| {
+                        if operation == Operation::Destroy
+                            && project.is_full_selection(resources)
+                            && !executor::confirm_full_destroy(Path::new(&cli.path))?
+                        {
+                            println!("\nDestroy cancelled");
+                            return Ok(false);
+                        }
+                        let outcome = metrics.time(Phase::Terraform, || {
+                            executor::execute_with_resources(resources, operation, cli, config)
+                        });
+                        // A cancelled run is neither a success nor a failure, so no hook runs
+                        match &outcome {
+                            Ok(true) => {
+                                hook::after_operation(cli, operation, resources.len(), true)
+                            }
+                            Ok(false) => {}
+                            Err(_) => hook::after_operation(cli, operation, resources.len(), false),
+                        }
+                        outcome
+                    },
+                )?;
+                return Ok(());
+            }
+        };
+    }
+}
+
+/// Resolves the selected targets to their resources, in selection order
+///
+/// With `--keys`, for_each resources are expanded into one instance per key.
+fn resolve_targets(
+    project: &TerraformProject,
+    targets: &[Target],
+    for_each_keys: Option<&[String]>,
+) -> Result<Vec<Resource>> {
+    let mut resources: Vec<Resource> = Vec::new();
+    for target in targets {
+        if let Target::Output(name) = target {
+            Display::print_warning(&format!(
+                "skipping output '{}': outputs cannot be targeted",
+                name
+            ));
+            continue;
+        }
+
+        // Get the resources for the selected target
+        for resource in project.get_resources_by_target(target)? {
+            if !resources.contains(&resource) {
+                resources.push(resource);
+            }
+        }
+    }
+
+    if let Some(for_each_keys) = for_each_keys {
+        resources = keys::expand(resources, for_each_keys);
+    }
+    Ok(resources)
+}
+
+/// With `--since-last-run`, reports changes since the previous run and saves this run's inventory
+///
+/// Returns the previous inventory, from which the selector marks the changes.
+fn track_changes(project: &TerraformProject, cli: &Cli) -> Result<Option<Inventory>> {
+    if !cli.since_last_run {
+        return Ok(None);
+    }
+    let dir = executor::project_dir(&cli.path);
+    let previous = inventory::load(dir)?;
+    let current = Inventory::capture(project);
+    // Output meant for other tools stays clean
+    if !cli.quiet && !cli.print_target_args && !cli.emit_makefile {
+        match &previous {
+            Some(previous) => print_changes(&inventory::diff(previous, &current)),
+            None => println!(
+                "No previous inventory in {}; changes will be marked from the next run\n",
+                inventory::INVENTORY_PATH
+            ),
+        }
+    }
+    inventory::save(dir, &current)?;
+    Ok(previous)
+}
+
+/// Prints how many resources are new, removed or in changed files, and lists the removed ones
+fn print_changes(changes: &InventoryDiff) {
+    Display::print_header("Since last run:");
+    println!(
+        "  {} new, {} removed, {} changed files",
+        changes.added.len(),
+        changes.removed.len(),
+        changes.changed_files.len()
+    );
+    for address in &changes.removed {
+        println!("  - {}", address);
+    }
+    println!();
+}
+
+/// Prints where the operation is about to run: terraform version, backend and workspace
+fn print_context(project: &TerraformProject, installed: Option<Version>, workspace: Option<&str>) {
+    Display::print_header("Context:");
+    println!(
+        "  terraform: {}",
+        installed.map_or_else(|| "unknown".to_string(), |v| v.to_string())
+    );
+    match project.get_backends() {
+        [] => println!("  backend:   local (no backend configured)"),
+        backends => {
+            for backend in backends {
+                println!(
+                    "  backend:   {} ({})",
+                    backend.backend_type,
+                    backend.file_path.display()
+                );
+            }
+        }
+    }
+    println!("  workspace: {}", workspace.unwrap_or("unknown"));
+    println!();
+}
+
+/// Prints the selected resources and warns about imports pending for them
+fn print_selection(project: &TerraformProject, resources: &[Resource]) {
     Display::print_header("\nSelected resources:");
-    for resource in &resources {
+    for resource in resources {
         Display::print_resource(resource);
     }
-
     println!();
-    // Execute the selected resources
-    executor::execute_with_resources(&resources)
+
+    for import in project.pending_imports(resources) {
+        Display::print_warning(&format!(
+            "import pending for {} (id: {}) in {}",
+            import.to,
+            import.id,
+            import.file_path.display()
+        ));
+    }
 }
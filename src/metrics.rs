@@ -0,0 +1,90 @@
+use std::time::{Duration, Instant};
+
+/// Phases of a run timed by `--metrics`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Parse,
+    Selection,
+    Terraform,
+}
+
+/// Local timing of a run's phases; nothing is sent anywhere
+#[derive(Debug)]
+pub struct Metrics {
+    started: Instant,
+    parse: Duration,
+    selection: Duration,
+    terraform: Duration,
+}
+
+impl Metrics {
+    /// Starts timing the run
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            parse: Duration::ZERO,
+            selection: Duration::ZERO,
+            terraform: Duration::ZERO,
+        }
+    }
+
+    /// Runs `f`, adding its duration to `phase`
+    pub fn time<T>(&mut self, phase: Phase, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = f();
+        let elapsed = start.elapsed();
+        match phase {
+            Phase::Parse => self.parse += elapsed,
+            Phase::Selection => self.selection += elapsed,
+            Phase::Terraform => self.terraform += elapsed,
+        }
+        result
+    }
+
+    /// Prints the timing table to stderr, keeping stdout clean for piping
+    pub fn print(&self) {
+        eprint!("{}", self.render(self.started.elapsed()));
+    }
+
+    /// Renders the timing table
+    fn render(&self, total: Duration) -> String {
+        let mut table = String::from("\nMetrics:\n");
+        for (label, duration) in [
+            ("parse", self.parse),
+            ("selection", self.selection),
+            ("terraform", self.terraform),
+            ("total", total),
+        ] {
+            table.push_str(&format!(
+                "  {:<10} {:>9.3}s\n",
+                label,
+                duration.as_secs_f64()
+            ));
+        }
+        table
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_accumulates_phases() {
+        let mut metrics = Metrics::new();
+        let value = metrics.time(Phase::Parse, || 42);
+        assert_eq!(value, 42);
+        metrics.parse = Duration::from_millis(12);
+        metrics.time(Phase::Terraform, || ());
+        metrics.terraform = Duration::from_millis(1500);
+
+        assert_eq!(
+            metrics.render(Duration::from_millis(2000)),
+            "\nMetrics:\n  \
+             parse          0.012s\n  \
+             selection      0.000s\n  \
+             terraform      1.500s\n  \
+             total          2.000s\n"
+        );
+    }
+}
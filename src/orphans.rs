@@ -0,0 +1,106 @@
+use colored::*;
+use std::collections::HashSet;
+
+use crate::project::TerraformProject;
+
+/// Returns the state addresses that no block in the configuration declares
+///
+/// Instance keys are ignored, so `aws_instance.web[0]` belongs to
+/// `aws_instance.web`. Anything under `module.NAME` belongs to the module call;
+/// tfocus does not look inside modules, so only calls that are gone count.
+pub fn find_orphans(project: &TerraformProject, state: &[String]) -> Vec<String> {
+    let mut declared = HashSet::new();
+    let mut modules = HashSet::new();
+    for resource in project.resources_iter() {
+        if resource.is_module {
+            modules.insert(resource.name.as_str());
+        } else {
+            declared.insert(resource.full_name());
+        }
+    }
+
+    let mut orphans: Vec<String> = state
+        .iter()
+        .filter(|address| match address.strip_prefix("module.") {
+            Some(rest) => {
+                let name = rest.split(['.', '[']).next().unwrap_or(rest);
+                !modules.contains(name)
+            }
+            None => !declared.contains(address.split('[').next().unwrap_or(address)),
+        })
+        .cloned()
+        .collect();
+    orphans.sort();
+    orphans.dedup();
+    orphans
+}
+
+/// Prints orphaned addresses one per line, or a note when there are none
+pub fn print_orphans(orphans: &[String]) {
+    if orphans.is_empty() {
+        println!(
+            "{} No orphaned addresses in the state",
+            "Orphans:".green().bold()
+        );
+        return;
+    }
+    for address in orphans {
+        println!("{}", address);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::ParseOptions;
+
+    #[test]
+    fn test_find_orphans() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("main.tf"),
+            r#"
+resource "aws_instance" "web" {
+  count = 2
+}
+
+data "aws_ami" "ubuntu" {
+}
+
+module "vpc" {
+  source = "./vpc"
+}
+"#,
+        )
+        .unwrap();
+        let options = ParseOptions {
+            quiet: true,
+            ..ParseOptions::default()
+        };
+        let project = TerraformProject::parse_path(dir.path(), &options).unwrap();
+
+        let state: Vec<String> = [
+            "aws_instance.web[0]",
+            "aws_instance.web[1]",
+            "aws_instance.server[0]",
+            "data.aws_ami.ubuntu",
+            r#"module.vpc["a"].aws_subnet.a"#,
+            "module.vpc.aws_subnet.b",
+            "module.legacy.aws_subnet.a",
+            "aws_s3_bucket.logs",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+
+        assert_eq!(
+            find_orphans(&project, &state),
+            vec![
+                "aws_instance.server[0]",
+                "aws_s3_bucket.logs",
+                "module.legacy.aws_subnet.a",
+            ]
+        );
+        assert!(find_orphans(&project, &[]).is_empty());
+    }
+}
@@ -0,0 +1,178 @@
+use log::warn;
+use std::fs;
+use std::path::Path;
+
+use crate::display::Display;
+use crate::error::{Result, TfocusError};
+use crate::types::{PlanAction, Resource};
+
+/// Loads the changed resources from `terraform show -json <planfile>` output
+///
+/// Resources are placed in `working_dir`, where terraform runs for them.
+/// Changes with no action (`no-op`) are left out. Unless `quiet`, a warning
+/// names each change skipped for an invalid address.
+pub fn load(plan_path: &Path, working_dir: &Path, quiet: bool) -> Result<Vec<Resource>> {
+    let content = fs::read_to_string(plan_path)?;
+    let file_name = plan_path.file_name().unwrap_or(plan_path.as_os_str());
+    let resources = parse(&content, &working_dir.join(file_name), quiet)
+        .map_err(|e| TfocusError::ParseError(format!("{}: {}", plan_path.display(), e)))?;
+    if resources.is_empty() {
+        return Err(TfocusError::ParseError(format!(
+            "{}: the plan has no resource changes",
+            plan_path.display()
+        )));
+    }
+    Ok(resources)
+}
+
+/// Parses the `resource_changes` of a JSON plan into resources
+fn parse(
+    content: &str,
+    file_path: &Path,
+    quiet: bool,
+) -> std::result::Result<Vec<Resource>, String> {
+    let root: serde_json::Value =
+        serde_json::from_str(content).map_err(|e| format!("invalid JSON: {}", e))?;
+    let Some(changes) = root.get("resource_changes").and_then(|v| v.as_array()) else {
+        return Ok(Vec::new());
+    };
+
+    let mut resources = Vec::new();
+    for change in changes {
+        let field = |name: &str| change.get(name).and_then(|v| v.as_str());
+        let (Some(address), Some(resource_type), Some(name)) =
+            (field("address"), field("type"), field("name"))
+        else {
+            return Err("resource change without address, type or name".to_string());
+        };
+
+        let actions: Vec<&str> = change
+            .pointer("/change/actions")
+            .and_then(|v| v.as_array())
+            .map(|actions| actions.iter().filter_map(|a| a.as_str()).collect())
+            .unwrap_or_default();
+        let action = match actions.as_slice() {
+            ["create"] => PlanAction::Create,
+            ["update"] => PlanAction::Update,
+            ["delete"] => PlanAction::Delete,
+            ["delete", "create"] | ["create", "delete"] => PlanAction::Replace,
+            ["read"] => PlanAction::Read,
+            ["no-op"] => continue,
+            _ => {
+                warn!("Skipping {}: unsupported actions {:?}", address, actions);
+                continue;
+            }
+        };
+
+        let index = change.get("index").and_then(|index| match index {
+            serde_json::Value::String(key) => Some((false, key.clone())),
+            serde_json::Value::Number(n) => Some((true, n.to_string())),
+            _ => None,
+        });
+
+        let resource = Resource {
+            resource_type: resource_type.to_string(),
+            name: name.to_string(),
+            is_module: false,
+            is_data: field("mode") == Some("data"),
+            file_path: file_path.to_path_buf(),
+            has_count: matches!(index, Some((true, _))),
+            has_for_each: matches!(index, Some((false, _))),
+            index: index.map(|(_, index)| index),
+            line: 0,
+            tainted: false,
+            depends_on: Vec::new(),
+            source: None,
+            // `registry.terraform.io/hashicorp/aws` is the aws provider
+            provider: field("provider_name")
+                .and_then(|name| name.rsplit('/').next())
+                .map(str::to_string),
+            providers: Vec::new(),
+            module_address: field("module_address").map(str::to_string),
+            planned_action: Some(action),
+        };
+        if !resource.is_valid_address() {
+            if !quiet {
+                Display::print_warning(&format!(
+                    "skipping {}: not a valid Terraform identifier",
+                    address
+                ));
+            }
+            continue;
+        }
+        resources.push(resource);
+    }
+    Ok(resources)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLAN: &str = r#"{
+  "format_version": "1.2",
+  "resource_changes": [
+    {"address": "aws_instance.web[0]", "mode": "managed", "type": "aws_instance",
+     "name": "web", "index": 0, "change": {"actions": ["create"]}},
+    {"address": "module.vpc.aws_subnet.private[\"a\"]", "module_address": "module.vpc",
+     "mode": "managed", "type": "aws_subnet", "name": "private", "index": "a",
+     "change": {"actions": ["delete", "create"]}},
+    {"address": "aws_s3_bucket.logs", "mode": "managed", "type": "aws_s3_bucket",
+     "name": "logs", "change": {"actions": ["no-op"]}},
+    {"address": "data.aws_ami.ubuntu", "mode": "data", "type": "aws_ami",
+     "name": "ubuntu", "change": {"actions": ["read"]}},
+    {"address": "aws_iam_role.old", "mode": "managed", "type": "aws_iam_role",
+     "name": "old", "change": {"actions": ["delete"]}}
+  ]
+}"#;
+
+    #[test]
+    fn test_parse_plan_resource_changes() {
+        let resources = parse(PLAN, Path::new("envs/prod/plan.json"), true).unwrap();
+        let summary: Vec<(String, PlanAction)> = resources
+            .iter()
+            .map(|r| (r.target_string(), r.planned_action.unwrap()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("aws_instance.web[0]".to_string(), PlanAction::Create),
+                (
+                    r#"module.vpc.aws_subnet.private["a"]"#.to_string(),
+                    PlanAction::Replace
+                ),
+                ("data.aws_ami.ubuntu".to_string(), PlanAction::Read),
+                ("aws_iam_role.old".to_string(), PlanAction::Delete),
+            ]
+        );
+        assert!(resources[2].is_data);
+        assert_eq!(resources[0].location(), "envs/prod/plan.json");
+    }
+
+    #[test]
+    fn test_parse_plan_errors() {
+        assert!(parse("not json", Path::new("plan.json"), true).is_err());
+        assert!(parse(
+            r#"{"resource_changes": [{"address": "x"}]}"#,
+            Path::new("plan.json"),
+            true
+        )
+        .is_err());
+        assert!(parse("{}", Path::new("plan.json"), true)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_parse_plan_skips_malformed_addresses() {
+        let plan = r#"{"resource_changes": [
+    {"address": "aws_instance.web", "mode": "managed", "type": "aws_instance",
+     "name": "web", "change": {"actions": ["create"]}},
+    {"address": "aws_instance.1bad", "mode": "managed", "type": "aws_instance",
+     "name": "1bad", "change": {"actions": ["create"]}}
+  ]}"#;
+        let resources = parse(plan, Path::new("plan.json"), true).unwrap();
+        assert_eq!(resources.len(), 1);
+        assert_eq!(resources[0].target_string(), "aws_instance.web");
+    }
+}
@@ -0,0 +1,163 @@
+use globset::{Glob, GlobMatcher};
+use std::fs;
+use std::path::Path;
+
+use crate::error::{Result, TfocusError};
+use crate::types::Resource;
+
+/// Whether a policy rule permits or forbids matching targets
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Action {
+    Allow,
+    Deny,
+}
+
+/// A single `allow <pattern>` or `deny <pattern>` line
+#[derive(Debug, Clone)]
+struct Rule {
+    action: Action,
+    pattern: String,
+    matcher: GlobMatcher,
+}
+
+impl Rule {
+    fn describe(&self) -> String {
+        match self.action {
+            Action::Allow => format!("allow {}", self.pattern),
+            Action::Deny => format!("deny {}", self.pattern),
+        }
+    }
+}
+
+/// Target address rules loaded from a `--policy` file
+///
+/// Each line is `allow <pattern>` or `deny <pattern>`, where the pattern is a glob
+/// over target addresses (e.g. `aws_db_instance.*`, `module.network*`). A target
+/// matching any deny rule is refused. When allow rules exist, every target must
+/// match one of them.
+#[derive(Debug, Clone, Default)]
+pub struct Policy {
+    rules: Vec<Rule>,
+}
+
+impl Policy {
+    /// Loads a policy file
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)?;
+        Self::parse(&content)
+            .map_err(|e| TfocusError::ConfigError(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Parses policy content; blank lines and `#` comments are ignored
+    pub fn parse(content: &str) -> std::result::Result<Self, String> {
+        let mut rules = Vec::new();
+        for (i, raw) in content.lines().enumerate() {
+            let line = raw.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let (action, pattern) = match line.split_once(char::is_whitespace) {
+                Some(("allow", pattern)) => (Action::Allow, pattern.trim()),
+                Some(("deny", pattern)) => (Action::Deny, pattern.trim()),
+                _ => {
+                    return Err(format!(
+                        "line {}: expected `allow <pattern>` or `deny <pattern>`",
+                        i + 1
+                    ))
+                }
+            };
+            let matcher = Glob::new(pattern)
+                .map_err(|e| format!("line {}: {}", i + 1, e))?
+                .compile_matcher();
+
+            rules.push(Rule {
+                action,
+                pattern: pattern.to_string(),
+                matcher,
+            });
+        }
+        Ok(Self { rules })
+    }
+
+    /// Checks the selected resources, naming the violated rule on failure
+    pub fn check(&self, resources: &[Resource]) -> Result<()> {
+        let has_allow_rules = self.rules.iter().any(|r| r.action == Action::Allow);
+
+        for resource in resources {
+            let address = resource.target_string();
+            if let Some(rule) = self
+                .rules
+                .iter()
+                .find(|r| r.action == Action::Deny && r.matcher.is_match(&address))
+            {
+                return Err(TfocusError::PolicyViolation {
+                    target: address,
+                    rule: rule.describe(),
+                });
+            }
+
+            if has_allow_rules
+                && !self
+                    .rules
+                    .iter()
+                    .any(|r| r.action == Action::Allow && r.matcher.is_match(&address))
+            {
+                return Err(TfocusError::PolicyViolation {
+                    target: address,
+                    rule: "no allow rule matches".to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resource(resource_type: &str, name: &str, is_module: bool) -> Resource {
+        Resource {
+            is_module,
+            ..Resource::for_test(resource_type, name)
+        }
+    }
+
+    #[test]
+    fn test_deny_rule_refuses_matching_target() {
+        let policy = Policy::parse("# guardrails\ndeny aws_db_instance.*\n").unwrap();
+        assert!(policy
+            .check(&[resource("aws_instance", "web", false)])
+            .is_ok());
+
+        let err = policy
+            .check(&[resource("aws_db_instance", "main", false)])
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Policy forbids targeting aws_db_instance.main (deny aws_db_instance.*)"
+        );
+    }
+
+    #[test]
+    fn test_allow_rules_restrict_targets() {
+        let policy = Policy::parse("allow module.*\nallow aws_instance.*\ndeny module.db").unwrap();
+        assert!(policy
+            .check(&[
+                resource("", "network", true),
+                resource("aws_instance", "web", false)
+            ])
+            .is_ok());
+        assert!(policy.check(&[resource("", "db", true)]).is_err());
+        assert!(policy
+            .check(&[resource("aws_s3_bucket", "logs", false)])
+            .is_err());
+    }
+
+    #[test]
+    fn test_parse_rejects_unknown_action() {
+        assert!(Policy::parse("forbid aws_db_instance.*").is_err());
+        assert!(Policy::parse("deny").is_err());
+    }
+}
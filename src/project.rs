@@ -1,128 +1,878 @@
-use log::debug;
+use crossterm::{
+    cursor, execute, style,
+    terminal::{self, ClearType},
+};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use log::{debug, warn};
 use regex::Regex;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 use std::fs;
+use std::io::IsTerminal;
 use std::path::{Path, PathBuf};
 
+use crate::display::Display;
 use crate::error::{Result, TfocusError};
-use crate::types::{Resource, Target};
+use crate::types::{
+    split_instance_key, Address, BackendConfig, ImportBlock, OutputBlock, ProviderRequirement,
+    Resource, ResourceKind, Target, VersionRequirement,
+};
+
+/// Options controlling how a project is discovered and parsed
+#[derive(Debug, Default, Clone)]
+pub struct ParseOptions {
+    /// Do not print the list of discovered files
+    pub quiet: bool,
+    /// Also discover OpenTofu files (`.tofu`, `.tofu.json`) and JSON configuration (`.tf.json`)
+    pub opentofu: bool,
+    /// Do not descend into git submodules listed in `.gitmodules` at the scan root
+    pub skip_submodules: bool,
+    /// Order files, and the resources in them, by modification time, newest first
+    pub recent_first: bool,
+    /// Leave files that declare no targets out of the discovered-file listing
+    pub hide_empty_files: bool,
+}
+
+impl ParseOptions {
+    /// Returns true if the path is a configuration file tfocus should parse
+    pub fn is_config_file(&self, path: &Path) -> bool {
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy())
+            .unwrap_or_default();
+        name.ends_with(".tf")
+            || (self.opentofu
+                && (name.ends_with(".tf.json")
+                    || name.ends_with(".tofu")
+                    || name.ends_with(".tofu.json")))
+    }
+}
 
 /// Represents a Terraform project with its resources
 pub struct TerraformProject {
+    files: Vec<PathBuf>,
     resources: Vec<Resource>,
+    imports: Vec<ImportBlock>,
+    outputs: Vec<OutputBlock>,
+    version_requirements: Vec<VersionRequirement>,
+    provider_requirements: Vec<ProviderRequirement>,
+    backends: Vec<BackendConfig>,
+    /// Whether `files` is in most-recently-modified order rather than alphabetical
+    recent_first: bool,
+    /// Warnings about blocks left out while parsing, in the order found
+    warnings: Vec<String>,
 }
 
 impl TerraformProject {
     /// Creates a new empty TerraformProject
     pub fn new() -> Self {
         Self {
+            files: Vec::new(),
             resources: Vec::new(),
+            imports: Vec::new(),
+            outputs: Vec::new(),
+            version_requirements: Vec::new(),
+            provider_requirements: Vec::new(),
+            backends: Vec::new(),
+            recent_first: false,
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Creates a project from resources read elsewhere, such as a plan file
+    pub fn from_resources(resources: Vec<Resource>) -> Self {
+        Self {
+            resources,
+            ..Self::new()
         }
     }
 
     /// Recursively finds all Terraform files in the given directory
-    fn find_terraform_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    ///
+    /// Files are sorted by path, or newest first with `recent_first`. In
+    /// OpenTofu mode, `x.tofu` shadows `x.tf` (and `x.tofu.json` shadows
+    /// `x.tf.json`) in the same directory, as OpenTofu itself does. Git
+    /// submodules are ordinary directories and are scanned unless
+    /// `skip_submodules` is set.
+    fn find_terraform_files(root: &Path, options: &ParseOptions) -> Result<Vec<PathBuf>> {
+        let excluded = if options.skip_submodules {
+            gitmodule_paths(root)?
+        } else {
+            Vec::new()
+        };
+
         let mut tf_files = Vec::new();
+        Self::collect_terraform_files(root, options, &excluded, &mut tf_files)?;
+
+        if options.opentofu {
+            let shadowed: Vec<PathBuf> = tf_files
+                .iter()
+                .filter_map(|path| {
+                    let name = path.file_name()?.to_string_lossy();
+                    let tf_name = match name.strip_suffix(".tofu.json") {
+                        Some(stem) => format!("{}.tf.json", stem),
+                        None => format!("{}.tf", name.strip_suffix(".tofu")?),
+                    };
+                    Some(path.with_file_name(tf_name))
+                })
+                .collect();
+            tf_files.retain(|path| !shadowed.contains(path));
+        }
+
+        if options.recent_first {
+            tf_files.sort_by_cached_key(|path| {
+                let modified = fs::metadata(path).and_then(|m| m.modified()).ok();
+                (std::cmp::Reverse(modified), path.clone())
+            });
+        } else {
+            tf_files.sort();
+        }
 
+        Ok(tf_files)
+    }
+
+    /// Adds the Terraform files under `dir` to `tf_files`, skipping `excluded` directories
+    ///
+    /// Subdirectories that cannot be read for lack of permission are skipped with
+    /// a warning; only an unreadable `dir` itself is an error. Unreadable files are
+    /// skipped when parsed, see [`TerraformProject::parse_found_file`].
+    fn collect_terraform_files(
+        dir: &Path,
+        options: &ParseOptions,
+        excluded: &[PathBuf],
+        tf_files: &mut Vec<PathBuf>,
+    ) -> Result<()> {
         for entry in fs::read_dir(dir).map_err(TfocusError::Io)? {
-            let entry = entry.map_err(TfocusError::Io)?;
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(e) if is_permission_denied(&e) => {
+                    warn!("Skipping an entry of {:?}: {}", dir, e);
+                    continue;
+                }
+                Err(e) => return Err(TfocusError::Io(e)),
+            };
             let path = entry.path();
 
             if path.is_file() {
-                if path.extension().is_some_and(|ext| ext == "tf")
-                    && !path.to_string_lossy().contains("/.terraform/")
-                {
+                if options.is_config_file(&path) {
                     tf_files.push(path);
                 }
             } else if path.is_dir()
-                && !path.to_string_lossy().contains("/.terraform/")
-                && !path.to_string_lossy().contains("/.git/")
+                && !matches!(entry.file_name().to_str(), Some(".terraform" | ".git"))
+                && !excluded.contains(&path)
             {
-                tf_files.extend(Self::find_terraform_files(&path)?);
+                match Self::collect_terraform_files(&path, options, excluded, tf_files) {
+                    Err(TfocusError::Io(e)) if is_permission_denied(&e) => {
+                        warn!("Skipping {:?}: {}", path, e)
+                    }
+                    result => result?,
+                }
             }
         }
+        Ok(())
+    }
 
-        Ok(tf_files)
+    /// Finds the Terraform root modules under `path`
+    ///
+    /// A directory counts as a root when it has been initialized (`.terraform/`
+    /// or `.terraform.lock.hcl`) or one of its files declares a backend or cloud
+    /// block. Roots are returned sorted.
+    pub fn find_roots(path: &Path, options: &ParseOptions) -> Result<Vec<PathBuf>> {
+        let backend_regex = Regex::new(r#"(?m)^\s*(backend\s+"[^"]+"|cloud)\s*\{"#)
+            .map_err(TfocusError::RegexError)?;
+
+        let mut dirs: Vec<PathBuf> = Self::find_terraform_files(path, options)?
+            .iter()
+            .filter_map(|file| file.parent().map(Path::to_path_buf))
+            .collect();
+        dirs.sort();
+        dirs.dedup();
+
+        let mut roots = Vec::new();
+        for dir in dirs {
+            let initialized =
+                dir.join(".terraform").is_dir() || dir.join(".terraform.lock.hcl").is_file();
+            let has_backend = || -> Result<bool> {
+                for entry in fs::read_dir(&dir).map_err(TfocusError::Io)? {
+                    let file = entry.map_err(TfocusError::Io)?.path();
+                    if options.is_config_file(&file) && backend_regex.is_match(&read_config(&file)?)
+                    {
+                        return Ok(true);
+                    }
+                }
+                Ok(false)
+            };
+            if initialized || has_backend()? {
+                roots.push(dir);
+            }
+        }
+        Ok(roots)
+    }
+
+    /// Parses a Terraform directory, or a single Terraform file
+    pub fn parse_path(path: &Path, options: &ParseOptions) -> Result<Self> {
+        check_path(path)?;
+        if path.is_file() {
+            Self::parse_single_file(path, options)
+        } else {
+            Self::parse_directory(path, options)
+        }
+    }
+
+    /// Parses files lazily, yielding each file's resources before reading the next
+    ///
+    /// Only one file's resources are held at a time, so consumers that fold over
+    /// the resources (e.g. counting them) never materialize the whole project.
+    /// Resources come in file order rather than sorted.
+    pub fn stream_path(path: &Path, options: &ParseOptions) -> Result<ResourceStream> {
+        check_path(path)?;
+        let files = if path.is_file() {
+            if !options.is_config_file(path) {
+                return Err(TfocusError::NoTerraformFiles);
+            }
+            vec![path.to_path_buf()]
+        } else {
+            Self::find_terraform_files(path, options)?
+        };
+        if files.is_empty() {
+            return Err(empty_directory_error(path));
+        }
+
+        Ok(ResourceStream {
+            files: files.into_iter(),
+            pending: Vec::new().into_iter(),
+            quiet: options.quiet,
+        })
+    }
+
+    /// Parses a single Terraform file into a project
+    pub fn parse_single_file(path: &Path, options: &ParseOptions) -> Result<Self> {
+        if !options.is_config_file(path) {
+            return Err(TfocusError::NoTerraformFiles);
+        }
+
+        let mut project = TerraformProject::new();
+        if !options.quiet {
+            println!("\nFound Terraform file:");
+            println!("  {}", path.display());
+            println!();
+        }
+
+        project.parse_file(path)?;
+        project.print_warnings(0, options.quiet);
+        Ok(project)
     }
 
     /// Parses a directory containing Terraform files
-    pub fn parse_directory(path: &Path) -> Result<Self> {
+    pub fn parse_directory(path: &Path, options: &ParseOptions) -> Result<Self> {
         let mut project = TerraformProject::new();
+        project.recent_first = options.recent_first;
 
-        let tf_files = Self::find_terraform_files(path)?;
+        let tf_files = Self::find_terraform_files(path, options)?;
         if tf_files.is_empty() {
-            return Err(TfocusError::NoTerraformFiles);
+            return Err(empty_directory_error(path));
         }
 
-        println!("\nFound Terraform files:");
-        for file in &tf_files {
-            if let Ok(rel_path) = file.strip_prefix(path) {
-                println!("  {}", rel_path.display());
-            } else {
-                println!("  {}", file.display());
+        if !options.quiet && !options.hide_empty_files {
+            print_found_files(path, &tf_files, 0);
+        }
+
+        let show_progress = !options.quiet && std::io::stderr().is_terminal();
+        let total = tf_files.len();
+        for (i, file_path) in tf_files.iter().enumerate() {
+            if show_progress {
+                print_progress(i + 1, total);
+            }
+            project.parse_found_file(file_path, options.quiet)?;
+        }
+        if show_progress {
+            clear_progress();
+        }
+        let reported = project.warnings.len();
+        for cycle in project.module_source_cycles() {
+            let dirs: Vec<String> = cycle.iter().map(|d| d.display().to_string()).collect();
+            project.warnings.push(format!(
+                "circular module sources, not followed further: {}",
+                dirs.join(" -> ")
+            ));
+        }
+        project.print_warnings(reported, options.quiet);
+
+        // Which files are empty is only known once they are parsed
+        if !options.quiet && options.hide_empty_files {
+            let empty = project.files_without_targets();
+            let listed: Vec<PathBuf> = tf_files
+                .into_iter()
+                .filter(|file| !empty.contains(file))
+                .collect();
+            print_found_files(path, &listed, empty.len());
+        }
+
+        Ok(project)
+    }
+
+    /// Finds cycles among local module sources, each as the directories along it
+    ///
+    /// Each directory is visited once, so the walk ends at the first repeated
+    /// directory of a cycle such as `a -> b -> a` instead of looping.
+    pub fn module_source_cycles(&self) -> Vec<Vec<PathBuf>> {
+        let mut edges: BTreeMap<PathBuf, BTreeSet<PathBuf>> = BTreeMap::new();
+        for (from, to) in self.local_module_sources() {
+            edges.entry(from).or_default().insert(to);
+        }
+
+        fn visit<'a>(
+            dir: &'a PathBuf,
+            edges: &'a BTreeMap<PathBuf, BTreeSet<PathBuf>>,
+            path: &mut Vec<&'a PathBuf>,
+            done: &mut HashSet<&'a PathBuf>,
+            cycles: &mut Vec<Vec<PathBuf>>,
+        ) {
+            if let Some(start) = path.iter().position(|d| *d == dir) {
+                let mut cycle: Vec<PathBuf> = path[start..].iter().map(|d| (*d).clone()).collect();
+                cycle.push(dir.clone());
+                cycles.push(cycle);
+                return;
+            }
+            if !done.insert(dir) {
+                return;
+            }
+            path.push(dir);
+            for next in edges.get(dir).into_iter().flatten() {
+                visit(next, edges, path, done, cycles);
             }
+            path.pop();
         }
-        println!();
 
-        for file_path in tf_files {
-            project.parse_file(&file_path)?;
+        let mut cycles = Vec::new();
+        let mut done = HashSet::new();
+        for dir in edges.keys() {
+            visit(dir, &edges, &mut Vec::new(), &mut done, &mut cycles);
         }
+        cycles
+    }
+
+    /// Returns each module call with a local `source` as its calling and source directories
+    ///
+    /// Both directories are canonicalized; sources that do not exist are left out.
+    pub fn local_module_sources(&self) -> Vec<(PathBuf, PathBuf)> {
+        self.resources
+            .iter()
+            .filter(|r| r.is_module)
+            .filter_map(|module| {
+                let source = module
+                    .source
+                    .as_deref()
+                    .filter(|s| s.starts_with("./") || s.starts_with("../"))?;
+                let dir = module.file_path.parent().unwrap_or(Path::new("."));
+                Some((
+                    fs::canonicalize(dir).ok()?,
+                    fs::canonicalize(dir.join(source)).ok()?,
+                ))
+            })
+            .collect()
+    }
+
+    /// Parses the configuration files directly in a module directory
+    ///
+    /// Unlike [`TerraformProject::parse_directory`], subdirectories are not
+    /// scanned, as terraform reads a module from its own directory only.
+    pub fn parse_module_dir(dir: &Path, options: &ParseOptions) -> Result<Self> {
+        let mut files: Vec<PathBuf> = fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.is_file() && options.is_config_file(path))
+            .collect();
+        files.sort();
 
+        let mut project = TerraformProject::new();
+        for file in &files {
+            project.parse_file(file)?;
+        }
+        project.print_warnings(0, options.quiet);
         Ok(project)
     }
 
+    /// Returns the parsed files that declare no module, resource or data source
+    ///
+    /// Such files (e.g. a `backend.tf` with only a `terraform` block) are still
+    /// parsed for version requirements, imports and outputs.
+    fn files_without_targets(&self) -> Vec<PathBuf> {
+        self.files
+            .iter()
+            .filter(|file| !self.resources.iter().any(|r| &r.file_path == *file))
+            .cloned()
+            .collect()
+    }
+
+    /// Parses a file found by scanning a directory
+    ///
+    /// A file that cannot be read, e.g. for lack of permission, is skipped with a
+    /// warning instead of failing the whole scan.
+    fn parse_found_file(&mut self, path: &Path, quiet: bool) -> Result<()> {
+        let reported = self.warnings.len();
+        let result = self.parse_file(path);
+        self.print_warnings(reported, quiet);
+        match result {
+            // Reading the file is the only I/O in parse_file
+            Err(TfocusError::Io(e)) => {
+                if !quiet {
+                    Display::print_warning(&format!("skipping {}: {}", path.display(), e));
+                }
+                Ok(())
+            }
+            result => result,
+        }
+    }
+
+    /// Prints the warnings from index `from` on, unless `quiet`
+    fn print_warnings(&self, from: usize, quiet: bool) {
+        if quiet {
+            return;
+        }
+        for warning in &self.warnings[from..] {
+            Display::print_warning(warning);
+        }
+    }
+
     /// Parses a single Terraform file for resources and modules
+    ///
+    /// Files whose leading comments include `# tfocus:ignore` are skipped.
     fn parse_file(&mut self, path: &Path) -> Result<()> {
-        let content = fs::read_to_string(path).map_err(TfocusError::Io)?;
-        debug!("Parsing file: {:?}", path);
-
-        // Parse resources with improved regex pattern
-        let resource_regex =
-            Regex::new(r#"(?m)^\s*resource\s+"([^"]+)"\s+"([^"]+)"\s*\{(?s:.*?)\n\s*\}"#)
-                .map_err(TfocusError::RegexError)?;
-
-        for cap in resource_regex.captures_iter(&content) {
-            let full_block = cap.get(0).unwrap().as_str();
-            let has_count = full_block.contains("count =") || full_block.contains("count=");
-            let has_for_each =
-                full_block.contains("for_each =") || full_block.contains("for_each=");
-
-            self.resources.push(Resource {
-                resource_type: cap[1].to_string(),
-                name: cap[2].to_string(),
-                is_module: false,
+        let (content, invalid_utf8_at) = read_config_lossy(path)?;
+        if has_ignore_header(&content) {
+            debug!(file:% = path.display(); "Skipping file with tfocus:ignore header: {:?}", path);
+            return Ok(());
+        }
+        if let Some(offset) = invalid_utf8_at {
+            self.warnings.push(format!(
+                "{}: invalid UTF-8 at byte {}, reading it with replacement characters",
+                path.display(),
+                offset
+            ));
+        }
+        debug!(file:% = path.display(); "Parsing file: {:?}", path);
+        self.files.push(path.to_owned());
+
+        if path.extension().is_some_and(|ext| ext == "json") {
+            return self.parse_json_file(path, &content);
+        }
+
+        let arguments = BlockArgumentsParser::new()?;
+
+        // Parse resources and data sources. Indentation may use spaces or tabs,
+        // and the opening brace may sit on the line after the labels. The block
+        // body runs to the matching closing brace, which may be on the same line.
+        for (keyword, is_data) in [("resource", false), ("data", true)] {
+            let header_regex = Regex::new(&format!(
+                r#"(?m)^[ \t]*{}\s+"([^"]+)"\s+"([^"]+)"\s*\{{"#,
+                keyword
+            ))
+            .map_err(TfocusError::RegexError)?;
+
+            for cap in header_regex.captures_iter(&content) {
+                let mut resource = Resource {
+                    resource_type: cap[1].to_string(),
+                    name: cap[2].to_string(),
+                    is_module: false,
+                    is_data,
+                    file_path: path.to_owned(),
+                    has_count: false,
+                    has_for_each: false,
+                    index: None,
+                    tainted: false,
+                    depends_on: Vec::new(),
+                    module_address: None,
+                    planned_action: None,
+                    source: None,
+                    provider: None,
+                    providers: Vec::new(),
+                    line: line_number(&content, cap.get(1).unwrap().start()),
+                };
+                // Checked before the body, so a malformed header is reported as such
+                if !resource.is_valid_address() {
+                    self.warnings.push(format!(
+                        "skipping {} \"{}\" \"{}\" at {}:{}: not a valid Terraform identifier",
+                        keyword,
+                        &cap[1],
+                        &cap[2],
+                        path.display(),
+                        resource.line
+                    ));
+                    continue;
+                }
+                let Some(full_block) = block_text(&content, cap.get(0).unwrap()) else {
+                    warn!(
+                        "Skipping {} \"{}\" \"{}\" in {:?}: block is not closed",
+                        keyword, &cap[1], &cap[2], path
+                    );
+                    continue;
+                };
+                resource.has_count = arguments.has_count(full_block);
+                resource.has_for_each = arguments.has_for_each(full_block);
+                resource.depends_on = arguments.depends_on(full_block);
+                resource.provider = arguments.resource_provider(full_block);
+                self.resources.push(resource);
+            }
+        }
+
+        for (keyword, resource_type, line) in unnamed_blocks(&content)? {
+            self.warnings.push(format!(
+                "skipping {} \"{}\" at {}:{}: block has no name label",
+                keyword,
+                resource_type,
+                path.display(),
+                line
+            ));
+        }
+
+        // Parse modules, with the same whitespace rules as resources
+        let module_regex =
+            Regex::new(r#"(?m)^[ \t]*module\s+"([^"]+)"\s*\{"#).map_err(TfocusError::RegexError)?;
+
+        for cap in module_regex.captures_iter(&content) {
+            let mut module = Resource {
+                resource_type: String::new(),
+                name: cap[1].to_string(),
+                is_module: true,
+                is_data: false,
                 file_path: path.to_owned(),
-                has_count,
-                has_for_each,
+                has_count: false,
+                has_for_each: false,
                 index: None,
+                tainted: false,
+                depends_on: Vec::new(),
+                module_address: None,
+                planned_action: None,
+                source: None,
+                provider: None,
+                providers: Vec::new(),
+                line: line_number(&content, cap.get(1).unwrap().start()),
+            };
+            if !module.is_valid_address() {
+                self.warnings.push(format!(
+                    "skipping module \"{}\" at {}:{}: not a valid Terraform identifier",
+                    &cap[1],
+                    path.display(),
+                    module.line
+                ));
+                continue;
+            }
+            let Some(full_block) = block_text(&content, cap.get(0).unwrap()) else {
+                warn!(
+                    "Skipping module \"{}\" in {:?}: block is not closed",
+                    &cap[1], path
+                );
+                continue;
+            };
+            module.has_count = arguments.has_count(full_block);
+            module.has_for_each = arguments.has_for_each(full_block);
+            module.depends_on = arguments.depends_on(full_block);
+            module.source = arguments.module_source(full_block);
+            module.providers = arguments.module_providers(full_block);
+            self.resources.push(module);
+        }
+
+        // Parse output blocks
+        let output_regex =
+            Regex::new(r#"(?m)^[ \t]*output\s+"([^"]+)"\s*\{"#).map_err(TfocusError::RegexError)?;
+
+        for cap in output_regex.captures_iter(&content) {
+            self.outputs.push(OutputBlock {
+                name: cap[1].to_string(),
+                file_path: path.to_owned(),
+                line: line_number(&content, cap.get(1).unwrap().start()),
             });
         }
 
-        // Parse modules with improved regex pattern
-        let module_regex = Regex::new(r#"(?m)^\s*module\s+"([^"]+)"\s*\{(?s:.*?)\n\s*\}"#)
+        // Parse required_version and the backend from terraform blocks
+        let terraform_regex =
+            Regex::new(r#"(?m)^[ \t]*terraform\s*\{"#).map_err(TfocusError::RegexError)?;
+        let required_version_regex = Regex::new(r#"(?:^|[\s{])required_version\s*=\s*"([^"]*)""#)
+            .map_err(TfocusError::RegexError)?;
+        let backend_regex = Regex::new(r#"(?m)^\s*(?:backend\s+"([^"]+)"|(cloud))\s*\{"#)
             .map_err(TfocusError::RegexError)?;
+        let required_providers = RequiredProvidersParser::new()?;
 
-        for cap in module_regex.captures_iter(&content) {
-            let full_block = cap.get(0).unwrap().as_str();
-            let has_count = full_block.contains("count =") || full_block.contains("count=");
-            let has_for_each =
-                full_block.contains("for_each =") || full_block.contains("for_each=");
+        for header in terraform_regex.find_iter(&content) {
+            let Some(block) = block_text(&content, header) else {
+                continue;
+            };
+            if let Some(cap) = required_version_regex.captures(block) {
+                self.version_requirements.push(VersionRequirement {
+                    constraint: cap[1].to_string(),
+                    file_path: path.to_owned(),
+                });
+            }
+            self.provider_requirements
+                .extend(required_providers.parse(block, path));
+            if let Some(cap) = backend_regex.captures(block) {
+                let backend_type = cap.get(1).or(cap.get(2)).unwrap().as_str();
+                self.backends.push(BackendConfig {
+                    backend_type: backend_type.to_string(),
+                    file_path: path.to_owned(),
+                });
+            }
+        }
+
+        // Parse import blocks (Terraform 1.5+), through the matching closing brace
+        let import_regex =
+            Regex::new(r#"(?m)^[ \t]*import\s*\{"#).map_err(TfocusError::RegexError)?;
+        let to_regex = Regex::new(r#"(?m)^\s*to\s*=\s*(\S+)"#).map_err(TfocusError::RegexError)?;
+        let id_regex =
+            Regex::new(r#"(?m)^\s*id\s*=\s*(.+?)\s*$"#).map_err(TfocusError::RegexError)?;
+
+        for header in import_regex.find_iter(&content) {
+            let Some(block) = block_text(&content, header) else {
+                continue;
+            };
+            let Some(to) = to_regex.captures(block) else {
+                continue;
+            };
+            let id = id_regex
+                .captures(block)
+                .map(|c| c[1].trim_matches('"').to_string())
+                .unwrap_or_default();
+
+            self.imports.push(ImportBlock {
+                to: to[1].to_string(),
+                id,
+                file_path: path.to_owned(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Parses a JSON configuration file (`.tf.json`, `.tofu.json`)
+    fn parse_json_file(&mut self, path: &Path, content: &str) -> Result<()> {
+        let root: serde_json::Value = serde_json::from_str(content).map_err(|e| {
+            TfocusError::ParseError(format!("{}: invalid JSON: {}", path.display(), e))
+        })?;
+        let line_of = |label: &str| {
+            content
+                .find(&format!("\"{}\"", label))
+                .map_or(1, |pos| line_number(content, pos))
+        };
+
+        for (keyword, is_data) in [("resource", false), ("data", true)] {
+            for (resource_type, name, body) in json_labeled_blocks(&root, keyword, 2) {
+                let resource = Resource {
+                    line: line_of(&name),
+                    resource_type: resource_type.unwrap_or_default(),
+                    name,
+                    is_module: false,
+                    is_data,
+                    file_path: path.to_owned(),
+                    has_count: body.get("count").is_some(),
+                    has_for_each: body.get("for_each").is_some(),
+                    index: None,
+                    tainted: false,
+                    depends_on: json_depends_on(body),
+                    module_address: None,
+                    planned_action: None,
+                    source: None,
+                    provider: body
+                        .get("provider")
+                        .and_then(|v| v.as_str())
+                        .map(str::to_string),
+                    providers: Vec::new(),
+                };
+                if !resource.is_valid_address() {
+                    self.warnings.push(format!(
+                        "skipping {} \"{}\" \"{}\" at {}:{}: not a valid Terraform identifier",
+                        keyword,
+                        resource.resource_type,
+                        resource.name,
+                        path.display(),
+                        resource.line
+                    ));
+                    continue;
+                }
+                self.resources.push(resource);
+            }
+        }
 
-            self.resources.push(Resource {
+        for (_, name, body) in json_labeled_blocks(&root, "module", 1) {
+            let module = Resource {
                 resource_type: String::new(),
-                name: cap[1].to_string(),
+                line: line_of(&name),
+                name,
                 is_module: true,
+                is_data: false,
                 file_path: path.to_owned(),
-                has_count,
-                has_for_each,
+                has_count: body.get("count").is_some(),
+                has_for_each: body.get("for_each").is_some(),
                 index: None,
+                tainted: false,
+                depends_on: json_depends_on(body),
+                module_address: None,
+                planned_action: None,
+                source: body
+                    .get("source")
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string),
+                provider: None,
+                providers: json_module_providers(body),
+            };
+            if !module.is_valid_address() {
+                self.warnings.push(format!(
+                    "skipping module \"{}\" at {}:{}: not a valid Terraform identifier",
+                    module.name,
+                    path.display(),
+                    module.line
+                ));
+                continue;
+            }
+            self.resources.push(module);
+        }
+
+        let terraform = root.get("terraform").map(json_block_body);
+        if let Some(constraint) = terraform
+            .and_then(|body| body.get("required_version"))
+            .and_then(|v| v.as_str())
+        {
+            self.version_requirements.push(VersionRequirement {
+                constraint: constraint.to_string(),
+                file_path: path.to_owned(),
+            });
+        }
+        let required_providers = terraform
+            .and_then(|body| body.get("required_providers"))
+            .map(json_block_body)
+            .and_then(|providers| providers.as_object());
+        for (name, requirement) in required_providers.into_iter().flatten() {
+            let field = |key: &str| {
+                requirement
+                    .get(key)
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string)
+            };
+            self.provider_requirements.push(ProviderRequirement {
+                name: name.clone(),
+                source: field("source"),
+                // Before Terraform 0.13, an entry was just the version constraint
+                version: requirement
+                    .as_str()
+                    .map(str::to_string)
+                    .or(field("version")),
+                file_path: path.to_owned(),
+            });
+        }
+        let backend_type = terraform.and_then(|body| {
+            body.get("backend")
+                .map(json_block_body)
+                .and_then(|backend| backend.as_object())
+                .and_then(|backend| backend.keys().next().cloned())
+                .or_else(|| body.get("cloud").map(|_| "cloud".to_string()))
+        });
+        if let Some(backend_type) = backend_type {
+            self.backends.push(BackendConfig {
+                backend_type,
+                file_path: path.to_owned(),
+            });
+        }
+
+        for (_, name, _) in json_labeled_blocks(&root, "output", 1) {
+            self.outputs.push(OutputBlock {
+                line: line_of(&name),
+                name,
+                file_path: path.to_owned(),
             });
         }
 
         Ok(())
     }
 
-    /// Returns a list of unique file paths
+    /// Returns the `required_version` constraints of all `terraform` blocks
+    pub fn get_version_requirements(&self) -> &[VersionRequirement] {
+        &self.version_requirements
+    }
+
+    /// Returns the `required_providers` entries of all `terraform` blocks
+    pub fn get_provider_requirements(&self) -> &[ProviderRequirement] {
+        &self.provider_requirements
+    }
+
+    /// Returns the `backend` and `cloud` blocks of all `terraform` blocks
+    pub fn get_backends(&self) -> &[BackendConfig] {
+        &self.backends
+    }
+
+    /// Returns all output blocks sorted by name
+    pub fn get_outputs(&self) -> Vec<OutputBlock> {
+        let mut outputs = self.outputs.clone();
+        outputs.sort_by(|a, b| a.name.cmp(&b.name));
+        outputs
+    }
+
+    /// Returns the output block with the given name
+    pub fn get_output(&self, name: &str) -> Result<&OutputBlock> {
+        self.outputs
+            .iter()
+            .find(|o| o.name == name)
+            .ok_or_else(|| TfocusError::NoMatchingTarget {
+                target: Target::Output(name.to_string()).to_string(),
+                suggestions: suggest_similar(
+                    name,
+                    &self
+                        .outputs
+                        .iter()
+                        .map(|o| o.name.clone())
+                        .collect::<Vec<_>>(),
+                ),
+            })
+    }
+
+    /// Drops the resources for which `keep` returns false
+    ///
+    /// Files, modules and targets are derived from the remaining resources, so
+    /// this narrows the whole project, e.g. for `--only-dynamic`.
+    pub fn retain_resources(&mut self, keep: impl FnMut(&Resource) -> bool) {
+        self.resources.retain(keep);
+    }
+
+    /// Marks the resources with a tainted instance in the state
+    ///
+    /// Addresses come from the state (e.g. `aws_instance.web[0]`); an address
+    /// inside a module marks the module block.
+    pub fn mark_tainted(&mut self, addresses: &[String]) {
+        for address in addresses {
+            let address = address.split('[').next().unwrap_or(address);
+            let module = address
+                .strip_prefix("module.")
+                .map(|rest| rest.split('.').next().unwrap_or(rest));
+
+            for resource in &mut self.resources {
+                let matches = match module {
+                    Some(name) => resource.is_module && resource.name == name,
+                    None => !resource.is_module && resource.full_name() == address,
+                };
+                if matches {
+                    resource.tainted = true;
+                }
+            }
+        }
+    }
+
+    /// Returns import blocks whose `to` address is one of the given resources
+    pub fn pending_imports(&self, resources: &[Resource]) -> Vec<&ImportBlock> {
+        self.imports
+            .iter()
+            .filter(|import| resources.iter().any(|r| import.targets(r)))
+            .collect()
+    }
+
+    /// Returns the import blocks whose `to` address has no block in the configuration
+    ///
+    /// These are the imports `plan -generate-config-out` writes configuration for.
+    pub fn unconfigured_imports(&self) -> Vec<&ImportBlock> {
+        self.imports
+            .iter()
+            .filter(|import| !self.resources.iter().any(|r| import.targets(r)))
+            .collect()
+    }
+
+    /// Returns a list of unique file paths, sorted by path or, with
+    /// `recent_first`, newest first
     pub fn get_unique_files(&self) -> Vec<PathBuf> {
         let mut files: HashSet<PathBuf> = HashSet::new();
         for resource in &self.resources {
@@ -130,9 +880,37 @@ impl TerraformProject {
         }
         let mut files: Vec<_> = files.into_iter().collect();
         files.sort();
+        if self.recent_first {
+            files.sort_by_key(|file| self.file_rank(file));
+        }
         files
     }
 
+    /// Returns the directories holding the project's files, sorted by path
+    pub fn get_unique_dirs(&self) -> Vec<PathBuf> {
+        let mut dirs: Vec<PathBuf> = self
+            .get_unique_files()
+            .iter()
+            .filter_map(|file| file.parent().map(Path::to_path_buf))
+            .collect();
+        dirs.sort();
+        dirs.dedup();
+        dirs
+    }
+
+    /// Returns the position of a file in discovery order
+    fn file_rank(&self, file: &Path) -> usize {
+        self.files
+            .iter()
+            .position(|f| f == file)
+            .unwrap_or(usize::MAX)
+    }
+
+    /// Returns every parsed file, including files without any blocks
+    pub fn get_parsed_files(&self) -> &[PathBuf] {
+        &self.files
+    }
+
     /// Returns a list of module names
     pub fn get_modules(&self) -> Vec<String> {
         let mut modules: Vec<String> = self
@@ -156,87 +934,939 @@ impl TerraformProject {
                 b.is_module.cmp(&a.is_module)
             }
         });
+        if self.recent_first {
+            // Stable, so each file keeps the order above
+            resources.sort_by_key(|r| self.file_rank(&r.file_path));
+        }
         resources
     }
 
-    /// Returns resources matching the specified target
-    pub fn get_resources_by_target(&self, target: &Target) -> Vec<Resource> {
-        match target {
-            Target::File(path) => self
-                .resources
-                .iter()
-                .filter(|r| &r.file_path == path)
-                .cloned()
-                .collect(),
-            Target::Module(module_name) => self
-                .resources
-                .iter()
-                .filter(|r| r.is_module && &r.name == module_name)
-                .cloned()
-                .collect(),
-            Target::Resource(resource_type, name) => self
-                .resources
-                .iter()
-                .filter(|r| !r.is_module && &r.resource_type == resource_type && &r.name == name)
-                .cloned()
-                .collect(),
-        }
+    /// Iterates over the resources in declaration order without copying them
+    pub fn resources_iter(&self) -> impl Iterator<Item = &Resource> + '_ {
+        self.resources.iter()
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use tempfile::NamedTempFile;
 
-    #[test]
-    fn test_parse_resource_with_count() {
-        let mut project = TerraformProject::new();
-        let content = r#"
-        resource "aws_instance" "web" {
-          count = 2
-          ami = "ami-123456"
-          instance_type = "t2.micro"
+    /// Returns the first `count` resources in the order of [`TerraformProject::get_all_resources`]
+    ///
+    /// Modules count as resources. Fails when `count` is 0 or more than the project has.
+    pub fn first_resources(&self, count: usize) -> Result<Vec<Resource>> {
+        let mut resources = self.get_all_resources();
+        if count == 0 || count > resources.len() {
+            return Err(TfocusError::SelectionOutOfRange {
+                requested: count,
+                available: resources.len(),
+            });
         }
-        "#;
+        resources.truncate(count);
+        Ok(resources)
+    }
 
-        let mut temp_file = NamedTempFile::new().unwrap();
-        std::io::Write::write_all(&mut temp_file, content.as_bytes()).unwrap();
+    /// Returns all resources of the given kinds; an empty slice means all kinds
+    pub fn get_resources_of_kinds(&self, kinds: &[ResourceKind]) -> Vec<Resource> {
+        self.get_all_resources()
+            .into_iter()
+            .filter(|r| kinds.is_empty() || kinds.contains(&r.kind()))
+            .collect()
+    }
 
-        project.parse_file(temp_file.path()).unwrap();
+    /// Returns the resources of the given kinds whose address matches `filter`
+    pub fn get_matching_resources(
+        &self,
+        kinds: &[ResourceKind],
+        filter: Option<&Regex>,
+    ) -> Vec<Resource> {
+        self.get_all_resources()
+            .into_iter()
+            .filter(|r| resource_matches(r, kinds, filter))
+            .collect()
+    }
 
-        let resources = project.get_all_resources();
-        assert_eq!(resources.len(), 1, "Expected exactly one resource");
-        assert!(resources[0].has_count, "Resource should have count");
-        assert!(
-            !resources[0].has_for_each,
-            "Resource should not have for_each"
-        );
+    /// Returns the addresses of matching resources as `terraform state list` prints them
+    ///
+    /// Addresses are sorted and unique, for_each keys are quoted and count
+    /// ranges expanded. Module calls are skipped since they have no state
+    /// address of their own.
+    pub fn get_state_addresses(
+        &self,
+        kinds: &[ResourceKind],
+        filter: Option<&Regex>,
+    ) -> Result<Vec<String>> {
+        let mut addresses = Vec::new();
+        for resource in self.get_matching_resources(kinds, filter) {
+            if !resource.is_module {
+                addresses.extend(resource.target_strings()?);
+            }
+        }
+        addresses.sort();
+        addresses.dedup();
+        Ok(addresses)
     }
 
-    #[test]
-    fn test_parse_resource_with_for_each() {
-        let mut project = TerraformProject::new();
-        let content = r#"
-        resource "aws_instance" "web" {
-          for_each = toset(["a", "b"])
-          ami = "ami-123456"
-          instance_type = "t2.micro"
+    /// Returns whether `resources` covers every module and managed resource
+    ///
+    /// Data sources are ignored since destroying them has no effect.
+    pub fn is_full_selection(&self, resources: &[Resource]) -> bool {
+        let selected: HashSet<String> = resources.iter().map(|r| r.full_name()).collect();
+        let mut managed = self
+            .resources
+            .iter()
+            .filter(|r| r.kind() != ResourceKind::Data)
+            .peekable();
+        managed.peek().is_some() && managed.all(|r| selected.contains(&r.full_name()))
+    }
+
+    /// Returns the block at `address` inside a module call of this project,
+    /// e.g. `module.vpc.aws_subnet.a`
+    ///
+    /// Blocks inside modules are not parsed, so the block is built from its
+    /// address and located at the module call.
+    fn module_resource(&self, address: &str) -> Option<Resource> {
+        let address = Address::parse(address)?;
+        let module_path = address.module_path?;
+        let root_call = module_path
+            .strip_prefix("module.")?
+            .split(['.', '['])
+            .next()?;
+        let call = self
+            .resources
+            .iter()
+            .find(|r| r.is_module && r.module_address.is_none() && r.name == root_call)?;
+        let block = Resource {
+            resource_type: address.resource_type.to_string(),
+            name: address.name.to_string(),
+            is_module: address.is_module,
+            is_data: address.is_data,
+            file_path: call.file_path.clone(),
+            has_count: address.key.is_some_and(|key| !key.starts_with('"')),
+            has_for_each: address.key.is_some_and(|key| key.starts_with('"')),
+            index: None,
+            tainted: false,
+            depends_on: Vec::new(),
+            module_address: Some(module_path.to_string()),
+            planned_action: None,
+            source: None,
+            provider: None,
+            providers: Vec::new(),
+            line: call.line,
+        };
+        match address.key {
+            Some(key) => block.instance(key),
+            None => Some(block),
         }
-        "#;
+    }
 
-        let mut temp_file = NamedTempFile::new().unwrap();
-        std::io::Write::write_all(&mut temp_file, content.as_bytes()).unwrap();
+    /// Returns resources matching the specified target
+    ///
+    /// Fails with [`TfocusError::NoMatchingTarget`] when nothing matches,
+    /// suggesting similarly named targets where possible.
+    pub fn get_resources_by_target(&self, target: &Target) -> Result<Vec<Resource>> {
+        let (matches, candidates): (Vec<Resource>, Vec<String>) = match target {
+            Target::File(path) => (
+                self.resources
+                    .iter()
+                    .filter(|r| &r.file_path == path)
+                    .cloned()
+                    .collect(),
+                self.get_unique_files()
+                    .iter()
+                    .map(|p| p.display().to_string())
+                    .collect(),
+            ),
+            Target::Directory(dir) => (
+                self.resources
+                    .iter()
+                    .filter(|r| in_directory(&r.file_path, dir))
+                    .cloned()
+                    .collect(),
+                self.get_unique_dirs()
+                    .iter()
+                    .map(|p| p.display().to_string())
+                    .collect(),
+            ),
+            Target::Module(module_name) => (
+                self.resources
+                    .iter()
+                    .filter(|r| r.is_module && &r.name == module_name)
+                    .cloned()
+                    .collect(),
+                self.get_modules(),
+            ),
+            // Outputs are not resources and cannot be passed to -target
+            Target::Output(_) => (Vec::new(), Vec::new()),
+            Target::FileGlob(pattern) => {
+                let matcher = file_glob_matcher(pattern)?;
+                (
+                    self.resources
+                        .iter()
+                        .filter(|r| {
+                            matcher.is_match(r.file_path.strip_prefix("./").unwrap_or(&r.file_path))
+                        })
+                        .cloned()
+                        .collect(),
+                    Vec::new(),
+                )
+            }
+            Target::Prefix(prefix) => (
+                self.resources
+                    .iter()
+                    .filter(|r| {
+                        has_address_prefix(&r.full_name(), prefix)
+                            || has_address_prefix(&r.target_string(), prefix)
+                    })
+                    .cloned()
+                    .collect(),
+                self.resources.iter().map(|r| r.full_name()).collect(),
+            ),
+            Target::LineRange(path, start, end) => (
+                self.resources
+                    .iter()
+                    .filter(|r| (*start..=*end).contains(&r.line) && same_file(&r.file_path, path))
+                    .cloned()
+                    .collect(),
+                self.get_unique_files()
+                    .iter()
+                    .map(|p| p.display().to_string())
+                    .collect(),
+            ),
+            Target::Address(address) => (
+                {
+                    let matches: Vec<Resource> = self
+                        .resources
+                        .iter()
+                        .filter_map(|r| match_address(r, address))
+                        .collect();
+                    if matches.is_empty() {
+                        self.module_resource(address).into_iter().collect()
+                    } else {
+                        matches
+                    }
+                },
+                self.resources.iter().map(|r| r.target_string()).collect(),
+            ),
+            Target::Resource(resource_type, name) => (
+                self.resources
+                    .iter()
+                    .filter(|r| {
+                        !r.is_module
+                            && !r.is_data
+                            && &r.resource_type == resource_type
+                            && &r.name == name
+                    })
+                    .cloned()
+                    .collect(),
+                self.resources
+                    .iter()
+                    .filter(|r| !r.is_module && !r.is_data)
+                    .map(|r| r.full_name())
+                    .collect(),
+            ),
+            Target::Data(data_type, name) => (
+                self.resources
+                    .iter()
+                    .filter(|r| r.is_data && &r.resource_type == data_type && &r.name == name)
+                    .cloned()
+                    .collect(),
+                self.resources
+                    .iter()
+                    .filter(|r| r.is_data)
+                    .map(|r| r.full_name())
+                    .collect(),
+            ),
+        };
 
-        project.parse_file(temp_file.path()).unwrap();
+        if matches.is_empty() {
+            let wanted = match target {
+                Target::File(path) | Target::Directory(path) | Target::LineRange(path, _, _) => {
+                    path.display().to_string()
+                }
+                Target::Module(name) => name.clone(),
+                Target::Resource(resource_type, name) => format!("{}.{}", resource_type, name),
+                Target::Data(data_type, name) => format!("data.{}.{}", data_type, name),
+                Target::FileGlob(pattern)
+                | Target::Output(pattern)
+                | Target::Prefix(pattern)
+                | Target::Address(pattern) => pattern.clone(),
+            };
+            return Err(TfocusError::NoMatchingTarget {
+                target: target.to_string(),
+                suggestions: suggest_similar(&wanted, &candidates),
+            });
+        }
 
-        let resources = project.get_all_resources();
-        assert_eq!(resources.len(), 1, "Expected exactly one resource");
-        assert!(!resources[0].has_count, "Resource should not have count");
-        assert!(resources[0].has_for_each, "Resource should have for_each");
+        Ok(matches)
     }
+}
 
-    #[test]
+/// Returns `resource`, or its instance, when `address` names it
+///
+/// Parsed blocks have no index, so `aws_instance.web[0]` matches the block
+/// `aws_instance.web` and yields its instance `0`.
+fn match_address(resource: &Resource, address: &str) -> Option<Resource> {
+    if resource.target_string() == address {
+        return Some(resource.clone());
+    }
+    let (base, key) = split_instance_key(address)?;
+    if resource.index.is_some() || resource.full_name() != base {
+        return None;
+    }
+    resource.instance(key)
+}
+
+/// Returns whether `prefix` selects `address` for `--prefix`
+///
+/// The prefix must end at an address boundary, so `module.platform` matches
+/// `module.platform.aws_vpc.main` but not `module.platform_dns`. A prefix ending
+/// in `.` or `_` (e.g. `aws_iam_`) is taken as deliberately partial.
+fn has_address_prefix(address: &str, prefix: &str) -> bool {
+    match address.strip_prefix(prefix) {
+        Some(rest) => {
+            rest.is_empty() || prefix.ends_with(['.', '_']) || rest.starts_with(['.', '['])
+        }
+        None => false,
+    }
+}
+
+/// Fails with a specific error when `path` is missing or cannot be read
+fn check_path(path: &Path) -> Result<()> {
+    let not_accessible = |e: std::io::Error| TfocusError::PathNotAccessible {
+        path: path.display().to_string(),
+        reason: e.to_string(),
+    };
+    match fs::metadata(path) {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            Err(TfocusError::PathNotFound(path.display().to_string()))
+        }
+        Err(e) => Err(not_accessible(e)),
+        Ok(metadata) if metadata.is_dir() => {
+            fs::read_dir(path).map_err(not_accessible)?;
+            Ok(())
+        }
+        Ok(_) => Ok(()),
+    }
+}
+
+/// Builds the error for a directory without Terraform files, with a hint at the likely mistake
+fn empty_directory_error(path: &Path) -> TfocusError {
+    let hint = if path.join(".terraform").is_dir() {
+        "it only holds terraform's working data (.terraform); \
+         did you mean to pass the module directory?"
+    } else {
+        "did you mean to pass the module directory?"
+    };
+    TfocusError::EmptyDirectory {
+        path: path.display().to_string(),
+        hint: hint.to_string(),
+    }
+}
+
+fn is_permission_denied(error: &std::io::Error) -> bool {
+    error.kind() == std::io::ErrorKind::PermissionDenied
+}
+
+/// Returns whether two paths name the same file
+///
+/// `./main.tf` and `main.tf` are equal, and paths that resolve to the same
+/// file on disk (e.g. an absolute path from an editor) are too.
+fn same_file(a: &Path, b: &Path) -> bool {
+    let normalize = |p: &Path| -> PathBuf {
+        p.components()
+            .filter(|c| *c != std::path::Component::CurDir)
+            .collect()
+    };
+    if normalize(a) == normalize(b) {
+        return true;
+    }
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// Returns whether `file` lies under `dir`, ignoring `.` components
+fn in_directory(file: &Path, dir: &Path) -> bool {
+    let normalize = |p: &Path| -> PathBuf {
+        p.components()
+            .filter(|c| *c != std::path::Component::CurDir)
+            .collect()
+    };
+    normalize(file).starts_with(normalize(dir))
+}
+
+/// Prints the discovered files relative to `root`, noting how many empty files were hidden
+fn print_found_files(root: &Path, files: &[PathBuf], hidden: usize) {
+    println!("\nFound Terraform files:");
+    for file in files {
+        if let Ok(rel_path) = file.strip_prefix(root) {
+            println!("  {}", rel_path.display());
+        } else {
+            println!("  {}", file.display());
+        }
+    }
+    if hidden > 0 {
+        println!("  ({} files without targets hidden)", hidden);
+    }
+    println!();
+}
+
+/// Resources parsed one file at a time; see [`TerraformProject::stream_path`]
+pub struct ResourceStream {
+    files: std::vec::IntoIter<PathBuf>,
+    pending: std::vec::IntoIter<Resource>,
+    quiet: bool,
+}
+
+impl Iterator for ResourceStream {
+    type Item = Result<Resource>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(resource) = self.pending.next() {
+                return Some(Ok(resource));
+            }
+            let file = self.files.next()?;
+            let mut project = TerraformProject::new();
+            if let Err(e) = project.parse_found_file(&file, self.quiet) {
+                return Some(Err(e));
+            }
+            self.pending = project.resources.into_iter();
+        }
+    }
+}
+
+/// Returns whether a resource is of one of `kinds` (empty means all) and its
+/// address matches `filter`
+pub fn resource_matches(
+    resource: &Resource,
+    kinds: &[ResourceKind],
+    filter: Option<&Regex>,
+) -> bool {
+    (kinds.is_empty() || kinds.contains(&resource.kind()))
+        && filter.map_or(true, |re| re.is_match(&resource.full_name()))
+}
+
+/// Prints a "Parsing N/M files..." progress line to stderr
+fn print_progress(current: usize, total: usize) {
+    let mut stderr = std::io::stderr();
+    let _ = execute!(
+        stderr,
+        cursor::MoveToColumn(0),
+        terminal::Clear(ClearType::CurrentLine),
+        style::Print(format!("Parsing {}/{} files...", current, total))
+    );
+}
+
+/// Clears the progress line so the selector starts from a clean line
+fn clear_progress() {
+    let mut stderr = std::io::stderr();
+    let _ = execute!(
+        stderr,
+        cursor::MoveToColumn(0),
+        terminal::Clear(ClearType::CurrentLine)
+    );
+}
+
+/// Builds a matcher for a file glob target
+///
+/// Relative patterns also match below any parent directory, so
+/// `modules/*/main.tf` matches `infra/modules/vpc/main.tf`.
+fn file_glob_matcher(pattern: &str) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    builder.add(GlobBuilder::new(pattern).literal_separator(true).build()?);
+    if !pattern.starts_with('/') && !pattern.starts_with("**/") {
+        builder.add(
+            GlobBuilder::new(&format!("**/{}", pattern))
+                .literal_separator(true)
+                .build()?,
+        );
+    }
+    Ok(builder.build()?)
+}
+
+/// Reads the arguments of resource, data and module blocks that tfocus uses
+struct BlockArgumentsParser {
+    count: Regex,
+    for_each: Regex,
+    depends_on: Regex,
+    source: Regex,
+    provider: Regex,
+    providers: Regex,
+    providers_entry: Regex,
+}
+
+impl BlockArgumentsParser {
+    fn new() -> Result<Self> {
+        Ok(BlockArgumentsParser {
+            count: meta_argument_regex("count")?,
+            for_each: meta_argument_regex("for_each")?,
+            depends_on: Regex::new(r"depends_on\s*=\s*\[([^\]]*)\]")
+                .map_err(TfocusError::RegexError)?,
+            source: Regex::new(r#"(?:^|[\s{])source\s*=\s*"([^"]*)""#)
+                .map_err(TfocusError::RegexError)?,
+            provider: Regex::new(r#"(?m)^\s*provider\s*=\s*"?([\w-]+(?:\.[\w-]+)?)"?"#)
+                .map_err(TfocusError::RegexError)?,
+            providers: Regex::new(r"(?:^|\s)providers\s*=\s*\{([^}]*)\}")
+                .map_err(TfocusError::RegexError)?,
+            providers_entry: Regex::new(r"([\w.-]+)\s*=\s*([\w.-]+)")
+                .map_err(TfocusError::RegexError)?,
+        })
+    }
+
+    /// Returns whether the block sets `count`; see [`meta_argument_regex`]
+    fn has_count(&self, block: &str) -> bool {
+        self.count.is_match(&top_level_text(block))
+    }
+
+    /// Returns whether the block sets `for_each`; see [`meta_argument_regex`]
+    fn has_for_each(&self, block: &str) -> bool {
+        self.for_each.is_match(&top_level_text(block))
+    }
+
+    /// Returns the addresses listed in the block's `depends_on` argument
+    fn depends_on(&self, block: &str) -> Vec<String> {
+        self.depends_on
+            .captures(block)
+            .map(|cap| {
+                cap[1]
+                    .split(|c: char| c == ',' || c.is_whitespace())
+                    .filter(|address| !address.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Returns the `source` argument of a module block, if present
+    fn module_source(&self, block: &str) -> Option<String> {
+        self.source.captures(block).map(|cap| cap[1].to_string())
+    }
+
+    /// Returns the explicit `provider` argument of a resource or data block, e.g. `aws.east`
+    ///
+    /// The quoted form of Terraform 0.11 (`provider = "aws.east"`) is accepted too.
+    fn resource_provider(&self, block: &str) -> Option<String> {
+        self.provider.captures(block).map(|cap| cap[1].to_string())
+    }
+
+    /// Returns the `providers` map of a module block as `(module provider, passed provider)` pairs
+    ///
+    /// `providers = { aws = aws.useast1 }` yields `("aws", "aws.useast1")`. Entries
+    /// may be separated by newlines or commas.
+    fn module_providers(&self, block: &str) -> Vec<(String, String)> {
+        self.providers
+            .captures(block)
+            .map(|map| {
+                self.providers_entry
+                    .captures_iter(&map[1])
+                    .map(|entry| (entry[1].to_string(), entry[2].to_string()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// Parses the `required_providers` entries of `terraform` blocks
+///
+/// Entries are either `name = { source = "...", version = "..." }` or, before
+/// Terraform 0.13, `name = "version"`.
+struct RequiredProvidersParser {
+    header: Regex,
+    object: Regex,
+    legacy: Regex,
+    source: Regex,
+    version: Regex,
+}
+
+impl RequiredProvidersParser {
+    fn new() -> Result<Self> {
+        Ok(RequiredProvidersParser {
+            header: Regex::new(r"(?:^|[\s{])required_providers\s*\{")
+                .map_err(TfocusError::RegexError)?,
+            object: Regex::new(r"(?m)^\s*([\w-]+)\s*=\s*\{").map_err(TfocusError::RegexError)?,
+            legacy: Regex::new(r#"(?m)^\s*([\w-]+)\s*=\s*"([^"]*)""#)
+                .map_err(TfocusError::RegexError)?,
+            source: Regex::new(r#"(?:^|[\s{])source\s*=\s*"([^"]*)""#)
+                .map_err(TfocusError::RegexError)?,
+            version: Regex::new(r#"(?:^|[\s{])version\s*=\s*"([^"]*)""#)
+                .map_err(TfocusError::RegexError)?,
+        })
+    }
+
+    fn parse(&self, terraform_block: &str, path: &Path) -> Vec<ProviderRequirement> {
+        let Some(body) = self
+            .header
+            .find(terraform_block)
+            .and_then(|header| block_text(terraform_block, header))
+        else {
+            return Vec::new();
+        };
+
+        let mut requirements = Vec::new();
+        let mut objects = Vec::new();
+        for header in self.object.find_iter(body) {
+            let Some(entry) = block_text(body, header) else {
+                continue;
+            };
+            objects.push(header.start()..header.start() + entry.len());
+            let name = &self.object.captures(header.as_str()).unwrap()[1];
+            requirements.push(ProviderRequirement {
+                name: name.to_string(),
+                source: self.source.captures(entry).map(|c| c[1].to_string()),
+                version: self.version.captures(entry).map(|c| c[1].to_string()),
+                file_path: path.to_owned(),
+            });
+        }
+        // A plain string outside the objects is a legacy version constraint
+        for cap in self.legacy.captures_iter(body) {
+            let start = cap.get(0).unwrap().start();
+            if objects.iter().any(|object| object.contains(&start)) {
+                continue;
+            }
+            requirements.push(ProviderRequirement {
+                name: cap[1].to_string(),
+                source: None,
+                version: Some(cap[2].to_string()),
+                file_path: path.to_owned(),
+            });
+        }
+        requirements
+    }
+}
+
+/// Returns the text of a block, from its header through the matching closing brace
+///
+/// `header` must end with the block's opening brace. Braces inside strings,
+/// comments and heredocs are ignored. Returns `None` if the block is not closed.
+fn block_text<'a>(content: &'a str, header: regex::Match<'_>) -> Option<&'a str> {
+    let mut depth = 0usize;
+    let mut end = None;
+    scan_code(content, header.end() - 1, |i, byte| {
+        match byte {
+            b'{' => depth += 1,
+            b'}' => {
+                depth -= 1;
+                if depth == 0 {
+                    end = Some(i);
+                    return false;
+                }
+            }
+            _ => {}
+        }
+        true
+    });
+    end.map(|i| &content[header.start()..=i])
+}
+
+/// Calls `visit` with each byte of code from `start`, skipping strings,
+/// comments and heredocs, until `visit` returns false
+fn scan_code(content: &str, start: usize, mut visit: impl FnMut(usize, u8) -> bool) {
+    let bytes = content.as_bytes();
+    let mut i = start;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'"' => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != b'"' {
+                    if bytes[i] == b'\\' {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+            }
+            b'#' => i = skip_to_line_end(bytes, i),
+            b'/' if bytes.get(i + 1) == Some(&b'/') => i = skip_to_line_end(bytes, i),
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i = content[i + 2..]
+                    .find("*/")
+                    .map_or(bytes.len(), |end| i + 2 + end + 1);
+            }
+            b'<' if content[i..].starts_with("<<") => {
+                let rest = content[i + 2..].trim_start_matches('-');
+                let marker: String = rest
+                    .chars()
+                    .take_while(|c| c.is_alphanumeric() || *c == '_')
+                    .collect();
+                if !marker.is_empty() {
+                    // Skip to the line holding only the closing marker
+                    let mut line_start = skip_to_line_end(bytes, i) + 1;
+                    i = bytes.len();
+                    while line_start < bytes.len() {
+                        let line_end = skip_to_line_end(bytes, line_start);
+                        if content[line_start..line_end].trim() == marker {
+                            i = line_end;
+                            break;
+                        }
+                        line_start = line_end + 1;
+                    }
+                }
+            }
+            byte => {
+                if !visit(i, byte) {
+                    return;
+                }
+            }
+        }
+        i += 1;
+    }
+}
+
+/// Returns the index of the newline ending the line at `i`, or the content length
+fn skip_to_line_end(bytes: &[u8], i: usize) -> usize {
+    bytes[i..]
+        .iter()
+        .position(|&b| b == b'\n')
+        .map_or(bytes.len(), |offset| i + offset)
+}
+
+/// Returns the code directly inside a block, blanking out nested blocks,
+/// object literals, strings and comments
+fn top_level_text(block: &str) -> String {
+    let mut top = vec![b' '; block.len()];
+    let mut depth = 0usize;
+    scan_code(block, 0, |i, byte| {
+        match byte {
+            b'{' => depth += 1,
+            b'}' => depth = depth.saturating_sub(1),
+            _ if depth == 1 => top[i] = byte,
+            _ => {}
+        }
+        true
+    });
+    String::from_utf8_lossy(&top).into_owned()
+}
+
+/// Returns the regex matching a meta-argument (e.g. `count`) in a block's top-level text
+///
+/// Only top-level arguments count, so a `for_each` inside a `dynamic` block
+/// does not make the resource itself a for_each resource. The name must start
+/// a word, so `account = ...` is not mistaken for `count`, and any spacing
+/// around `=` is accepted.
+fn meta_argument_regex(name: &str) -> Result<Regex> {
+    Regex::new(&format!(r"(?:^|\s){}\s*=(?:[^=]|$)", name)).map_err(TfocusError::RegexError)
+}
+
+/// Returns the `providers` object of a JSON module body as `(module provider, passed provider)` pairs
+fn json_module_providers(body: &serde_json::Value) -> Vec<(String, String)> {
+    body.get("providers")
+        .and_then(|p| p.as_object())
+        .map(|providers| {
+            providers
+                .iter()
+                .filter_map(|(name, value)| {
+                    let value = value
+                        .as_str()?
+                        .trim_start_matches("${")
+                        .trim_end_matches('}');
+                    Some((name.clone(), value.to_string()))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Returns the addresses listed in a JSON block body's `depends_on` array
+fn json_depends_on(body: &serde_json::Value) -> Vec<String> {
+    body.get("depends_on")
+        .and_then(|d| d.as_array())
+        .map(|addresses| {
+            addresses
+                .iter()
+                .filter_map(|a| a.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Returns the submodule directories listed in `root/.gitmodules`
+fn gitmodule_paths(root: &Path) -> Result<Vec<PathBuf>> {
+    let gitmodules = root.join(".gitmodules");
+    if !gitmodules.is_file() {
+        return Ok(Vec::new());
+    }
+
+    let content = fs::read_to_string(&gitmodules).map_err(TfocusError::Io)?;
+    Ok(content
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once('=')?;
+            (key.trim() == "path").then(|| root.join(value.trim()))
+        })
+        .collect())
+}
+
+/// Collects the labeled blocks of one type from a JSON configuration
+///
+/// Returns `(first label, last label, body)` tuples; with one label the first
+/// label is `None`. A block body may be an object or an array of objects.
+fn json_labeled_blocks<'a>(
+    root: &'a serde_json::Value,
+    keyword: &str,
+    labels: usize,
+) -> Vec<(Option<String>, String, &'a serde_json::Value)> {
+    let mut blocks = Vec::new();
+    let Some(by_label) = root.get(keyword).and_then(|v| v.as_object()) else {
+        return blocks;
+    };
+
+    for (label, value) in by_label {
+        if labels == 1 {
+            blocks.push((None, label.clone(), json_block_body(value)));
+        } else if let Some(by_name) = value.as_object() {
+            for (name, body) in by_name {
+                blocks.push((Some(label.clone()), name.clone(), json_block_body(body)));
+            }
+        }
+    }
+    blocks
+}
+
+/// Returns the first body of a JSON block, unwrapping the array form
+fn json_block_body(value: &serde_json::Value) -> &serde_json::Value {
+    value
+        .as_array()
+        .and_then(|bodies| bodies.first())
+        .unwrap_or(value)
+}
+
+/// Reads a configuration file, replacing invalid UTF-8 rather than failing
+///
+/// A stray byte (e.g. a Latin-1 comment) then costs at most the block around
+/// it instead of aborting the whole scan. Parsing the file warns about it.
+pub fn read_config(path: &Path) -> Result<String> {
+    read_config_lossy(path).map(|(content, _)| content)
+}
+
+/// Reads a configuration file like [`read_config`], also returning the offset
+/// of the first invalid UTF-8 byte, if any
+fn read_config_lossy(path: &Path) -> Result<(String, Option<usize>)> {
+    let bytes = fs::read(path).map_err(TfocusError::Io)?;
+    match String::from_utf8(bytes) {
+        Ok(content) => Ok((content, None)),
+        Err(e) => {
+            let offset = e.utf8_error().valid_up_to();
+            Ok((
+                String::from_utf8_lossy(e.as_bytes()).into_owned(),
+                Some(offset),
+            ))
+        }
+    }
+}
+
+/// Returns whether the comments at the top of `content` include `tfocus:ignore`
+///
+/// Only the leading run of blank and comment lines (`#` or `//`) is checked,
+/// so a marker further down the file has no effect.
+fn has_ignore_header(content: &str) -> bool {
+    content
+        .lines()
+        .map(str::trim)
+        .take_while(|line| line.is_empty() || line.starts_with('#') || line.starts_with("//"))
+        .any(|line| line.trim_start_matches(['#', '/']).trim() == "tfocus:ignore")
+}
+
+/// Returns the 1-based line number of a byte offset in `content`
+fn line_number(content: &str, offset: usize) -> usize {
+    content[..offset].matches('\n').count() + 1
+}
+
+/// Finds `resource` and `data` headers with a type label but no name label
+///
+/// Returns the keyword, type and line of each, so a malformed block is
+/// reported instead of silently missing from the selector.
+fn unnamed_blocks(content: &str) -> Result<Vec<(&str, &str, usize)>> {
+    let regex = Regex::new(r#"(?m)^[ \t]*(resource|data)\s+"([^"]*)"\s*\{"#)
+        .map_err(TfocusError::RegexError)?;
+    Ok(regex
+        .captures_iter(content)
+        .map(|cap| {
+            let keyword = cap.get(1).unwrap();
+            let line = line_number(content, keyword.start());
+            (keyword.as_str(), cap.get(2).unwrap().as_str(), line)
+        })
+        .collect())
+}
+
+/// Returns up to three candidates within a small edit distance of `wanted`
+fn suggest_similar(wanted: &str, candidates: &[String]) -> Vec<String> {
+    let max_distance = (wanted.chars().count() / 3).max(2);
+    let mut scored: Vec<(usize, &String)> = candidates
+        .iter()
+        .map(|c| (levenshtein(wanted, c), c))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .collect();
+    scored.sort();
+    scored.dedup_by(|a, b| a.1 == b.1);
+    scored.into_iter().take(3).map(|(_, c)| c.clone()).collect()
+}
+
+/// Computes the Levenshtein edit distance between two strings
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut curr = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            curr[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+        prev = curr;
+    }
+    prev[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_parse_resource_with_count() {
+        let mut project = TerraformProject::new();
+        let content = r#"
+        resource "aws_instance" "web" {
+          count = 2
+          ami = "ami-123456"
+          instance_type = "t2.micro"
+        }
+        "#;
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut temp_file, content.as_bytes()).unwrap();
+
+        project.parse_file(temp_file.path()).unwrap();
+
+        let resources = project.get_all_resources();
+        assert_eq!(resources.len(), 1, "Expected exactly one resource");
+        assert!(resources[0].has_count, "Resource should have count");
+        assert!(
+            !resources[0].has_for_each,
+            "Resource should not have for_each"
+        );
+    }
+
+    #[test]
+    fn test_parse_resource_with_for_each() {
+        let mut project = TerraformProject::new();
+        let content = r#"
+        resource "aws_instance" "web" {
+          for_each = toset(["a", "b"])
+          ami = "ami-123456"
+          instance_type = "t2.micro"
+        }
+        "#;
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut temp_file, content.as_bytes()).unwrap();
+
+        project.parse_file(temp_file.path()).unwrap();
+
+        let resources = project.get_all_resources();
+        assert_eq!(resources.len(), 1, "Expected exactly one resource");
+        assert!(!resources[0].has_count, "Resource should not have count");
+        assert!(resources[0].has_for_each, "Resource should have for_each");
+    }
+
+    #[test]
     fn test_parse_module_with_count() {
         let mut project = TerraformProject::new();
         let content = r#"
@@ -283,6 +1913,77 @@ mod tests {
         assert!(resources[0].is_module, "Resource should be a module");
     }
 
+    #[test]
+    fn test_parse_resource_provider() {
+        let arguments = BlockArgumentsParser::new().unwrap();
+        assert_eq!(
+            arguments
+                .resource_provider("resource \"aws_instance\" \"web\" {\n  provider = aws.east\n}"),
+            Some("aws.east".to_string())
+        );
+        assert_eq!(
+            arguments.resource_provider(
+                "resource \"aws_instance\" \"web\" {\n  provider = \"google-beta\"\n}"
+            ),
+            Some("google-beta".to_string())
+        );
+        assert_eq!(
+            arguments
+                .resource_provider("resource \"aws_instance\" \"web\" {\n  ami = \"ami-1\"\n}"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_module_providers() {
+        let mut project = TerraformProject::new();
+        let content = r#"
+        module "replica" {
+          source = "./modules/replica"
+          providers = {
+            aws     = aws.useast1
+            aws.dst = aws.euwest1
+          }
+        }
+
+        module "inline" {
+          source    = "./modules/inline"
+          providers = { google = google.shared, google-beta = google-beta.shared }
+        }
+
+        module "plain" {
+          source = "./modules/plain"
+        }
+        "#;
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut temp_file, content.as_bytes()).unwrap();
+        project.parse_file(temp_file.path()).unwrap();
+
+        let providers = |name: &str| {
+            project
+                .resources
+                .iter()
+                .find(|r| r.name == name)
+                .unwrap()
+                .providers
+                .clone()
+        };
+        let pair = |a: &str, b: &str| (a.to_string(), b.to_string());
+        assert_eq!(
+            providers("replica"),
+            vec![pair("aws", "aws.useast1"), pair("aws.dst", "aws.euwest1")]
+        );
+        assert_eq!(
+            providers("inline"),
+            vec![
+                pair("google", "google.shared"),
+                pair("google-beta", "google-beta.shared")
+            ]
+        );
+        assert!(providers("plain").is_empty());
+    }
+
     #[test]
     fn test_get_resources_by_target() {
         let mut project = TerraformProject::new();
@@ -304,18 +2005,1308 @@ mod tests {
 
         project.parse_file(&file_path).unwrap();
 
-        let by_file = project.get_resources_by_target(&Target::File(file_path.clone()));
+        let by_file = project
+            .get_resources_by_target(&Target::File(file_path.clone()))
+            .unwrap();
         assert_eq!(by_file.len(), 2, "Expected two resources in the file");
 
-        let by_resource = project.get_resources_by_target(&Target::Resource(
-            "aws_instance".to_string(),
-            "web".to_string(),
-        ));
+        let by_resource = project
+            .get_resources_by_target(&Target::Resource(
+                "aws_instance".to_string(),
+                "web".to_string(),
+            ))
+            .unwrap();
         assert_eq!(by_resource.len(), 1, "Expected one matching resource");
         assert!(by_resource[0].has_count, "Resource should have count");
 
-        let by_module = project.get_resources_by_target(&Target::Module("app".to_string()));
+        let by_module = project
+            .get_resources_by_target(&Target::Module("app".to_string()))
+            .unwrap();
         assert_eq!(by_module.len(), 1, "Expected one matching module");
         assert!(by_module[0].is_module, "Resource should be a module");
     }
+
+    #[test]
+    fn test_parse_single_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("main.tf");
+        std::fs::write(
+            &file_path,
+            r#"
+        resource "aws_instance" "web" {
+          ami = "ami-123456"
+        }
+
+        module "app" {
+          source = "./modules/app"
+        }
+        "#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("other.tf"),
+            r#"
+        resource "aws_s3_bucket" "logs" {
+          bucket = "logs"
+        }
+        "#,
+        )
+        .unwrap();
+
+        let project = TerraformProject::parse_path(&file_path, &quiet()).unwrap();
+        let resources = project.get_all_resources();
+        assert_eq!(resources.len(), 2, "Expected only resources from main.tf");
+        assert!(resources.iter().all(|r| r.file_path == file_path));
+    }
+
+    #[test]
+    fn test_parse_skips_ignored_files() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("main.tf"),
+            "resource \"aws_instance\" \"web\" {\n}\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("generated.tf"),
+            "# Rendered from a template\n# tfocus:ignore\n\nresource \"aws_instance\" \"fake\" {\n}\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("late.tf"),
+            "resource \"aws_instance\" \"db\" {\n}\n# tfocus:ignore\n",
+        )
+        .unwrap();
+
+        let project = TerraformProject::parse_path(dir.path(), &quiet()).unwrap();
+        let mut names: Vec<String> = project
+            .get_all_resources()
+            .into_iter()
+            .map(|r| r.name)
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["db", "web"]);
+        assert!(!project
+            .get_parsed_files()
+            .contains(&dir.path().join("generated.tf")));
+    }
+
+    #[test]
+    fn test_files_without_targets() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("main.tf"),
+            "resource \"aws_instance\" \"web\" {\n}\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("backend.tf"),
+            "terraform {\n  required_version = \">= 1.5\"\n  backend \"s3\" {}\n}\n",
+        )
+        .unwrap();
+
+        let options = ParseOptions {
+            hide_empty_files: true,
+            ..quiet()
+        };
+        let project = TerraformProject::parse_path(dir.path(), &options).unwrap();
+        assert_eq!(
+            project.files_without_targets(),
+            vec![dir.path().join("backend.tf")]
+        );
+        assert_eq!(project.get_version_requirements().len(), 1);
+    }
+
+    #[test]
+    fn test_parse_identifiers_with_digits_and_hyphens() {
+        let mut project = TerraformProject::new();
+        let content = r#"
+        resource "aws_s3_bucket" "logs-2024_v2" {
+          count = 2
+          bucket = "logs"
+        }
+
+        module "vpc-01" {
+          source = "./modules/vpc"
+        }
+        "#;
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut temp_file, content.as_bytes()).unwrap();
+        project.parse_file(temp_file.path()).unwrap();
+
+        let resources = project.get_all_resources();
+        assert_eq!(resources.len(), 2);
+        assert_eq!(resources[0].full_name(), "module.vpc-01");
+        assert_eq!(resources[1].full_name(), "aws_s3_bucket.logs-2024_v2");
+
+        let mut indexed = resources[1].clone();
+        indexed.index = Some("1".to_string());
+        assert_eq!(indexed.target_string(), "aws_s3_bucket.logs-2024_v2[1]");
+    }
+
+    #[test]
+    fn test_parse_skips_invalid_identifiers() {
+        let mut project = TerraformProject::new();
+        let content = r#"
+        resource "aws_instance" "web server" {
+          ami = "ami-123456"
+        }
+
+        resource "aws_instance" "2web" {
+          ami = "ami-123456"
+        }
+
+        resource "aws_instance" "web" {
+          ami = "ami-123456"
+        }
+        "#;
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut temp_file, content.as_bytes()).unwrap();
+        project.parse_file(temp_file.path()).unwrap();
+
+        let resources = project.get_all_resources();
+        assert_eq!(resources.len(), 1, "Invalid identifiers should be skipped");
+        assert_eq!(project.warnings.len(), 2);
+        assert_eq!(resources[0].full_name(), "aws_instance.web");
+    }
+
+    #[test]
+    fn test_unnamed_blocks_are_reported() {
+        let content = r#"
+resource "aws_instance" {
+  ami = "ami-123456"
+}
+
+resource "aws_instance" "web" {
+  ami = "ami-123456"
+}
+
+  data "aws_ami" {}
+"#;
+
+        assert_eq!(
+            unnamed_blocks(content).unwrap(),
+            vec![("resource", "aws_instance", 2), ("data", "aws_ami", 10)]
+        );
+
+        let mut project = TerraformProject::new();
+        let mut temp_file = NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut temp_file, content.as_bytes()).unwrap();
+        project.parse_file(temp_file.path()).unwrap();
+        let names: Vec<String> = project
+            .get_all_resources()
+            .iter()
+            .map(|r| r.full_name())
+            .collect();
+        assert_eq!(names, vec!["aws_instance.web"]);
+        let path = temp_file.path().display();
+        assert_eq!(
+            project.warnings,
+            vec![
+                format!(
+                    "skipping resource \"aws_instance\" at {}:2: block has no name label",
+                    path
+                ),
+                format!(
+                    "skipping data \"aws_ami\" at {}:10: block has no name label",
+                    path
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_utf8() {
+        let mut content = b"# caf\xe9 \xff\n".to_vec();
+        content.extend_from_slice(
+            b"resource \"aws_instance\" \"web\" {\n  tags = { Name = \"\xe9\" }\n}\n",
+        );
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut temp_file, &content).unwrap();
+        let mut project = TerraformProject::new();
+        project.parse_file(temp_file.path()).unwrap();
+
+        let resources = project.get_all_resources();
+        assert_eq!(resources.len(), 1);
+        assert_eq!(resources[0].full_name(), "aws_instance.web");
+        assert_eq!(resources[0].line, 2);
+        assert_eq!(
+            project.warnings,
+            vec![format!(
+                "{}: invalid UTF-8 at byte 5, reading it with replacement characters",
+                temp_file.path().display()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_parse_tab_indentation() {
+        let mut project = TerraformProject::new();
+        let content = "\tresource\t\"aws_instance\"\t\"web\" {\n\t\tcount = 2\n\t}\n\n\tmodule\t\"vpc\" {\n\t\tsource = \"./vpc\"\n\t}\n";
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut temp_file, content.as_bytes()).unwrap();
+        project.parse_file(temp_file.path()).unwrap();
+
+        let resources = project.get_all_resources();
+        assert_eq!(resources.len(), 2, "Expected a module and a resource");
+        assert_eq!(resources[0].full_name(), "module.vpc");
+        assert_eq!(resources[1].full_name(), "aws_instance.web");
+        assert!(resources[1].has_count, "Resource should have count");
+    }
+
+    #[test]
+    fn test_parse_brace_on_next_line() {
+        let mut project = TerraformProject::new();
+        let content = r#"
+
+resource "aws_instance" "web"
+{
+  ami = "ami-123456"
+}
+
+module "vpc"
+{
+  source = "./vpc"
+}
+"#;
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut temp_file, content.as_bytes()).unwrap();
+        project.parse_file(temp_file.path()).unwrap();
+
+        let resources = project.get_all_resources();
+        assert_eq!(resources.len(), 2, "Expected a module and a resource");
+        assert_eq!(resources[0].full_name(), "module.vpc");
+        assert_eq!(resources[0].line, 8);
+        assert_eq!(resources[1].full_name(), "aws_instance.web");
+        assert_eq!(resources[1].line, 3);
+    }
+
+    #[test]
+    fn test_first_resources() {
+        let mut project = TerraformProject::new();
+        let content = "resource \"aws_instance\" \"web\" {}\nresource \"aws_eip\" \"ip\" {}\nmodule \"vpc\" {\n  source = \"./vpc\"\n}\n";
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut temp_file, content.as_bytes()).unwrap();
+        project.parse_file(temp_file.path()).unwrap();
+
+        let first: Vec<String> = project
+            .first_resources(2)
+            .unwrap()
+            .iter()
+            .map(|r| r.full_name())
+            .collect();
+        assert_eq!(first, vec!["module.vpc", "aws_eip.ip"]);
+        assert_eq!(project.first_resources(3).unwrap().len(), 3);
+        assert!(matches!(
+            project.first_resources(4),
+            Err(TfocusError::SelectionOutOfRange {
+                requested: 4,
+                available: 3
+            })
+        ));
+        assert!(project.first_resources(0).is_err());
+    }
+
+    #[test]
+    fn test_parse_records_line_numbers() {
+        let mut project = TerraformProject::new();
+        let content = "# header\n\nresource \"aws_vpc\" \"main\" {\n  cidr_block = \"10.0.0.0/16\"\n}\n\n\nmodule \"vpc\" {\n  source = \"./vpc\"\n}\n";
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut temp_file, content.as_bytes()).unwrap();
+        project.parse_file(temp_file.path()).unwrap();
+
+        let resources = project.get_all_resources();
+        let module = resources.iter().find(|r| r.is_module).unwrap();
+        let vpc = resources.iter().find(|r| !r.is_module).unwrap();
+        assert_eq!(vpc.line, 3, "Resource should start on line 3");
+        assert_eq!(module.line, 8, "Module should start on line 8");
+    }
+
+    #[test]
+    fn test_get_resources_by_file_glob() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |rel: &str, name: &str| {
+            let path = dir.path().join(rel);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(
+                &path,
+                format!(
+                    "resource \"aws_instance\" \"{}\" {{\n  ami = \"ami\"\n}}\n",
+                    name
+                ),
+            )
+            .unwrap();
+        };
+        write("modules/vpc/main.tf", "vpc");
+        write("modules/eks/main.tf", "eks");
+        write("modules/eks/variables.tf", "vars");
+        write("main.tf", "root");
+
+        let project = TerraformProject::parse_path(dir.path(), &quiet()).unwrap();
+        let mut names: Vec<String> = project
+            .get_resources_by_target(&Target::FileGlob("modules/*/main.tf".to_string()))
+            .unwrap()
+            .into_iter()
+            .map(|r| r.name)
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["eks", "vpc"]);
+
+        let err = project
+            .get_resources_by_target(&Target::FileGlob("envs/*/main.tf".to_string()))
+            .unwrap_err();
+        assert!(matches!(err, TfocusError::NoMatchingTarget { .. }));
+    }
+
+    #[test]
+    fn test_get_resources_by_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        for (rel, name) in [
+            ("modules/vpc/main.tf", "vpc"),
+            ("modules/eks/main.tf", "eks"),
+            ("main.tf", "root"),
+        ] {
+            let path = dir.path().join(rel);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(
+                &path,
+                format!("resource \"aws_instance\" \"{}\" {{}}\n", name),
+            )
+            .unwrap();
+        }
+
+        let project = TerraformProject::parse_path(dir.path(), &quiet()).unwrap();
+        assert_eq!(
+            project.get_unique_dirs(),
+            vec![
+                dir.path().to_path_buf(),
+                dir.path().join("modules/eks"),
+                dir.path().join("modules/vpc"),
+            ]
+        );
+        let mut names: Vec<String> = project
+            .get_resources_by_target(&Target::Directory(dir.path().join("modules")))
+            .unwrap()
+            .into_iter()
+            .map(|r| r.name)
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["eks", "vpc"]);
+
+        assert!(in_directory(
+            Path::new("./modules/vpc/main.tf"),
+            Path::new("modules")
+        ));
+        assert!(!in_directory(
+            Path::new("modules-old/main.tf"),
+            Path::new("modules")
+        ));
+        let err = project
+            .get_resources_by_target(&Target::Directory(dir.path().join("envs")))
+            .unwrap_err();
+        assert!(matches!(err, TfocusError::NoMatchingTarget { .. }));
+    }
+
+    #[test]
+    fn test_parse_closing_brace_with_trailing_comment() {
+        let mut project = TerraformProject::new();
+        let content = r#"
+resource "aws_instance" "web" {
+  count = 2
+  tags = { Team = "platform" } # inline map
+} # managed by team
+
+resource "aws_instance" "app" {
+  for_each = toset(["a", "b"])
+}   // managed by team
+
+import {
+  to = aws_instance.db
+  id = "i-0123456789"
+} /* adopted */
+
+resource "aws_instance" "db" {}
+"#;
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut temp_file, content.as_bytes()).unwrap();
+        project.parse_file(temp_file.path()).unwrap();
+
+        let resources = project.get_all_resources();
+        assert_eq!(resources.len(), 3, "Expected web, app and db");
+        let web = resources.iter().find(|r| r.name == "web").unwrap();
+        assert!(web.has_count && !web.has_for_each);
+        let app = resources.iter().find(|r| r.name == "app").unwrap();
+        assert!(app.has_for_each && !app.has_count);
+        let db = resources.iter().find(|r| r.name == "db").unwrap();
+        assert!(!db.has_count && !db.has_for_each);
+
+        let imports = project.pending_imports(std::slice::from_ref(db));
+        assert_eq!(imports.len(), 1);
+        assert_eq!(imports[0].id, "i-0123456789");
+    }
+
+    #[test]
+    fn test_parse_import_blocks() {
+        let mut project = TerraformProject::new();
+        let content = r#"
+        import {
+          to = aws_instance.web
+          id = "i-0123456789"
+        }
+
+        resource "aws_instance" "web" {
+          ami = "ami-123456"
+        }
+
+        resource "aws_instance" "app" {
+          ami = "ami-123456"
+        }
+        "#;
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut temp_file, content.as_bytes()).unwrap();
+        project.parse_file(temp_file.path()).unwrap();
+
+        let web = project
+            .get_resources_by_target(&Target::Resource(
+                "aws_instance".to_string(),
+                "web".to_string(),
+            ))
+            .unwrap();
+        let imports = project.pending_imports(&web);
+        assert_eq!(imports.len(), 1, "Expected one pending import");
+        assert_eq!(imports[0].to, "aws_instance.web");
+        assert_eq!(imports[0].id, "i-0123456789");
+
+        let app = project
+            .get_resources_by_target(&Target::Resource(
+                "aws_instance".to_string(),
+                "app".to_string(),
+            ))
+            .unwrap();
+        assert!(project.pending_imports(&app).is_empty());
+        assert!(project.unconfigured_imports().is_empty());
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        std::io::Write::write_all(
+            &mut temp_file,
+            b"import {\n  to = aws_s3_bucket.logs\n  id = \"logs\"\n}\n",
+        )
+        .unwrap();
+        project.parse_file(temp_file.path()).unwrap();
+        let unconfigured = project.unconfigured_imports();
+        assert_eq!(unconfigured.len(), 1);
+        assert_eq!(unconfigured[0].to, "aws_s3_bucket.logs");
+    }
+
+    #[test]
+    fn test_parse_output_blocks() {
+        let mut project = TerraformProject::new();
+        let content = r#"
+        output "vpc_id" {
+          value = aws_vpc.main.id
+        }
+
+        output "cluster_endpoint" {
+          value = aws_eks_cluster.main.endpoint
+        }
+        "#;
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut temp_file, content.as_bytes()).unwrap();
+        project.parse_file(temp_file.path()).unwrap();
+
+        let names: Vec<String> = project.get_outputs().into_iter().map(|o| o.name).collect();
+        assert_eq!(names, vec!["cluster_endpoint", "vpc_id"]);
+        assert_eq!(project.get_output("vpc_id").unwrap().line, 2);
+
+        let err = project.get_output("vpc_ud").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "No resource found for output 'vpc_ud' (did you mean 'vpc_id'?)"
+        );
+    }
+
+    #[test]
+    fn test_parse_data_sources_and_filter_by_kind() {
+        let mut project = TerraformProject::new();
+        let content = r#"
+        data "aws_ami" "ubuntu" {
+          most_recent = true
+        }
+
+        resource "aws_instance" "web" {
+          ami = data.aws_ami.ubuntu.id
+        }
+
+        module "vpc" {
+          source = "./vpc"
+        }
+        "#;
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut temp_file, content.as_bytes()).unwrap();
+        project.parse_file(temp_file.path()).unwrap();
+
+        let names = |kinds: &[ResourceKind]| -> Vec<String> {
+            project
+                .get_resources_of_kinds(kinds)
+                .iter()
+                .map(|r| r.full_name())
+                .collect()
+        };
+        assert_eq!(
+            names(&[]),
+            vec!["module.vpc", "aws_instance.web", "data.aws_ami.ubuntu"]
+        );
+        assert_eq!(names(&[ResourceKind::Data]), vec!["data.aws_ami.ubuntu"]);
+        assert_eq!(
+            names(&[ResourceKind::Module, ResourceKind::Resource]),
+            vec!["module.vpc", "aws_instance.web"]
+        );
+
+        let data = project
+            .get_resources_by_target(&Target::Data("aws_ami".to_string(), "ubuntu".to_string()))
+            .unwrap();
+        assert_eq!(data.len(), 1);
+        assert!(project
+            .get_resources_by_target(&Target::Resource(
+                "aws_ami".to_string(),
+                "ubuntu".to_string()
+            ))
+            .is_err());
+    }
+
+    #[test]
+    fn test_is_full_selection() {
+        let (project, file) = project_with_vpc();
+        let all = project
+            .get_resources_by_target(&Target::File(file.path().to_path_buf()))
+            .unwrap();
+        assert!(project.is_full_selection(&all));
+
+        let vpc = project
+            .get_resources_by_target(&Target::Module("vpc".to_string()))
+            .unwrap();
+        assert!(!project.is_full_selection(&vpc));
+    }
+
+    fn quiet() -> ParseOptions {
+        ParseOptions {
+            quiet: true,
+            ..ParseOptions::default()
+        }
+    }
+
+    fn opentofu_fixture() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let files = [
+            (
+                "main.tf",
+                "resource \"aws_instance\" \"web\" {\n  ami = \"ami\"\n}\n",
+            ),
+            (
+                "network.tf",
+                "resource \"aws_vpc\" \"tf\" {\n  cidr_block = \"\"\n}\n",
+            ),
+            (
+                "network.tofu",
+                "resource \"aws_vpc\" \"tofu\" {\n  cidr_block = \"\"\n}\n",
+            ),
+            (
+                "storage.tofu.json",
+                r#"{"resource": {"aws_s3_bucket": {"logs": {"bucket": "logs", "count": 2}}}}"#,
+            ),
+            (
+                "app.tf.json",
+                r#"{"module": {"app": [{"source": "./app"}]}, "output": {"url": {"value": "x"}}}"#,
+            ),
+        ];
+        for (name, content) in files {
+            std::fs::write(dir.path().join(name), content).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_tofu_files_ignored_by_default() {
+        let dir = opentofu_fixture();
+        let project = TerraformProject::parse_path(dir.path(), &quiet()).unwrap();
+        let mut names: Vec<String> = project
+            .get_all_resources()
+            .into_iter()
+            .map(|r| r.name)
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["tf", "web"]);
+    }
+
+    #[test]
+    fn test_opentofu_mode_discovers_tofu_and_json_files() {
+        let dir = opentofu_fixture();
+        let options = ParseOptions {
+            opentofu: true,
+            ..quiet()
+        };
+        let project = TerraformProject::parse_path(dir.path(), &options).unwrap();
+        let resources = project.get_all_resources();
+        let mut names: Vec<&str> = resources.iter().map(|r| r.name.as_str()).collect();
+        names.sort();
+
+        // network.tofu shadows network.tf
+        assert_eq!(names, vec!["app", "logs", "tofu", "web"]);
+        let logs = resources.iter().find(|r| r.name == "logs").unwrap();
+        assert_eq!(logs.resource_type, "aws_s3_bucket");
+        assert!(logs.has_count);
+        assert!(resources.iter().any(|r| r.is_module && r.name == "app"));
+        assert_eq!(project.get_outputs()[0].name, "url");
+    }
+
+    #[test]
+    fn test_mark_tainted_resources_and_modules() {
+        let (mut project, _file) = project_with_vpc();
+        project.mark_tainted(&[
+            "aws_vpc.main[0]".to_string(),
+            "module.vpc.aws_subnet.private[\"a\"]".to_string(),
+        ]);
+        assert!(project.get_all_resources().iter().all(|r| r.tainted));
+
+        let (mut project, _file) = project_with_vpc();
+        project.mark_tainted(&["aws_vpc.other".to_string()]);
+        assert!(project.get_all_resources().iter().all(|r| !r.tainted));
+    }
+
+    #[test]
+    fn test_unreadable_file_is_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        let main = dir.path().join("main.tf");
+        fs::write(&main, "resource \"a_b\" \"open\" {\n}\n").unwrap();
+        // A directory cannot be read as a file, whoever runs the test
+        let unreadable = dir.path().join("broken.tf");
+        fs::create_dir(&unreadable).unwrap();
+
+        let mut project = TerraformProject::new();
+        project.parse_found_file(&unreadable, true).unwrap();
+        project.parse_found_file(&main, true).unwrap();
+        assert_eq!(project.get_parsed_files(), std::slice::from_ref(&main));
+        assert_eq!(project.get_all_resources().len(), 1);
+
+        assert!(matches!(
+            project.parse_file(&unreadable),
+            Err(TfocusError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn test_circular_module_sources_terminate() {
+        let dir = tempfile::tempdir().unwrap();
+        for (name, other) in [("a", "b"), ("b", "a")] {
+            fs::create_dir(dir.path().join(name)).unwrap();
+            fs::write(
+                dir.path().join(name).join("main.tf"),
+                format!("module \"{}\" {{\n  source = \"../{}\"\n}}\n", other, other),
+            )
+            .unwrap();
+        }
+        fs::write(
+            dir.path().join("main.tf"),
+            "module \"a\" {\n  source = \"./a\"\n}\n",
+        )
+        .unwrap();
+
+        let options = ParseOptions {
+            quiet: true,
+            ..ParseOptions::default()
+        };
+        let project = TerraformProject::parse_directory(dir.path(), &options).unwrap();
+        let root = fs::canonicalize(dir.path()).unwrap();
+        assert_eq!(
+            project.module_source_cycles(),
+            vec![vec![root.join("a"), root.join("b"), root.join("a")]]
+        );
+        assert_eq!(
+            project.warnings,
+            vec![format!(
+                "circular module sources, not followed further: {} -> {} -> {}",
+                root.join("a").display(),
+                root.join("b").display(),
+                root.join("a").display()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_submodules_scanned_unless_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |rel: &str, content: &str| {
+            let path = dir.path().join(rel);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        };
+        write(
+            "main.tf",
+            "module \"shared\" {\n  source = \"./vendor/shared\"\n}\n",
+        );
+        write(
+            ".gitmodules",
+            "[submodule \"shared\"]\n\tpath = vendor/shared\n\turl = https://example.com/shared.git\n",
+        );
+        write("vendor/shared/.git", "gitdir: ../../.git/modules/shared\n");
+        write(
+            "vendor/shared/nested/main.tf",
+            "resource \"aws_s3_bucket\" \"shared\" {\n  bucket = \"shared\"\n}\n",
+        );
+        write(
+            ".git/modules/shared/ignored.tf",
+            "resource \"aws_s3_bucket\" \"git\" {\n  bucket = \"git\"\n}\n",
+        );
+
+        let names = |options: &ParseOptions| -> Vec<String> {
+            let project = TerraformProject::parse_path(dir.path(), options).unwrap();
+            let mut names: Vec<String> = project
+                .get_all_resources()
+                .into_iter()
+                .map(|r| r.name)
+                .collect();
+            names.sort();
+            names
+        };
+
+        assert_eq!(names(&quiet()), vec!["shared", "shared"]);
+        let options = ParseOptions {
+            skip_submodules: true,
+            ..quiet()
+        };
+        assert_eq!(names(&options), vec!["shared"]);
+    }
+
+    #[test]
+    fn test_get_matching_resources() {
+        let (project, _file) = project_with_vpc();
+        let filter = Regex::new("^aws_").unwrap();
+        let matching = project.get_matching_resources(&[], Some(&filter));
+        assert_eq!(matching.len(), 1);
+        assert_eq!(matching[0].full_name(), "aws_vpc.main");
+        assert_eq!(project.get_matching_resources(&[], None).len(), 2);
+        assert!(project
+            .get_matching_resources(&[ResourceKind::Module], Some(&filter))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_parse_depends_on() {
+        let mut project = TerraformProject::new();
+        let content = r#"
+        resource "aws_instance" "web" {
+          ami        = "ami-123456"
+          depends_on = [
+            aws_vpc.main,
+            module.network,
+          ]
+        }
+
+        module "app" {
+          source     = "./app"
+          depends_on = [aws_instance.web]
+        }
+        "#;
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut temp_file, content.as_bytes()).unwrap();
+        project.parse_file(temp_file.path()).unwrap();
+
+        let resources = project.get_all_resources();
+        let web = resources.iter().find(|r| r.name == "web").unwrap();
+        assert_eq!(web.depends_on, vec!["aws_vpc.main", "module.network"]);
+        let app = resources.iter().find(|r| r.name == "app").unwrap();
+        assert_eq!(app.depends_on, vec!["aws_instance.web"]);
+    }
+
+    #[test]
+    fn test_get_resources_by_line_range() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("main.tf");
+        fs::write(
+            &path,
+            r#"resource "aws_instance" "web" {
+  ami = "ami-123456"
+}
+
+resource "aws_instance" "api" {
+  ami = "ami-123456"
+}
+
+module "vpc" {
+  source = "./vpc"
+}
+"#,
+        )
+        .unwrap();
+        let project =
+            TerraformProject::parse_directory(dir.path(), &ParseOptions::default()).unwrap();
+
+        let names = |start: usize, end: usize| -> Vec<String> {
+            project
+                .get_resources_by_target(&Target::LineRange(path.clone(), start, end))
+                .map(|found| found.iter().map(|r| r.full_name()).collect())
+                .unwrap_or_default()
+        };
+
+        assert_eq!(names(1, 5), vec!["aws_instance.web", "aws_instance.api"]);
+        assert_eq!(names(2, 9), vec!["aws_instance.api", "module.vpc"]);
+        assert!(names(2, 4).is_empty());
+        assert!(project
+            .get_resources_by_target(&Target::LineRange(PathBuf::from("other.tf"), 1, 20))
+            .is_err());
+    }
+
+    #[test]
+    fn test_get_state_addresses() {
+        let resource = |name: &str, index: Option<&str>, for_each: bool| Resource {
+            has_count: index.is_some() && !for_each,
+            has_for_each: for_each,
+            index: index.map(str::to_string),
+            ..Resource::for_test("aws_instance", name)
+        };
+        let mut project = TerraformProject::from_resources(vec![
+            resource("web", Some("0-1"), false),
+            resource("api", Some("blue \"b\""), true),
+            resource("api", Some("blue \"b\""), true),
+            Resource {
+                is_data: true,
+                ..resource("ami", None, false)
+            },
+        ]);
+        project.resources.push(Resource {
+            resource_type: String::new(),
+            is_module: true,
+            ..resource("vpc", None, false)
+        });
+
+        assert_eq!(
+            project.get_state_addresses(&[], None).unwrap(),
+            vec![
+                r#"aws_instance.api["blue \"b\""]"#,
+                "aws_instance.web[0]",
+                "aws_instance.web[1]",
+                "data.aws_instance.ami",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_get_resources_by_prefix() {
+        let mut project = TerraformProject::new();
+        let content = r#"
+        module "platform" {
+          source = "./platform"
+        }
+
+        module "platform_dns" {
+          source = "./dns"
+        }
+
+        resource "aws_iam_role" "app" {
+          name = "app"
+        }
+
+        resource "aws_iam_policy" "app" {
+          name = "app"
+        }
+
+        resource "aws_instance" "web" {
+          ami = "ami-123456"
+        }
+        "#;
+        let mut temp_file = NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut temp_file, content.as_bytes()).unwrap();
+        project.parse_file(temp_file.path()).unwrap();
+
+        let names = |prefix: &str| -> Vec<String> {
+            let mut names: Vec<String> = project
+                .get_resources_by_target(&Target::Prefix(prefix.to_string()))
+                .unwrap()
+                .iter()
+                .map(|r| r.full_name())
+                .collect();
+            names.sort();
+            names
+        };
+
+        assert_eq!(names("module.platform"), vec!["module.platform"]);
+        assert_eq!(names("module.platform_dns"), vec!["module.platform_dns"]);
+        assert_eq!(names("module.platform_"), vec!["module.platform_dns"]);
+        assert_eq!(
+            names("aws_iam_"),
+            vec!["aws_iam_policy.app", "aws_iam_role.app"]
+        );
+        assert_eq!(names("aws_instance.web"), vec!["aws_instance.web"]);
+        assert!(project
+            .get_resources_by_target(&Target::Prefix("google_".to_string()))
+            .is_err());
+        assert!(project
+            .get_resources_by_target(&Target::Prefix("aws_inst".to_string()))
+            .is_err());
+
+        assert!(has_address_prefix(
+            "module.platform.aws_vpc.main",
+            "module.platform"
+        ));
+        assert!(has_address_prefix(
+            "module.platform[\"eu\"].aws_vpc.main",
+            "module.platform"
+        ));
+        assert!(!has_address_prefix(
+            "module.platform_dns",
+            "module.platform"
+        ));
+    }
+
+    #[test]
+    fn test_parse_single_line_blocks() {
+        let mut project = TerraformProject::new();
+        let content = r#"
+resource "null_resource" "x" { triggers = {} }
+resource "aws_instance" "web" { count = 2 }
+module "app" { source = "./app" }
+data "aws_ami" "ubuntu" { most_recent = true }
+resource "aws_s3_bucket" "logs" {
+  bucket = "logs-{env}"
+  policy = <<POLICY
+{ "Statement": [ }
+POLICY
+  for_each = toset(["a"])
+}
+"#;
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut temp_file, content.as_bytes()).unwrap();
+        project.parse_file(temp_file.path()).unwrap();
+
+        let resources = project.get_all_resources();
+        let find = |name: &str| resources.iter().find(|r| r.name == name).unwrap();
+        assert_eq!(resources.len(), 5);
+        assert!(!find("x").has_count);
+        assert!(find("web").has_count);
+        assert_eq!(find("app").source.as_deref(), Some("./app"));
+        assert!(find("ubuntu").is_data);
+        assert!(find("logs").has_for_each);
+        assert_eq!(find("logs").line, 6);
+    }
+
+    #[test]
+    fn test_meta_arguments_only_at_top_level() {
+        let mut project = TerraformProject::new();
+        let content = r#"
+resource "aws_security_group" "dynamic_only" {
+  dynamic "ingress" {
+    for_each = var.ports
+    content {
+      from_port = ingress.value
+    }
+  }
+  tags = { count = "1" }
+}
+
+resource "aws_security_group" "both" {
+  for_each = var.groups
+  dynamic "ingress" {
+    for_each = each.value.ports
+    content {}
+  }
+}
+
+resource "aws_instance" "commented" {
+  # count = 2
+  ami = "count = 3"
+}
+"#;
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut temp_file, content.as_bytes()).unwrap();
+        project.parse_file(temp_file.path()).unwrap();
+
+        let resources = project.get_all_resources();
+        let find = |name: &str| resources.iter().find(|r| r.name == name).unwrap();
+        assert!(!find("dynamic_only").has_for_each);
+        assert!(!find("dynamic_only").has_count);
+        assert!(find("both").has_for_each);
+        assert!(!find("commented").has_count);
+    }
+
+    #[test]
+    fn test_stream_path_matches_parse_path() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("a.tf"),
+            "resource \"aws_vpc\" \"main\" {\n}\nmodule \"app\" {\n  source = \"./app\"\n}\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("b.tf"),
+            "data \"aws_ami\" \"ubuntu\" {\n}\n",
+        )
+        .unwrap();
+
+        let mut streamed: Vec<Resource> = TerraformProject::stream_path(dir.path(), &quiet())
+            .unwrap()
+            .collect::<Result<_>>()
+            .unwrap();
+        let project = TerraformProject::parse_path(dir.path(), &quiet()).unwrap();
+        let mut parsed: Vec<Resource> = project.resources_iter().cloned().collect();
+
+        streamed.sort_by_key(|r| r.full_name());
+        parsed.sort_by_key(|r| r.full_name());
+        assert_eq!(streamed, parsed);
+        assert_eq!(streamed.len(), 3);
+
+        let empty = tempfile::tempdir().unwrap();
+        assert!(matches!(
+            TerraformProject::stream_path(empty.path(), &quiet()),
+            Err(TfocusError::EmptyDirectory { .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_missing_or_empty_directory() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let missing = dir.path().join("missing");
+        assert!(matches!(
+            TerraformProject::parse_path(&missing, &quiet()),
+            Err(TfocusError::PathNotFound(path)) if path == missing.display().to_string()
+        ));
+
+        match TerraformProject::parse_path(dir.path(), &quiet()) {
+            Err(TfocusError::EmptyDirectory { hint, .. }) => {
+                assert_eq!(hint, "did you mean to pass the module directory?")
+            }
+            other => panic!("expected EmptyDirectory, got {:?}", other.err()),
+        }
+
+        // An initialized directory whose configuration lives elsewhere
+        std::fs::create_dir(dir.path().join(".terraform")).unwrap();
+        std::fs::write(dir.path().join(".terraform/modules.tf"), "module \"x\" {}").unwrap();
+        match TerraformProject::parse_path(dir.path(), &quiet()) {
+            Err(TfocusError::EmptyDirectory { hint, .. }) => assert!(hint.contains(".terraform")),
+            other => panic!("expected EmptyDirectory, got {:?}", other.err()),
+        }
+    }
+
+    #[test]
+    fn test_parse_required_version() {
+        let mut project = TerraformProject::new();
+        let content = r#"
+terraform {
+  required_version = ">= 1.6, < 2.0"
+
+  required_providers {
+    aws = {
+      source  = "hashicorp/aws"
+      version = "~> 5.0"
+    }
+  }
+}
+"#;
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut temp_file, content.as_bytes()).unwrap();
+        project.parse_file(temp_file.path()).unwrap();
+
+        let requirements = project.get_version_requirements();
+        assert_eq!(requirements.len(), 1);
+        assert_eq!(requirements[0].constraint, ">= 1.6, < 2.0");
+        assert_eq!(requirements[0].file_path, temp_file.path());
+
+        let providers = project.get_provider_requirements();
+        assert_eq!(providers.len(), 1);
+        assert_eq!(providers[0].name, "aws");
+        assert_eq!(providers[0].source.as_deref(), Some("hashicorp/aws"));
+        assert_eq!(providers[0].version.as_deref(), Some("~> 5.0"));
+    }
+
+    #[test]
+    fn test_parse_backend() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("backend.tf"),
+            "terraform {\n  required_version = \">= 1.6\"\n  backend \"s3\" {\n    bucket = \"state\"\n  }\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("cloud.tf.json"),
+            r#"{"terraform": {"cloud": {"organization": "acme"}}}"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("gcs.tf.json"),
+            r#"{"terraform": [{"backend": [{"gcs": {"bucket": "state"}}]}]}"#,
+        )
+        .unwrap();
+        fs::write(dir.path().join("main.tf"), "terraform {\n}\n").unwrap();
+
+        let options = ParseOptions {
+            quiet: true,
+            opentofu: true,
+            ..ParseOptions::default()
+        };
+        let project = TerraformProject::parse_directory(dir.path(), &options).unwrap();
+        let backends: Vec<&str> = project
+            .get_backends()
+            .iter()
+            .map(|b| b.backend_type.as_str())
+            .collect();
+        assert_eq!(backends, vec!["s3", "cloud", "gcs"]);
+        assert_eq!(
+            project.get_backends()[0].file_path,
+            dir.path().join("backend.tf")
+        );
+    }
+
+    #[test]
+    fn test_recent_first_orders_by_mtime() {
+        let dir = tempfile::tempdir().unwrap();
+        let now = std::time::SystemTime::now();
+        for (name, age) in [("a.tf", 300), ("b.tf", 100), ("c.tf", 200)] {
+            let path = dir.path().join(name);
+            std::fs::write(
+                &path,
+                format!("resource \"null_resource\" \"{}\" {{\n}}\n", &name[..1]),
+            )
+            .unwrap();
+            std::fs::File::options()
+                .write(true)
+                .open(&path)
+                .unwrap()
+                .set_modified(now - std::time::Duration::from_secs(age))
+                .unwrap();
+        }
+
+        let names = |project: &TerraformProject| -> Vec<String> {
+            project
+                .get_all_resources()
+                .iter()
+                .map(|r| r.name.clone())
+                .collect()
+        };
+        let project = TerraformProject::parse_path(dir.path(), &quiet()).unwrap();
+        assert_eq!(names(&project), vec!["a", "b", "c"]);
+
+        let options = ParseOptions {
+            recent_first: true,
+            ..quiet()
+        };
+        let project = TerraformProject::parse_path(dir.path(), &options).unwrap();
+        assert_eq!(names(&project), vec!["b", "c", "a"]);
+        assert_eq!(
+            project.get_unique_files(),
+            vec![
+                dir.path().join("b.tf"),
+                dir.path().join("c.tf"),
+                dir.path().join("a.tf")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_roots() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |rel: &str, content: &str| {
+            let path = dir.path().join(rel);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        };
+        write(
+            "envs/prod/backend.tf",
+            "terraform {\n  backend \"s3\" {\n    bucket = \"state\"\n  }\n}\n",
+        );
+        write(
+            "envs/prod/main.tf",
+            "module \"app\" {\n  source = \"../../modules/app\"\n}\n",
+        );
+        write(
+            "envs/dev/main.tf",
+            "module \"app\" {\n  source = \"../../modules/app\"\n}\n",
+        );
+        write("envs/dev/.terraform.lock.hcl", "");
+        write(
+            "modules/app/main.tf",
+            "terraform {\n  required_version = \">= 1.5\"\n}\n",
+        );
+
+        let roots = TerraformProject::find_roots(dir.path(), &quiet()).unwrap();
+        assert_eq!(
+            roots,
+            vec![dir.path().join("envs/dev"), dir.path().join("envs/prod")]
+        );
+    }
+
+    fn project_with_vpc() -> (TerraformProject, NamedTempFile) {
+        let mut project = TerraformProject::new();
+        let content = r#"
+        resource "aws_vpc" "main" {
+          cidr_block = "10.0.0.0/16"
+        }
+
+        module "vpc" {
+          source = "./modules/vpc"
+        }
+        "#;
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut temp_file, content.as_bytes()).unwrap();
+        project.parse_file(temp_file.path()).unwrap();
+        (project, temp_file)
+    }
+
+    #[test]
+    fn test_unmatched_module_suggests_similar() {
+        let (project, _file) = project_with_vpc();
+        let err = project
+            .get_resources_by_target(&Target::Module("vcp".to_string()))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "No resource found for module 'vcp' (did you mean 'vpc'?)"
+        );
+    }
+
+    #[test]
+    fn test_unmatched_resource_suggests_similar() {
+        let (project, _file) = project_with_vpc();
+        let err = project
+            .get_resources_by_target(&Target::Resource("aws_vpc".to_string(), "mian".to_string()))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "No resource found for resource 'aws_vpc.mian' (did you mean 'aws_vpc.main'?)"
+        );
+    }
+
+    #[test]
+    fn test_unmatched_file_reports_target() {
+        let (project, _file) = project_with_vpc();
+        let err = project
+            .get_resources_by_target(&Target::File(PathBuf::from("/nonexistent/other.tf")))
+            .unwrap_err();
+        assert!(matches!(err, TfocusError::NoMatchingTarget { .. }));
+        assert!(err
+            .to_string()
+            .starts_with("No resource found for file '/nonexistent/other.tf'"));
+    }
+
+    #[test]
+    fn test_levenshtein() {
+        assert_eq!(levenshtein("vpc", "vpc"), 0);
+        assert_eq!(levenshtein("vpc", "vcp"), 2);
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+    }
 }
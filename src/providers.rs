@@ -0,0 +1,257 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use crate::display::Display;
+use crate::error::Result;
+use crate::project::{ParseOptions, TerraformProject};
+use crate::types::ProviderRequirement;
+
+/// A provider required somewhere in the module tree, merged by local name
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ProviderUsage {
+    pub name: String,
+    pub sources: BTreeSet<String>,
+    /// Distinct version constraints in the order found; terraform requires all of them
+    pub constraints: Vec<String>,
+    /// Directories of the modules that require the provider
+    pub required_by: BTreeSet<PathBuf>,
+}
+
+/// Collects the providers required by `project` and, recursively, by the local modules it calls
+///
+/// Module directories are read once each, so circular sources end the walk.
+/// Modules from registries or git are not followed.
+pub fn collect(project: &TerraformProject, options: &ParseOptions) -> Result<Vec<ProviderUsage>> {
+    let mut requirements = project.get_provider_requirements().to_vec();
+    // Directories under the scan root were parsed with the project already
+    let mut visited: HashSet<PathBuf> = project
+        .get_parsed_files()
+        .iter()
+        .filter_map(|file| fs::canonicalize(file.parent()?).ok())
+        .collect();
+    let mut pending: Vec<PathBuf> = project
+        .local_module_sources()
+        .into_iter()
+        .map(|(_, source)| source)
+        .collect();
+
+    while let Some(dir) = pending.pop() {
+        if !visited.insert(dir.clone()) {
+            continue;
+        }
+        let module = TerraformProject::parse_module_dir(&dir, options)?;
+        requirements.extend_from_slice(module.get_provider_requirements());
+        pending.extend(
+            module
+                .local_module_sources()
+                .into_iter()
+                .map(|(_, source)| source),
+        );
+    }
+    Ok(merge(&requirements))
+}
+
+/// Merges requirements by provider name, sorted by name
+fn merge(requirements: &[ProviderRequirement]) -> Vec<ProviderUsage> {
+    let mut usages: BTreeMap<&str, ProviderUsage> = BTreeMap::new();
+    for requirement in requirements {
+        let usage = usages
+            .entry(&requirement.name)
+            .or_insert_with(|| ProviderUsage {
+                name: requirement.name.clone(),
+                ..ProviderUsage::default()
+            });
+        usage.sources.extend(requirement.source.clone());
+        for constraint in requirement.version.iter().flat_map(|v| v.split(',')) {
+            let constraint = constraint.trim();
+            if !constraint.is_empty() && !usage.constraints.iter().any(|c| c == constraint) {
+                usage.constraints.push(constraint.to_string());
+            }
+        }
+        if let Some(dir) = requirement.file_path.parent() {
+            usage
+                .required_by
+                .insert(fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf()));
+        }
+    }
+    usages.into_values().collect()
+}
+
+/// Prints one row per provider: name, source, merged constraint and requiring modules
+///
+/// Module directories are shown relative to `root`.
+pub fn print_table(usages: &[ProviderUsage], root: &Path) {
+    if usages.is_empty() {
+        println!("No required_providers found");
+        return;
+    }
+    let root = fs::canonicalize(root).unwrap_or_else(|_| root.to_path_buf());
+    let rows: Vec<[String; 4]> = usages
+        .iter()
+        .map(|usage| {
+            let required_by: Vec<String> = usage
+                .required_by
+                .iter()
+                .map(|dir| relative_path(dir, &root).display().to_string())
+                .collect();
+            [
+                usage.name.clone(),
+                join_or_dash(usage.sources.iter()),
+                join_or_dash(usage.constraints.iter()),
+                required_by.join(", "),
+            ]
+        })
+        .collect();
+
+    let header = ["PROVIDER", "SOURCE", "VERSION", "REQUIRED BY"];
+    let mut widths = header.map(str::len);
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+    let line = |cells: [&str; 4]| {
+        format!(
+            "{:w0$}  {:w1$}  {:w2$}  {}",
+            cells[0],
+            cells[1],
+            cells[2],
+            cells[3],
+            w0 = widths[0],
+            w1 = widths[1],
+            w2 = widths[2]
+        )
+    };
+    println!("{}", Display::header_style(&line(header)));
+    for row in &rows {
+        println!("{}", line([&row[0], &row[1], &row[2], &row[3]]));
+    }
+}
+
+/// Returns `path` relative to `base`, going up with `..` where needed
+///
+/// Both paths must be absolute; `base` itself becomes `.`.
+fn relative_path(path: &Path, base: &Path) -> PathBuf {
+    let path: Vec<_> = path.components().collect();
+    let base: Vec<_> = base.components().collect();
+    let common = path.iter().zip(&base).take_while(|(a, b)| a == b).count();
+    let relative: PathBuf = std::iter::repeat(Component::ParentDir)
+        .take(base.len() - common)
+        .chain(path[common..].iter().copied())
+        .collect();
+    if relative.as_os_str().is_empty() {
+        PathBuf::from(".")
+    } else {
+        relative
+    }
+}
+
+fn join_or_dash<'a>(items: impl Iterator<Item = &'a String>) -> String {
+    let items: Vec<&str> = items.map(String::as_str).collect();
+    if items.is_empty() {
+        "-".to_string()
+    } else {
+        items.join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relative_path() {
+        let root = Path::new("/infra/live");
+        assert_eq!(
+            relative_path(Path::new("/infra/live"), root),
+            Path::new(".")
+        );
+        assert_eq!(
+            relative_path(Path::new("/infra/live/modules/vpc"), root),
+            Path::new("modules/vpc")
+        );
+        assert_eq!(
+            relative_path(Path::new("/infra/shared/network"), root),
+            Path::new("../shared/network")
+        );
+    }
+
+    #[test]
+    fn test_collect_recurses_into_local_modules() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |rel: &str, content: &str| {
+            let path = dir.path().join(rel);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        };
+        write(
+            "live/main.tf",
+            r#"
+terraform {
+  required_providers {
+    aws = {
+      source  = "hashicorp/aws"
+      version = "~> 5.0"
+    }
+  }
+}
+
+module "network" {
+  source = "../shared/network"
+}
+"#,
+        );
+        write(
+            "shared/network/main.tf",
+            r#"
+terraform {
+  required_providers {
+    aws = {
+      source  = "hashicorp/aws"
+      version = ">= 5.10, ~> 5.0"
+    }
+    random = "~> 3.0"
+  }
+}
+
+module "subnets" {
+  source = "./subnets"
+}
+"#,
+        );
+        // Calls back into its parent, which must not loop
+        write(
+            "shared/network/subnets/main.tf",
+            r#"
+terraform {
+  required_providers {
+    null = { source = "hashicorp/null" }
+  }
+}
+
+module "parent" {
+  source = "../"
+}
+"#,
+        );
+
+        let live = dir.path().join("live");
+        let options = ParseOptions {
+            quiet: true,
+            ..ParseOptions::default()
+        };
+        let project = TerraformProject::parse_path(&live, &options).unwrap();
+        let usages = collect(&project, &options).unwrap();
+
+        let names: Vec<&str> = usages.iter().map(|u| u.name.as_str()).collect();
+        assert_eq!(names, vec!["aws", "null", "random"]);
+        let aws = &usages[0];
+        assert_eq!(aws.sources, BTreeSet::from(["hashicorp/aws".to_string()]));
+        assert_eq!(aws.constraints, vec!["~> 5.0", ">= 5.10"]);
+        assert_eq!(aws.required_by.len(), 2);
+        assert_eq!(usages[1].constraints, Vec::<String>::new());
+        assert_eq!(usages[2].constraints, vec!["~> 3.0"]);
+        assert!(usages[2].sources.is_empty());
+    }
+}
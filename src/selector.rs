@@ -1,19 +1,39 @@
 use crate::error::Result;
+use crate::theme::{self, Theme};
+use crate::types::Resource;
 use crossterm::{
     cursor,
     event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
     execute,
-    style::{self, Stylize},
+    style::{self, StyledContent, Stylize},
     terminal::{self, ClearType},
 };
 use fuzzy_matcher::skim::SkimMatcherV2;
 use fuzzy_matcher::FuzzyMatcher;
+use std::collections::HashMap;
 use std::io::{stdout, Write};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 pub struct SelectItem {
-    pub display: String,     // 表示用の文字列
-    pub search_text: String, // 検索用の文字列
-    pub data: String,        // 選択時に返すデータ
+    pub display: String,            // 表示用の文字列
+    pub search_text: String,        // 検索用の文字列
+    pub data: String,               // 選択時に返すデータ
+    pub resource: Option<Resource>, // 詳細フッター用のリソース
+    /// The full target address, shown in the footer when `display` abbreviates it
+    pub address: Option<String>,
+}
+
+/// The outcome of running a [`Selector`]
+#[derive(Debug, PartialEq, Eq)]
+pub enum Selection {
+    /// An item was selected; holds its data
+    Selected(String),
+    /// Several items were marked; holds their data in the order they were marked
+    SelectedMany(Vec<String>),
+    /// Esc was pressed on a selector that allows going back
+    Back,
+    /// The user cancelled the selection
+    Cancelled,
 }
 
 pub struct Selector {
@@ -23,8 +43,31 @@ pub struct Selector {
     filtered_items: Vec<usize>,
     matcher: SkimMatcherV2,
     window_size: usize,
+    allow_back: bool,
+    multi_select: bool,
+    /// Indices into `items`, in the order the user marked them
+    marked: Vec<usize>,
+    /// Rebuilds the items when Ctrl+R is pressed
+    reload: Option<ReloadFn>,
+    /// Query tokens expanded before matching (e.g. `ec2` -> `aws_instance`)
+    aliases: HashMap<String, String>,
+    /// Colors for the focused line and query matches
+    theme: Theme,
+    /// Whether Ctrl+S may confirm the selection with a request to save it
+    allow_save: bool,
+    /// Set when the selection was confirmed with Ctrl+S
+    save_requested: bool,
+    /// A one-off message for the status line, cleared on the next key press
+    notice: Option<String>,
+    /// Whether the resource type panel is shown and has the keyboard (Ctrl+F)
+    facets_open: bool,
+    /// The highlighted row of the resource type panel
+    facet_selected: usize,
 }
 
+/// Produces a fresh item list for [`Selector::with_reload`]
+type ReloadFn = Box<dyn FnMut() -> Result<Vec<SelectItem>>>;
+
 impl Selector {
     pub fn new(items: Vec<SelectItem>) -> Self {
         let filtered_items: Vec<usize> = (0..items.len()).collect();
@@ -35,19 +78,206 @@ impl Selector {
             filtered_items,
             matcher: SkimMatcherV2::default(),
             window_size: 15,
+            allow_back: false,
+            multi_select: false,
+            marked: Vec::new(),
+            reload: None,
+            aliases: HashMap::new(),
+            theme: Theme::current(),
+            allow_save: false,
+            save_requested: false,
+            notice: None,
+            facets_open: false,
+            facet_selected: 0,
+        }
+    }
+
+    /// Sets query aliases; items match either the typed or the expanded query
+    pub fn with_aliases(mut self, aliases: HashMap<String, String>) -> Self {
+        self.aliases = aliases;
+        self
+    }
+
+    /// Returns the query with alias tokens expanded, if any token is an alias
+    fn expanded_query(&self, query: &str) -> Option<String> {
+        let mut expanded = false;
+        let tokens: Vec<&str> = query
+            .split_whitespace()
+            .map(|token| match self.aliases.get(token) {
+                Some(expansion) => {
+                    expanded = true;
+                    expansion.as_str()
+                }
+                None => token,
+            })
+            .collect();
+        expanded.then(|| tokens.join(" "))
+    }
+
+    /// Lets Ctrl+R replace the items with the result of `reload`
+    pub fn with_reload(
+        mut self,
+        reload: impl FnMut() -> Result<Vec<SelectItem>> + 'static,
+    ) -> Self {
+        self.reload = Some(Box::new(reload));
+        self
+    }
+
+    /// Replaces the items with the result of the reload callback
+    ///
+    /// A failed reload, e.g. of a file saved half-edited, keeps the current
+    /// items and reports the error on the status line.
+    fn reload_items(&mut self) {
+        let Some(reload) = self.reload.as_mut() else {
+            return;
+        };
+        match reload() {
+            Ok(items) => self.replace_items(items),
+            Err(e) => self.notice = Some(format!("Reload failed: {}", e)),
+        }
+    }
+
+    /// Replaces the items, keeping the current query and clearing marks
+    fn replace_items(&mut self, items: Vec<SelectItem>) {
+        self.items = items;
+        self.marked.clear();
+        self.filter_items();
+    }
+
+    /// Lets Tab mark several items; Enter then returns them in marking order
+    pub fn with_multi_select(mut self) -> Self {
+        self.multi_select = true;
+        self
+    }
+
+    /// Toggles the mark on the highlighted item
+    fn toggle_mark(&mut self) {
+        if let Some(&idx) = self.filtered_items.get(self.selected) {
+            if let Some(pos) = self.marked.iter().position(|&m| m == idx) {
+                self.marked.remove(pos);
+            } else {
+                self.marked.push(idx);
+            }
+        }
+    }
+
+    /// Unmarks every item that matches the current query, keeping other marks
+    fn unmark_filtered(&mut self) {
+        let filtered = &self.filtered_items;
+        self.marked.retain(|idx| !filtered.contains(idx));
+    }
+
+    /// Returns the target address of the highlighted item, if it has one
+    #[cfg(any(feature = "clipboard", test))]
+    fn highlighted_address(&self) -> Option<&str> {
+        self.filtered_items
+            .get(self.selected)
+            .and_then(|&idx| self.items[idx].address.as_deref())
+    }
+
+    /// Copies the highlighted address to the clipboard and reports the outcome
+    #[cfg(feature = "clipboard")]
+    fn copy_highlighted(&mut self) {
+        self.notice = Some(match self.highlighted_address() {
+            Some(address) => match crate::clipboard::copy(address) {
+                Ok(()) => format!("Copied {}", address),
+                Err(e) => format!("Copy failed: {}", e),
+            },
+            None => "Nothing to copy".to_string(),
+        });
+    }
+
+    /// Returns each resource type among the items with its count, most common first
+    fn facets(&self) -> Vec<(String, usize)> {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for resource in self.items.iter().filter_map(|item| item.resource.as_ref()) {
+            if !resource.is_module {
+                *counts.entry(resource.resource_type.as_str()).or_default() += 1;
+            }
+        }
+        let mut facets: Vec<(String, usize)> = counts
+            .into_iter()
+            .map(|(resource_type, count)| (resource_type.to_string(), count))
+            .collect();
+        facets.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        facets
+    }
+
+    /// Sets the query to the highlighted resource type and closes the panel
+    fn apply_facet(&mut self) {
+        if let Some((resource_type, _)) = self.facets().get(self.facet_selected) {
+            self.query = resource_type.clone();
+            self.selected = 0;
+            self.filter_items();
+        }
+        self.facets_open = false;
+    }
+
+    /// Handles a key while the resource type panel is open; returns whether it was used
+    fn handle_facet_key(&mut self, code: KeyCode, modifiers: KeyModifiers) -> bool {
+        match (code, modifiers) {
+            (KeyCode::Enter, _) => self.apply_facet(),
+            (KeyCode::Esc, _) | (KeyCode::Char('f'), KeyModifiers::CONTROL) => {
+                self.facets_open = false;
+            }
+            (KeyCode::Up, _) | (KeyCode::Char('k'), _) => {
+                self.facet_selected = self.facet_selected.saturating_sub(1);
+            }
+            (KeyCode::Down, _) | (KeyCode::Char('j'), _) => {
+                self.facet_selected =
+                    (self.facet_selected + 1).min(self.facets().len().saturating_sub(1));
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// Returns the result of pressing Enter on the current state
+    fn confirm(&self) -> Option<Selection> {
+        if !self.marked.is_empty() {
+            return Some(Selection::SelectedMany(
+                self.marked
+                    .iter()
+                    .map(|&idx| self.items[idx].data.clone())
+                    .collect(),
+            ));
         }
+        self.filtered_items
+            .get(self.selected)
+            .map(|&idx| Selection::Selected(self.items[idx].data.clone()))
+    }
+
+    /// Lets Ctrl+S confirm the selection like Enter and ask to save it
+    pub fn with_save(mut self) -> Self {
+        self.allow_save = true;
+        self
+    }
+
+    /// Returns whether the selection was confirmed with Ctrl+S
+    pub fn save_requested(&self) -> bool {
+        self.save_requested
+    }
+
+    /// Makes Esc return [`Selection::Back`] instead of cancelling
+    pub fn with_back(mut self) -> Self {
+        self.allow_back = true;
+        self
     }
 
     fn filter_items(&mut self) {
         let query = self.query.to_lowercase();
+        let expanded = self.expanded_query(&query);
         let mut matches: Vec<(usize, i64)> = self
             .items
             .iter()
             .enumerate()
             .filter_map(|(index, item)| {
-                self.matcher
-                    .fuzzy_match(&item.search_text.to_lowercase(), &query)
-                    .map(|score| (index, score))
+                let text = item.search_text.to_lowercase();
+                let typed = self.matcher.fuzzy_match(&text, &query);
+                let aliased = expanded
+                    .as_deref()
+                    .and_then(|q| self.matcher.fuzzy_match(&text, q));
+                typed.max(aliased).map(|score| (index, score))
             })
             .collect();
 
@@ -58,6 +288,23 @@ impl Selector {
             .min(self.filtered_items.len().saturating_sub(1));
     }
 
+    /// Returns the character positions in `text` that match the query
+    fn match_indices(&self, text: &str) -> Vec<usize> {
+        if self.query.is_empty() {
+            return Vec::new();
+        }
+        let text = text.to_lowercase();
+        let query = self.query.to_lowercase();
+        self.matcher
+            .fuzzy_indices(&text, &query)
+            .or_else(|| {
+                self.expanded_query(&query)
+                    .and_then(|q| self.matcher.fuzzy_indices(&text, &q))
+            })
+            .map(|(_, indices)| indices)
+            .unwrap_or_default()
+    }
+
     fn get_terminal_size() -> (u16, u16) {
         terminal::size().unwrap_or((80, 24))
     }
@@ -91,26 +338,53 @@ impl Selector {
 
         let end = (start + self.window_size).min(self.filtered_items.len());
 
+        let facets = if self.facets_open {
+            self.facets()
+        } else {
+            Vec::new()
+        };
+        // The panel takes up to a third of the width, to the right of the list
+        let panel_width = facets
+            .iter()
+            .map(|(resource_type, count)| resource_type.width() + count.to_string().len() + 3)
+            .max()
+            .map_or(0, |width| width.min(term_width as usize / 3));
+        let list_width = (term_width as usize).saturating_sub(panel_width);
+
         // アイテムリストの表示
         for i in start..end {
             let item_idx = self.filtered_items[i];
             let item = &self.items[item_idx];
+            let mark = if self.marked.contains(&item_idx) {
+                "●"
+            } else {
+                " "
+            };
 
-            if i == self.selected {
-                execute!(
-                    stdout,
-                    style::PrintStyledContent("▶ ".green()),
-                    style::PrintStyledContent(item.display.clone().green()),
-                    cursor::MoveToNextLine(1)
-                )?;
+            let (arrow, color) = if i == self.selected {
+                ("▶", self.theme.highlight)
             } else {
-                execute!(
-                    stdout,
-                    style::Print("  "),
-                    style::Print(&item.display),
-                    cursor::MoveToNextLine(1)
-                )?;
+                (" ", None)
+            };
+            execute!(
+                stdout,
+                style::PrintStyledContent(paint(format!("{}{}", arrow, mark), color))
+            )?;
+            // Long lines are cut rather than wrapped, which would shift the list
+            let shown = truncate(&item.display, list_width.saturating_sub(2));
+            for (run, matched) in highlight_runs(&shown, &self.match_indices(&item.display)) {
+                let run_color = if matched {
+                    self.theme.match_highlight.or(color)
+                } else {
+                    color
+                };
+                execute!(stdout, style::PrintStyledContent(paint(run, run_color)))?;
             }
+            execute!(stdout, cursor::MoveToNextLine(1))?;
+        }
+
+        if !facets.is_empty() {
+            self.render_facets(&facets, list_width as u16, panel_width)?;
         }
 
         // フッターの表示
@@ -124,22 +398,116 @@ impl Selector {
         }
 
         // ステータスラインの表示
-        let status = format!("{}/{} items", self.filtered_items.len(), self.items.len());
-        let help = "[↑/k]Up [↓/j]Down [Enter]Select [Esc/Ctrl+C]Cancel";
+        let mut status = format!("{}/{} items", self.filtered_items.len(), self.items.len());
+        if !self.marked.is_empty() {
+            status.push_str(&format!(" ({} marked)", self.marked.len()));
+        }
+        if let Some(notice) = &self.notice {
+            status.push_str(&format!("  {}", notice));
+        }
+        let help = self.help_line();
 
         execute!(
             stdout,
-            style::Print(&status),
-            cursor::MoveToColumn(term_width - help.len() as u16),
-            style::Print(help),
+            style::Print(status_line(&status, &help, term_width as usize)),
             cursor::MoveToNextLine(1)
         )?;
 
+        // 詳細フッターの表示
+        let focused = self
+            .filtered_items
+            .get(self.selected)
+            .map(|&idx| &self.items[idx]);
+        if let Some((item, resource)) =
+            focused.and_then(|item| item.resource.as_ref().map(|r| (item, r)))
+        {
+            let details = match &item.address {
+                Some(address) if !item.display.contains(address.as_str()) => {
+                    format!("address: {}  {}", address, resource_details(resource))
+                }
+                _ => resource_details(resource),
+            };
+            let footer = truncate(&details, term_width as usize);
+            execute!(
+                stdout,
+                style::PrintStyledContent(footer.dark_grey()),
+                cursor::MoveToNextLine(1)
+            )?;
+        }
+
         stdout.flush()?;
         Ok(())
     }
 
-    pub fn run(&mut self) -> Result<Option<String>> {
+    /// Returns the key help for the status line
+    fn help_line(&self) -> String {
+        if self.facets_open {
+            return "[↑/k]Up [↓/j]Down [Enter]Filter by type [Esc/Ctrl+F]Close".to_string();
+        }
+        let mut help = String::from("[↑/k]Up [↓/j]Down ");
+        if self.multi_select {
+            help.push_str("[Tab]Mark [Ctrl+D]Unmark shown ");
+        }
+        if self.reload.is_some() {
+            help.push_str("[Ctrl+R]Reload ");
+        }
+        if self.allow_save {
+            help.push_str("[Ctrl+S]Save set ");
+        }
+        if cfg!(feature = "clipboard") {
+            help.push_str("[Ctrl+Y]Copy ");
+        }
+        if !self.facets().is_empty() {
+            help.push_str("[Ctrl+F]Types ");
+        }
+        help.push_str("[Enter]Select ");
+        help.push_str(if self.allow_back {
+            "[Esc]Back [Ctrl+C]Cancel"
+        } else {
+            "[Esc/Ctrl+C]Cancel"
+        });
+        help
+    }
+
+    /// Draws the resource type panel beside the item list, keeping the cursor in place
+    fn render_facets(&self, facets: &[(String, usize)], column: u16, width: usize) -> Result<()> {
+        let mut stdout = stdout();
+        let start = if facets.len() > self.window_size {
+            self.facet_selected
+                .saturating_sub(self.window_size / 2)
+                .min(facets.len() - self.window_size)
+        } else {
+            0
+        };
+        execute!(stdout, cursor::SavePosition)?;
+        for (row, i) in (start..facets.len().min(start + self.window_size)).enumerate() {
+            let (resource_type, count) = &facets[i];
+            let count = count.to_string();
+            let name_width = width.saturating_sub(count.len() + 3);
+            let name = truncate(resource_type, name_width);
+            let (arrow, color) = if i == self.facet_selected {
+                ("▶", self.theme.highlight)
+            } else {
+                (" ", None)
+            };
+            let line = format!(
+                "│{}{}{} {}",
+                arrow,
+                name,
+                " ".repeat(name_width.saturating_sub(name.width())),
+                count
+            );
+            execute!(
+                stdout,
+                cursor::MoveTo(column, row as u16 + 2),
+                style::PrintStyledContent(paint(line, color))
+            )?;
+        }
+        execute!(stdout, cursor::RestorePosition)?;
+        Ok(())
+    }
+
+    pub fn run(&mut self) -> Result<Selection> {
         terminal::enable_raw_mode()?;
         execute!(stdout(), terminal::EnterAlternateScreen, cursor::Hide)?;
 
@@ -151,7 +519,7 @@ impl Selector {
         result
     }
 
-    fn run_loop(&mut self) -> Result<Option<String>> {
+    fn run_loop(&mut self) -> Result<Selection> {
         loop {
             self.render_screen()?;
 
@@ -159,31 +527,58 @@ impl Selector {
                 if key.kind != KeyEventKind::Press {
                     continue;
                 }
+                self.notice = None;
+                if self.facets_open && self.handle_facet_key(key.code, key.modifiers) {
+                    continue;
+                }
 
                 match (key.code, key.modifiers) {
                     (KeyCode::Enter, _) => {
-                        if let Some(&idx) = self.filtered_items.get(self.selected) {
-                            return Ok(Some(self.items[idx].data.clone()));
+                        if let Some(selection) = self.confirm() {
+                            return Ok(selection);
+                        }
+                    }
+                    (KeyCode::Tab, _) if self.multi_select => {
+                        self.toggle_mark();
+                        self.selected =
+                            (self.selected + 1).min(self.filtered_items.len().saturating_sub(1));
+                    }
+                    (KeyCode::Char('d'), KeyModifiers::CONTROL) if self.multi_select => {
+                        self.unmark_filtered();
+                    }
+                    (KeyCode::Char('s'), KeyModifiers::CONTROL) if self.allow_save => {
+                        if let Some(selection) = self.confirm() {
+                            self.save_requested = true;
+                            return Ok(selection);
                         }
                     }
+                    #[cfg(feature = "clipboard")]
+                    (KeyCode::Char('y'), KeyModifiers::CONTROL) => {
+                        self.copy_highlighted();
+                    }
+                    (KeyCode::Char('f'), KeyModifiers::CONTROL) if !self.facets().is_empty() => {
+                        self.facets_open = true;
+                        self.facet_selected = 0;
+                    }
+                    (KeyCode::Char('r'), KeyModifiers::CONTROL) => {
+                        self.reload_items();
+                    }
+                    (KeyCode::Esc, _) if self.allow_back => {
+                        return Ok(Selection::Back);
+                    }
                     (KeyCode::Esc, _) | (KeyCode::Char('c'), KeyModifiers::CONTROL) => {
-                        return Ok(None);
+                        return Ok(Selection::Cancelled);
                     }
                     (KeyCode::Up, _) | (KeyCode::Char('k'), _) => {
-                        if self.selected > 0 {
-                            self.selected -= 1;
-                        }
+                        self.selected = self.selected.saturating_sub(1);
                     }
                     (KeyCode::Down, _) | (KeyCode::Char('j'), _) => {
-                        if self.selected + 1 < self.filtered_items.len() {
-                            self.selected += 1;
-                        }
+                        self.selected =
+                            (self.selected + 1).min(self.filtered_items.len().saturating_sub(1));
                     }
-                    (KeyCode::Backspace, _) => {
-                        if !self.query.is_empty() {
-                            self.query.pop();
-                            self.filter_items();
-                        }
+                    (KeyCode::Backspace, _) if !self.query.is_empty() => {
+                        self.query.pop();
+                        self.filter_items();
                     }
                     (KeyCode::Char(c), m)
                         if m == KeyModifiers::NONE || m == KeyModifiers::SHIFT =>
@@ -197,3 +592,332 @@ impl Selector {
         }
     }
 }
+
+/// Styles text with an optional theme color
+fn paint(text: String, color: Option<colored::Color>) -> StyledContent<String> {
+    match color {
+        Some(color) => text.with(theme::to_crossterm(color)),
+        None => style::style(text),
+    }
+}
+
+/// Splits `text` into runs of characters that are or are not at `indices`
+fn highlight_runs(text: &str, indices: &[usize]) -> Vec<(String, bool)> {
+    let mut runs: Vec<(String, bool)> = Vec::new();
+    for (i, c) in text.chars().enumerate() {
+        let matched = indices.contains(&i);
+        match runs.last_mut() {
+            Some((run, run_matched)) if *run_matched == matched => run.push(c),
+            _ => runs.push((c.to_string(), matched)),
+        }
+    }
+    runs
+}
+
+/// Describes a resource for the detail footer
+fn resource_details(resource: &Resource) -> String {
+    let flag = |set: bool| if set { "yes" } else { "no" };
+    let mut details = format!(
+        "type: {}  file: {}  count: {}  for_each: {}  index: {}",
+        if resource.is_module {
+            "module"
+        } else {
+            &resource.resource_type
+        },
+        resource.location(),
+        flag(resource.has_count),
+        flag(resource.has_for_each),
+        resource.index.as_deref().unwrap_or("-")
+    );
+    if !resource.providers.is_empty() {
+        let providers: Vec<String> = resource
+            .providers
+            .iter()
+            .map(|(name, passed)| format!("{}={}", name, passed))
+            .collect();
+        details.push_str(&format!("  providers: {}", providers.join(", ")));
+    }
+    details
+}
+
+/// Truncates `text` to `width` terminal columns, marking the cut with an ellipsis
+///
+/// Wide characters such as CJK and most emoji take two columns.
+/// Joins the status and the right-aligned key help into one line of `width` columns
+///
+/// The status comes first; the help is cut to the room left after it.
+fn status_line(status: &str, help: &str, width: usize) -> String {
+    let status = truncate(status, width);
+    // At least two columns between the status and the help
+    let room = width.saturating_sub(status.width() + 2);
+    let help = truncate(help, room);
+    if help.is_empty() {
+        return status;
+    }
+    let padding = width - status.width() - help.width();
+    format!("{}{}{}", status, " ".repeat(padding), help)
+}
+
+fn truncate(text: &str, width: usize) -> String {
+    if text.width() <= width {
+        return text.to_string();
+    }
+    if width == 0 {
+        return String::new();
+    }
+    let mut truncated = String::new();
+    let mut used = 0;
+    for c in text.chars() {
+        let char_width = c.width().unwrap_or(0);
+        if used + char_width > width - 1 {
+            break;
+        }
+        truncated.push(c);
+        used += char_width;
+    }
+    truncated.push('…');
+    truncated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn items(names: &[&str]) -> Vec<SelectItem> {
+        names
+            .iter()
+            .map(|name| SelectItem {
+                display: name.to_string(),
+                search_text: name.to_string(),
+                data: name.to_string(),
+                resource: None,
+                address: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_marks_preserve_selection_order() {
+        let mut selector = Selector::new(items(&["a", "b", "c"])).with_multi_select();
+
+        selector.selected = 2;
+        selector.toggle_mark();
+        selector.selected = 0;
+        selector.toggle_mark();
+
+        assert_eq!(
+            selector.confirm(),
+            Some(Selection::SelectedMany(vec![
+                "c".to_string(),
+                "a".to_string()
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_replace_items_keeps_query() {
+        let mut selector = Selector::new(items(&["aws_vpc", "aws_subnet"])).with_multi_select();
+        selector.query = "subnet".to_string();
+        selector.filter_items();
+        selector.toggle_mark();
+
+        selector.replace_items(items(&["aws_vpc", "aws_subnet", "aws_subnet_b"]));
+
+        assert_eq!(selector.query, "subnet");
+        assert_eq!(selector.filtered_items.len(), 2);
+        assert!(
+            selector.marked.is_empty(),
+            "Marks should be cleared on reload"
+        );
+    }
+
+    #[test]
+    fn test_status_line_fits_80_columns() {
+        let selector = Selector::new(items(&["a", "b"]))
+            .with_multi_select()
+            .with_reload(|| Ok(Vec::new()))
+            .with_save()
+            .with_back();
+        let help = selector.help_line();
+        assert!(help.width() > 80);
+
+        let status = "2/2 items (1 marked)  Copied aws_instance.web";
+        let line = status_line(status, &help, 80);
+        assert_eq!(line.width(), 80);
+        assert!(line.starts_with(&format!("{}  [↑/k]Up", status)));
+        assert!(line.ends_with('…'));
+
+        assert_eq!(
+            status_line("2/2 items", "[Enter]Select", 30),
+            "2/2 items        [Enter]Select"
+        );
+        assert_eq!(
+            status_line(&"x".repeat(90), &help, 80),
+            format!("{}…", "x".repeat(79))
+        );
+    }
+
+    #[test]
+    fn test_failed_reload_keeps_items() {
+        use crate::error::TfocusError;
+
+        let mut selector = Selector::new(items(&["aws_vpc", "aws_subnet"])).with_reload(|| {
+            Err(TfocusError::ParseError(
+                "main.tf: unexpected end".to_string(),
+            ))
+        });
+        selector.reload_items();
+
+        assert_eq!(selector.items.len(), 2);
+        assert_eq!(
+            selector.notice.as_deref(),
+            Some("Reload failed: Failed to parse terraform file: main.tf: unexpected end")
+        );
+    }
+
+    #[test]
+    fn test_alias_expands_query() {
+        let mut selector =
+            Selector::new(items(&["aws_instance.web", "aws_s3_bucket.logs"])).with_aliases(
+                HashMap::from([("ec2".to_string(), "aws_instance".to_string())]),
+            );
+        selector.query = "ec2".to_string();
+        selector.filter_items();
+
+        assert_eq!(selector.filtered_items, vec![0]);
+    }
+
+    #[test]
+    fn test_resource_details_and_truncate() {
+        let resource = Resource {
+            has_count: true,
+            index: Some("0".to_string()),
+            line: 4,
+            ..Resource::for_test("aws_instance", "web")
+        };
+
+        let details = resource_details(&resource);
+        assert_eq!(
+            details,
+            "type: aws_instance  file: main.tf:4  count: yes  for_each: no  index: 0"
+        );
+        assert_eq!(truncate(&details, 20), "type: aws_instance …");
+        assert_eq!(truncate(&details, 20).chars().count(), 20);
+        assert_eq!(truncate("short", 20), "short");
+
+        // Japanese characters and emoji take two columns each
+        let wide = r#"aws_instance.web["東京リージョン🚀"]"#;
+        assert_eq!(truncate(wide, 24), r#"aws_instance.web["東京…"#);
+        assert_eq!(truncate(wide, 24).width(), 23);
+        assert_eq!(truncate(wide, 50), wide);
+
+        let module = Resource {
+            resource_type: String::new(),
+            is_module: true,
+            has_count: false,
+            index: None,
+            provider: None,
+            providers: vec![("aws".to_string(), "aws.useast1".to_string())],
+            ..resource
+        };
+        assert_eq!(
+            resource_details(&module),
+            "type: module  file: main.tf:4  count: no  for_each: no  index: -  providers: aws=aws.useast1"
+        );
+    }
+
+    #[test]
+    fn test_highlight_runs_follow_query_matches() {
+        let mut selector = Selector::new(items(&["aws_instance.web"]));
+        selector.query = "web".to_string();
+        let indices = selector.match_indices("aws_instance.web");
+        assert_eq!(
+            highlight_runs("aws_instance.web", &indices),
+            vec![
+                ("aws_instance.".to_string(), false),
+                ("web".to_string(), true)
+            ]
+        );
+
+        selector.query.clear();
+        assert!(selector.match_indices("aws_instance.web").is_empty());
+    }
+
+    #[test]
+    fn test_unmark_filtered_keeps_hidden_marks() {
+        let mut selector =
+            Selector::new(items(&["aws_vpc", "aws_subnet", "aws_subnet_b"])).with_multi_select();
+        selector.marked = vec![2, 0, 1];
+        selector.query = "subnet".to_string();
+        selector.filter_items();
+
+        selector.unmark_filtered();
+
+        assert_eq!(selector.marked, vec![0]);
+    }
+
+    #[test]
+    fn test_facets_count_types_and_filter() {
+        let resource = |resource_type: &str, name: &str, is_module: bool| SelectItem {
+            display: format!("{}.{}", resource_type, name),
+            search_text: format!("{}.{}", resource_type, name),
+            data: name.to_string(),
+            resource: Some(Resource {
+                is_module,
+                ..Resource::for_test(resource_type, name)
+            }),
+            address: None,
+        };
+        let mut list = vec![
+            resource("aws_s3_bucket", "logs", false),
+            resource("aws_instance", "web", false),
+            resource("aws_instance", "api", false),
+            resource("", "vpc", true),
+        ];
+        list.extend(items(&["main.tf"]));
+        let mut selector = Selector::new(list);
+
+        assert_eq!(
+            selector.facets(),
+            vec![
+                ("aws_instance".to_string(), 2),
+                ("aws_s3_bucket".to_string(), 1)
+            ]
+        );
+
+        selector.facets_open = true;
+        assert!(selector.handle_facet_key(KeyCode::Down, KeyModifiers::NONE));
+        assert!(selector.handle_facet_key(KeyCode::Down, KeyModifiers::NONE));
+        assert_eq!(selector.facet_selected, 1);
+        assert!(!selector.handle_facet_key(KeyCode::Char('x'), KeyModifiers::CONTROL));
+        assert!(selector.handle_facet_key(KeyCode::Enter, KeyModifiers::NONE));
+        assert!(!selector.facets_open);
+        assert_eq!(selector.query, "aws_s3_bucket");
+        assert_eq!(selector.filtered_items, vec![0]);
+    }
+
+    #[test]
+    fn test_highlighted_address() {
+        let mut list = items(&["aws_vpc.main", "main.tf"]);
+        list[0].address = Some("aws_vpc.main".to_string());
+        let mut selector = Selector::new(list);
+
+        assert_eq!(selector.highlighted_address(), Some("aws_vpc.main"));
+        selector.selected = 1;
+        assert_eq!(selector.highlighted_address(), None);
+    }
+
+    #[test]
+    fn test_unmarking_removes_from_order() {
+        let mut selector = Selector::new(items(&["a", "b", "c"])).with_multi_select();
+
+        selector.selected = 1;
+        selector.toggle_mark();
+        selector.toggle_mark();
+
+        assert_eq!(
+            selector.confirm(),
+            Some(Selection::Selected("b".to_string()))
+        );
+    }
+}
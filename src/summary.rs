@@ -0,0 +1,129 @@
+use regex::Regex;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+
+use crate::error::{Result, TfocusError};
+
+/// Environment variable GitHub Actions sets to the step summary file
+pub const STEP_SUMMARY_ENV: &str = "GITHUB_STEP_SUMMARY";
+
+/// Resource counts from the `Plan:` line of terraform's plan output
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PlanCounts {
+    pub add: usize,
+    pub change: usize,
+    pub destroy: usize,
+}
+
+impl PlanCounts {
+    /// Adds the counts of another plan run
+    pub fn accumulate(&mut self, other: PlanCounts) {
+        self.add += other.add;
+        self.change += other.change;
+        self.destroy += other.destroy;
+    }
+}
+
+/// Parses the `Plan:` lines of terraform's plan output
+pub struct PlanLineParser {
+    count: Regex,
+}
+
+impl PlanLineParser {
+    pub fn new() -> Result<Self> {
+        Ok(PlanLineParser {
+            count: Regex::new(r"(\d+) to (add|change|destroy)\b")
+                .map_err(TfocusError::RegexError)?,
+        })
+    }
+
+    /// Parses a `Plan: 1 to add, 0 to change, 0 to destroy.` line
+    ///
+    /// Color codes and extra counts (e.g. `to import`) are ignored.
+    pub fn parse(&self, line: &str) -> Option<PlanCounts> {
+        if !line.contains("Plan:") {
+            return None;
+        }
+        let mut counts = PlanCounts::default();
+        for cap in self.count.captures_iter(line) {
+            let count = cap[1].parse().unwrap_or(0);
+            match &cap[2] {
+                "add" => counts.add = count,
+                "change" => counts.change = count,
+                _ => counts.destroy = count,
+            }
+        }
+        Some(counts)
+    }
+}
+
+/// Renders the markdown summary of a plan run
+pub fn render_plan_summary(targets: &[String], counts: PlanCounts) -> String {
+    let mut markdown = String::from("### tfocus plan\n\n**Targets**\n\n");
+    for target in targets {
+        markdown.push_str(&format!("- `{}`\n", target));
+    }
+    markdown.push_str("\n| Add | Change | Destroy |\n|----:|-------:|--------:|\n");
+    markdown.push_str(&format!(
+        "| {} | {} | {} |\n\n",
+        counts.add, counts.change, counts.destroy
+    ));
+    markdown
+}
+
+/// Appends the plan summary to the GitHub Actions step summary file
+pub fn append_step_summary(path: &Path, targets: &[String], counts: PlanCounts) -> Result<()> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(TfocusError::Io)?;
+    file.write_all(render_plan_summary(targets, counts).as_bytes())
+        .map_err(TfocusError::Io)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_plan_line() {
+        let parser = PlanLineParser::new().unwrap();
+        assert_eq!(
+            parser.parse("Plan: 2 to add, 1 to change, 0 to destroy."),
+            Some(PlanCounts {
+                add: 2,
+                change: 1,
+                destroy: 0
+            })
+        );
+        assert_eq!(
+            parser.parse("\x1b[1mPlan:\x1b[0m 1 to import, 0 to add, 3 to change, 1 to destroy."),
+            Some(PlanCounts {
+                add: 0,
+                change: 3,
+                destroy: 1
+            })
+        );
+        assert_eq!(parser.parse("No changes."), None);
+    }
+
+    #[test]
+    fn test_append_step_summary() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let targets = vec!["aws_instance.web".to_string()];
+        let counts = PlanCounts {
+            add: 1,
+            change: 0,
+            destroy: 0,
+        };
+        append_step_summary(file.path(), &targets, counts).unwrap();
+        append_step_summary(file.path(), &targets, counts).unwrap();
+
+        let content = std::fs::read_to_string(file.path()).unwrap();
+        assert_eq!(content.matches("### tfocus plan").count(), 2);
+        assert!(content.contains("- `aws_instance.web`"));
+        assert!(content.contains("| 1 | 0 | 0 |"));
+    }
+}
@@ -0,0 +1,130 @@
+use std::fs;
+use std::path::Path;
+
+use crate::error::{Result, TfocusError};
+use crate::types::Address;
+
+/// Loads resource addresses from an `--also-target-file` file
+pub fn load(path: &Path) -> Result<Vec<String>> {
+    let content = fs::read_to_string(path)?;
+    parse(&content).map_err(|e| TfocusError::ConfigError(format!("{}: {}", path.display(), e)))
+}
+
+/// Parses one address per line; blank lines and `#` comments are skipped and duplicates dropped
+///
+/// Lines must be addresses as terraform prints them, e.g. `aws_instance.web[0]`
+/// or `module.vpc.aws_subnet.a`.
+fn parse(content: &str) -> std::result::Result<Vec<String>, String> {
+    let mut addresses: Vec<String> = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if Address::parse(line).is_none() {
+            return Err(format!(
+                "line {}: '{}' is not a resource address",
+                i + 1,
+                line
+            ));
+        }
+        if !addresses.iter().any(|a| a == line) {
+            addresses.push(line.to_string());
+        }
+    }
+    Ok(addresses)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::{ParseOptions, TerraformProject};
+    use crate::types::{Resource, Target};
+
+    #[test]
+    fn test_parse_addresses() {
+        assert_eq!(
+            parse("# base set\naws_vpc.main\n\n  module.network  \naws_vpc.main\n").unwrap(),
+            vec!["aws_vpc.main", "module.network"]
+        );
+        assert_eq!(
+            parse("aws_instance.web[0]\nmodule.vpc.aws_subnet.a\naws_instance.web[\"blue\"]\n")
+                .unwrap(),
+            vec![
+                "aws_instance.web[0]",
+                "module.vpc.aws_subnet.a",
+                r#"aws_instance.web["blue"]"#
+            ]
+        );
+        assert!(parse("aws_vpc.main\nnot an address\n").is_err());
+        assert!(parse("aws_vpc\n").is_err());
+        assert!(parse("aws_vpc.main.extra\n").is_err());
+        assert!(parse("aws_instance.web[blue]\n").is_err());
+        assert!(parse("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_indexed_and_module_lines_resolve() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("main.tf"),
+            r#"
+resource "aws_instance" "web" {
+  count = 2
+}
+
+module "vpc" {
+  source = "./vpc"
+}
+"#,
+        )
+        .unwrap();
+        let options = ParseOptions {
+            quiet: true,
+            ..ParseOptions::default()
+        };
+        let project = TerraformProject::parse_path(dir.path(), &options).unwrap();
+
+        let lines =
+            "aws_instance.web[1]\nmodule.vpc.aws_subnet.a\nmodule.vpc.data.aws_ami.x[\"eu\"]\n";
+        let resolved: Vec<Resource> = parse(lines)
+            .unwrap()
+            .into_iter()
+            .flat_map(|a| {
+                project
+                    .get_resources_by_target(&Target::Address(a))
+                    .unwrap()
+            })
+            .collect();
+        let targets: Vec<String> = resolved.iter().map(|r| r.target_string()).collect();
+        assert_eq!(
+            targets,
+            vec![
+                "aws_instance.web[1]",
+                "module.vpc.aws_subnet.a",
+                r#"module.vpc.data.aws_ami.x["eu"]"#
+            ]
+        );
+        assert_eq!(resolved[1].file_path, dir.path().join("main.tf"));
+        assert_eq!(resolved[1].line, 6);
+
+        // An index range expands into one target per instance
+        let range = parse("aws_instance.web[0-1]\n").unwrap().remove(0);
+        let resolved = project
+            .get_resources_by_target(&Target::Address(range))
+            .unwrap();
+        assert_eq!(
+            resolved[0].target_strings().unwrap(),
+            vec!["aws_instance.web[0]", "aws_instance.web[1]"]
+        );
+
+        for unknown in [
+            "aws_instance.web[\"blue\"]",
+            "module.dns.aws_route53_record.a",
+        ] {
+            assert!(project
+                .get_resources_by_target(&Target::Address(unknown.to_string()))
+                .is_err());
+        }
+    }
+}
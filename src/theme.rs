@@ -0,0 +1,146 @@
+use colored::Color;
+use crossterm::style;
+use std::sync::OnceLock;
+
+use crate::config::Config;
+use crate::error::{Result, TfocusError};
+
+/// The theme used for the rest of the run, set once by [`Theme::install`]
+static THEME: OnceLock<Theme> = OnceLock::new();
+
+/// Colors for the selector and printed headers, from the `[theme]` config section
+///
+/// `None` leaves the text in the terminal's default color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    /// The focused line in the selector (`theme.highlight`)
+    pub highlight: Option<Color>,
+    /// Section headers such as "Selected resources:" (`theme.header`)
+    pub header: Option<Color>,
+    /// Characters matching the query in the selector (`theme.match_highlight`)
+    pub match_highlight: Option<Color>,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            highlight: Some(Color::Green),
+            header: Some(Color::BrightBlue),
+            match_highlight: Some(Color::Yellow),
+        }
+    }
+}
+
+impl Theme {
+    /// A theme without any colors, for `--no-color` and `NO_COLOR`
+    pub fn plain() -> Self {
+        Self {
+            highlight: None,
+            header: None,
+            match_highlight: None,
+        }
+    }
+
+    /// Reads `[theme]` from the config; unset fields keep their defaults
+    ///
+    /// Colors are names (`cyan`, `bright_blue`) or hex values (`#005f87`).
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let color = |key: &str, default: Option<Color>| match config.get_string(key) {
+            None => Ok(default),
+            Some(value) => parse_color(&value).map(Some).ok_or_else(|| {
+                TfocusError::ConfigError(format!("{}: unknown color '{}'", key, value))
+            }),
+        };
+
+        let defaults = Self::default();
+        Ok(Self {
+            highlight: color("theme.highlight", defaults.highlight)?,
+            header: color("theme.header", defaults.header)?,
+            match_highlight: color("theme.match_highlight", defaults.match_highlight)?,
+        })
+    }
+
+    /// Makes this the theme for the rest of the run; the first installed theme wins
+    pub fn install(self) {
+        let _ = THEME.set(self);
+    }
+
+    /// Returns the installed theme, or the default one
+    pub fn current() -> Self {
+        THEME.get().copied().unwrap_or_default()
+    }
+}
+
+/// Parses a color name or a `#rrggbb` hex value
+fn parse_color(value: &str) -> Option<Color> {
+    if let Some(hex) = value.strip_prefix('#') {
+        if hex.len() != 6 || !hex.is_ascii() {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        return Some(Color::TrueColor {
+            r: channel(0)?,
+            g: channel(2)?,
+            b: channel(4)?,
+        });
+    }
+    value.replace(['_', '-'], " ").parse().ok()
+}
+
+/// Converts a theme color for crossterm, which draws the selector
+pub fn to_crossterm(color: Color) -> style::Color {
+    match color {
+        Color::Black => style::Color::Black,
+        Color::Red => style::Color::DarkRed,
+        Color::Green => style::Color::DarkGreen,
+        Color::Yellow => style::Color::DarkYellow,
+        Color::Blue => style::Color::DarkBlue,
+        Color::Magenta => style::Color::DarkMagenta,
+        Color::Cyan => style::Color::DarkCyan,
+        Color::White => style::Color::Grey,
+        Color::BrightBlack => style::Color::DarkGrey,
+        Color::BrightRed => style::Color::Red,
+        Color::BrightGreen => style::Color::Green,
+        Color::BrightYellow => style::Color::Yellow,
+        Color::BrightBlue => style::Color::Blue,
+        Color::BrightMagenta => style::Color::Magenta,
+        Color::BrightCyan => style::Color::Cyan,
+        Color::BrightWhite => style::Color::White,
+        Color::TrueColor { r, g, b } => style::Color::Rgb { r, g, b },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(parse_color("cyan"), Some(Color::Cyan));
+        assert_eq!(parse_color("bright_blue"), Some(Color::BrightBlue));
+        assert_eq!(parse_color("Bright Black"), Some(Color::BrightBlack));
+        assert_eq!(
+            parse_color("#005f87"),
+            Some(Color::TrueColor {
+                r: 0x00,
+                g: 0x5f,
+                b: 0x87
+            })
+        );
+        assert_eq!(parse_color("#05f"), None);
+        assert_eq!(parse_color("#zz0000"), None);
+        assert_eq!(parse_color("teal"), None);
+    }
+
+    #[test]
+    fn test_theme_from_config() {
+        let config = Config::parse("[theme]\nhighlight = \"blue\"\nheader = \"#000000\"").unwrap();
+        let theme = Theme::from_config(&config).unwrap();
+        assert_eq!(theme.highlight, Some(Color::Blue));
+        assert_eq!(theme.header, Some(Color::TrueColor { r: 0, g: 0, b: 0 }));
+        assert_eq!(theme.match_highlight, Theme::default().match_highlight);
+
+        let config = Config::parse("[theme]\nheader = \"teal\"").unwrap();
+        assert!(Theme::from_config(&config).is_err());
+    }
+}
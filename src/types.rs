@@ -1,5 +1,7 @@
 use std::path::PathBuf;
 
+use crate::error::{Result, TfocusError};
+
 /// Represents a Terraform resource with extended metadata
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Resource {
@@ -9,6 +11,8 @@ pub struct Resource {
     pub name: String,
     /// Whether this is a module
     pub is_module: bool,
+    /// Whether this is a data source
+    pub is_data: bool,
     /// Path to the file containing this resource
     pub file_path: PathBuf,
     /// Whether the resource uses count
@@ -17,32 +21,764 @@ pub struct Resource {
     pub has_for_each: bool,
     /// The specific index for count/for_each resources
     pub index: Option<String>,
+    /// The 1-based line number where the block starts
+    pub line: usize,
+    /// Whether the state has a tainted instance of this block (see `--check-tainted`)
+    pub tainted: bool,
+    /// Addresses listed in the block's `depends_on` argument
+    pub depends_on: Vec<String>,
+    /// The `source` argument of a module block
+    pub source: Option<String>,
+    /// The explicit `provider` argument of a resource or data block (e.g. `aws.east`)
+    pub provider: Option<String>,
+    /// The `providers` map of a module block, as `(module provider, passed provider)` pairs
+    pub providers: Vec<(String, String)>,
+    /// The enclosing module instance for resources read from a plan (e.g. `module.vpc`)
+    pub module_address: Option<String>,
+    /// The change a plan makes to this resource (see `--from-plan`)
+    pub planned_action: Option<PlanAction>,
 }
 
 impl Resource {
+    /// Returns whether the type and name are valid Terraform identifiers
+    ///
+    /// Anything else, e.g. a label a regex picked up from a comment or string,
+    /// would make a malformed `-target`. Modules only have a name to check.
+    pub fn is_valid_address(&self) -> bool {
+        (self.is_module || is_valid_identifier(&self.resource_type))
+            && is_valid_identifier(&self.name)
+    }
+
     /// Returns the full name of the resource in Terraform format
     pub fn full_name(&self) -> String {
-        if self.is_module {
+        if let Some(module_address) = &self.module_address {
+            format!("{}.{}", module_address, self.short_name())
+        } else if self.is_module {
             format!("module.{}", self.name)
+        } else if self.is_data {
+            format!("data.{}.{}", self.resource_type, self.name)
         } else {
             format!("{}.{}", self.resource_type, self.name)
         }
     }
 
+    /// Returns the address without the enclosing module, e.g. `aws_subnet.a`
+    /// for `module.vpc.aws_subnet.a`
+    pub fn short_name(&self) -> String {
+        Resource {
+            module_address: None,
+            ..self.clone()
+        }
+        .full_name()
+    }
+
+    /// Returns whether the block may expand into several instances (count or for_each)
+    pub fn is_dynamic(&self) -> bool {
+        self.has_count || self.has_for_each
+    }
+
+    /// Returns the local name of the provider managing this resource, e.g. `aws`
+    ///
+    /// An explicit `provider` argument wins over the type prefix (`aws_instance`
+    /// is managed by `aws`). Module calls have no provider of their own.
+    pub fn provider_name(&self) -> Option<&str> {
+        if self.is_module {
+            return None;
+        }
+        let name = match &self.provider {
+            Some(provider) => provider.split('.').next().unwrap_or(provider),
+            None => self
+                .resource_type
+                .split('_')
+                .next()
+                .unwrap_or(&self.resource_type),
+        };
+        Some(name)
+    }
+
+    /// Returns whether the resource's address matches one of the `--exclude-target` globs
+    ///
+    /// Both the instance address and the block address are tried, so
+    /// `aws_instance.web` also excludes `aws_instance.web[0]`.
+    pub fn is_excluded(&self, exclude: &globset::GlobSet) -> bool {
+        exclude.is_match(self.target_string()) || exclude.is_match(self.full_name())
+    }
+
+    /// Returns which kind of block this is
+    pub fn kind(&self) -> ResourceKind {
+        if self.is_module {
+            ResourceKind::Module
+        } else if self.is_data {
+            ResourceKind::Data
+        } else {
+            ResourceKind::Resource
+        }
+    }
+
+    /// Returns the block location as `file:line`, or just the file for plan resources
+    pub fn location(&self) -> String {
+        if self.line == 0 {
+            return self.file_path.display().to_string();
+        }
+        format!("{}:{}", self.file_path.display(), self.line)
+    }
+
     /// Returns the target string for Terraform commands
+    ///
+    /// for_each keys are quoted the same way for modules and resources, e.g.
+    /// `module.app["blue"]` and `aws_instance.web["a"]`.
     pub fn target_string(&self) -> String {
         let base = self.full_name();
         match (&self.has_count, &self.has_for_each, &self.index) {
-            (true, _, Some(idx)) | (_, true, Some(idx)) => format!("{}[{}]", base, idx),
+            (_, true, Some(key)) => format!("{}[{}]", base, quote_for_each_key(key)),
+            (true, _, Some(idx)) => format!("{}[{}]", base, idx),
             _ => base,
         }
     }
+
+    /// Returns every target string for Terraform commands.
+    ///
+    /// A count index written as a range (e.g. `aws_instance.web[0-2]`) is
+    /// expanded into one target per instance. Ranges are a tfocus-side
+    /// expansion only; Terraform itself has no range syntax for `-target`.
+    pub fn target_strings(&self) -> Result<Vec<String>> {
+        let base = self.full_name();
+        match (&self.has_count, &self.index) {
+            (true, Some(idx)) => match parse_index_range(idx)? {
+                Some((start, end)) => {
+                    Ok((start..=end).map(|i| format!("{}[{}]", base, i)).collect())
+                }
+                None => Ok(vec![self.target_string()]),
+            },
+            _ => Ok(vec![self.target_string()]),
+        }
+    }
+
+    /// Returns the instance of this block at `key`, written as in an address (`0` or `"blue"`)
+    ///
+    /// Returns `None` when the key does not fit the block: a number or a range
+    /// such as `0-2` needs `count`, and a quoted string needs `for_each`.
+    pub fn instance(&self, key: &str) -> Option<Resource> {
+        let index = if self.has_count && is_count_key(key) {
+            key.to_string()
+        } else if self.has_for_each {
+            unquote_for_each_key(key)?
+        } else {
+            return None;
+        };
+        Some(Resource {
+            index: Some(index),
+            ..self.clone()
+        })
+    }
+}
+
+#[cfg(test)]
+impl Resource {
+    /// Returns a block declared at `main.tf:1` for tests; an empty type makes a module call
+    pub fn for_test(resource_type: &str, name: &str) -> Resource {
+        Resource {
+            resource_type: resource_type.to_string(),
+            name: name.to_string(),
+            is_module: resource_type.is_empty(),
+            is_data: false,
+            file_path: PathBuf::from("main.tf"),
+            has_count: false,
+            has_for_each: false,
+            index: None,
+            line: 1,
+            tainted: false,
+            depends_on: Vec::new(),
+            source: None,
+            provider: None,
+            providers: Vec::new(),
+            module_address: None,
+            planned_action: None,
+        }
+    }
+}
+
+/// Quotes a for_each key for use in an instance address
+///
+/// Keys that are already quoted are left as they are.
+fn quote_for_each_key(key: &str) -> String {
+    if key.len() >= 2 && key.starts_with('"') && key.ends_with('"') {
+        return key.to_string();
+    }
+    format!("\"{}\"", key.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Reverses [`quote_for_each_key`], or returns `None` if `key` is not a quoted string
+fn unquote_for_each_key(key: &str) -> Option<String> {
+    let inner = key.strip_prefix('"')?.strip_suffix('"')?;
+    let mut unquoted = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => unquoted.push(chars.next()?),
+            '"' => return None,
+            c => unquoted.push(c),
+        }
+    }
+    Some(unquoted)
+}
+
+/// Splits the instance key off an address, e.g. `aws_instance.web["blue"]` into
+/// `aws_instance.web` and `"blue"`
+///
+/// Returns `None` when the address does not end with a count index or a quoted key.
+pub fn split_instance_key(address: &str) -> Option<(&str, &str)> {
+    let inner = address.strip_suffix(']')?;
+    // A quoted key may itself contain '[', so try each bracket from the right
+    inner.match_indices('[').rev().find_map(|(open, _)| {
+        let key = &inner[open + 1..];
+        (is_count_key(key) || unquote_for_each_key(key).is_some()).then(|| (&inner[..open], key))
+    })
+}
+
+/// Returns whether `key` is a count index (`0`) or an index range (`0-2`)
+fn is_count_key(key: &str) -> bool {
+    let is_number = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    match key.split_once('-') {
+        Some((start, end)) => is_number(start) && is_number(end),
+        None => is_number(key),
+    }
+}
+
+/// Returns whether `name` is a valid Terraform identifier
+///
+/// Identifiers start with a letter or underscore and may contain letters,
+/// digits, underscores and hyphens.
+pub fn is_valid_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) if c.is_alphabetic() || c == '_' => {}
+        _ => return false,
+    }
+    chars.all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Parses a count index range such as `0-2` into its inclusive bounds.
+///
+/// Returns `Ok(None)` when the index is not a range.
+pub fn parse_index_range(index: &str) -> Result<Option<(usize, usize)>> {
+    let Some((start, end)) = index.trim().split_once('-') else {
+        return Ok(None);
+    };
+    let (Ok(start), Ok(end)) = (start.trim().parse::<usize>(), end.trim().parse::<usize>()) else {
+        return Ok(None);
+    };
+    if start > end {
+        return Err(TfocusError::ParseError(format!(
+            "Invalid index range [{}]: start is greater than end",
+            index
+        )));
+    }
+    Ok(Some((start, end)))
+}
+
+/// The kinds of targetable blocks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResourceKind {
+    Module,
+    Resource,
+    Data,
+}
+
+/// The change a plan makes to a resource, from its `resource_changes` actions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlanAction {
+    Create,
+    Update,
+    Delete,
+    Replace,
+    Read,
+}
+
+impl PlanAction {
+    /// Returns the symbol Terraform uses for this action in plan output
+    pub fn marker(&self) -> &'static str {
+        match self {
+            PlanAction::Create => "+",
+            PlanAction::Update => "~",
+            PlanAction::Delete => "-",
+            PlanAction::Replace => "-/+",
+            PlanAction::Read => "<=",
+        }
+    }
+}
+
+impl std::fmt::Display for PlanAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            PlanAction::Create => "create",
+            PlanAction::Update => "update",
+            PlanAction::Delete => "delete",
+            PlanAction::Replace => "replace",
+            PlanAction::Read => "read",
+        };
+        write!(f, "{} {}", self.marker(), name)
+    }
+}
+
+/// Represents a Terraform `output` block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputBlock {
+    /// The name of the output
+    pub name: String,
+    /// Path to the file containing this block
+    pub file_path: PathBuf,
+    /// The 1-based line number where the block starts
+    pub line: usize,
+}
+
+/// A `required_version` constraint from a `terraform` block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionRequirement {
+    /// The constraint, e.g. `>= 1.6, < 2.0`
+    pub constraint: String,
+    /// Path to the file containing the `terraform` block
+    pub file_path: PathBuf,
+}
+
+/// An entry of `required_providers` in a `terraform` block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProviderRequirement {
+    /// The local name, e.g. `aws`
+    pub name: String,
+    /// The source address, e.g. `hashicorp/aws`
+    pub source: Option<String>,
+    /// The version constraint, e.g. `~> 5.0`
+    pub version: Option<String>,
+    /// Path to the file containing the `terraform` block
+    pub file_path: PathBuf,
+}
+
+/// A `backend` or `cloud` block from a `terraform` block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackendConfig {
+    /// The backend type, e.g. `s3`, or `cloud` for a `cloud` block
+    pub backend_type: String,
+    /// Path to the file containing the `terraform` block
+    pub file_path: PathBuf,
+}
+
+/// Represents a Terraform 1.5+ `import` block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportBlock {
+    /// The resource address being imported into (the `to` argument)
+    pub to: String,
+    /// The provider-specific import ID (the `id` argument)
+    pub id: String,
+    /// Path to the file containing this block
+    pub file_path: PathBuf,
+}
+
+impl ImportBlock {
+    /// Returns whether this import targets the given resource
+    pub fn targets(&self, resource: &Resource) -> bool {
+        let base = self.to.split('[').next().unwrap_or(&self.to);
+        base == resource.full_name()
+    }
+}
+
+/// A resource, data source or module call address split into its parts
+#[derive(Debug, PartialEq, Eq)]
+pub struct Address<'a> {
+    /// The enclosing module path, e.g. `module.vpc["eu"]`; `None` at the root
+    pub module_path: Option<&'a str>,
+    pub is_module: bool,
+    pub is_data: bool,
+    /// The block type; empty for module calls
+    pub resource_type: &'a str,
+    pub name: &'a str,
+    /// The instance key as written, e.g. `0` or `"blue"`
+    pub key: Option<&'a str>,
+}
+
+impl<'a> Address<'a> {
+    /// Parses an address such as `aws_instance.web[0]` or `module.vpc.data.aws_ami.ubuntu`
+    ///
+    /// Each segment must be a valid identifier. Only module names and the final
+    /// name may carry an instance key, which is a count index or a quoted string.
+    /// The final name may also carry an index range such as `[0-2]`.
+    pub fn parse(address: &'a str) -> Option<Address<'a>> {
+        // (offset, identifier, key) of each dot-separated segment
+        let mut segments = Vec::new();
+        let mut offset = 0;
+        loop {
+            let rest = &address[offset..];
+            let end = rest.find(['.', '[']).unwrap_or(rest.len());
+            let ident = &rest[..end];
+            if !is_valid_identifier(ident) {
+                return None;
+            }
+            let mut after = &rest[end..];
+            let mut key = None;
+            if let Some(bracketed) = after.strip_prefix('[') {
+                let len = instance_key_len(bracketed)?;
+                key = Some(&bracketed[..len]);
+                after = bracketed[len..].strip_prefix(']')?;
+            }
+            segments.push((offset, ident, key));
+            if after.is_empty() {
+                break;
+            }
+            after.strip_prefix('.')?;
+            offset = address.len() - after.len() + 1;
+        }
+
+        let is_range =
+            |key: &Option<&str>| key.is_some_and(|k| !k.starts_with('"') && k.contains('-'));
+        if segments[..segments.len() - 1]
+            .iter()
+            .any(|(_, _, key)| is_range(key))
+        {
+            return None;
+        }
+
+        let mut start = 0;
+        while segments.len() - start > 2 && segments[start].1 == "module" {
+            if segments[start].2.is_some() {
+                return None;
+            }
+            start += 2;
+        }
+        let module_path = (start > 0).then(|| &address[..segments[start].0 - 1]);
+        let (is_module, is_data, resource_type, (_, name, key)) = match &segments[start..] {
+            [(_, "module", None), name] => (true, false, "", *name),
+            [(_, "data", None), (_, resource_type, None), name] => {
+                (false, true, *resource_type, *name)
+            }
+            [(_, resource_type, None), name] if !matches!(*resource_type, "module" | "data") => {
+                (false, false, *resource_type, *name)
+            }
+            _ => return None,
+        };
+        Some(Address {
+            module_path,
+            is_module,
+            is_data,
+            resource_type,
+            name,
+            key,
+        })
+    }
+}
+
+/// Returns the length of the instance key at the start of `s`: digits, a
+/// range of digits or a quoted string
+fn instance_key_len(s: &str) -> Option<usize> {
+    if let Some(quoted) = s.strip_prefix('"') {
+        let mut escaped = false;
+        for (i, c) in quoted.char_indices() {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => return Some(i + 2),
+                _ => {}
+            }
+        }
+        return None;
+    }
+    let digits = |s: &str| s.bytes().take_while(u8::is_ascii_digit).count();
+    let start = digits(s);
+    if start == 0 {
+        return None;
+    }
+    // An index range such as `0-2`, expanded by `Resource::target_strings`
+    match s[start..].strip_prefix('-').map(digits) {
+        Some(0) => None,
+        Some(end) => Some(start + 1 + end),
+        None => Some(start),
+    }
 }
 
 /// Represents different types of targets for Terraform operations
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Target {
     File(PathBuf),
+    /// All resources in files under a directory, including its subdirectories
+    Directory(PathBuf),
     Module(String),
     Resource(String, String),
+    /// A data source, by type and name
+    Data(String, String),
+    /// All resources in files whose path matches a glob pattern
+    FileGlob(String),
+    /// All resources whose address starts with a prefix (e.g. `module.platform`)
+    Prefix(String),
+    /// One resource instance by its exact address, as listed in a plan
+    Address(String),
+    /// All resources whose block starts within an inclusive line range of a file
+    LineRange(PathBuf, usize, usize),
+    /// An output value; shown with `terraform output` rather than targeted
+    Output(String),
+}
+
+impl std::fmt::Display for Target {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Target::File(path) => write!(f, "file '{}'", path.display()),
+            Target::Directory(path) => write!(f, "directory '{}'", path.display()),
+            Target::Module(name) => write!(f, "module '{}'", name),
+            Target::Resource(resource_type, name) => {
+                write!(f, "resource '{}.{}'", resource_type, name)
+            }
+            Target::Data(data_type, name) => write!(f, "data source 'data.{}.{}'", data_type, name),
+            Target::FileGlob(pattern) => write!(f, "file glob '{}'", pattern),
+            Target::Prefix(prefix) => write!(f, "address prefix '{}'", prefix),
+            Target::Address(address) => write!(f, "address '{}'", address),
+            Target::LineRange(path, start, end) => {
+                write!(f, "lines {}-{} of '{}'", start, end, path.display())
+            }
+            Target::Output(name) => write!(f, "output '{}'", name),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_for_each_module_key_is_quoted() {
+        let module = Resource {
+            has_for_each: true,
+            index: Some("blue".to_string()),
+            ..Resource::for_test("", "app")
+        };
+        assert_eq!(module.target_string(), r#"module.app["blue"]"#);
+        assert_eq!(
+            module.target_strings().unwrap(),
+            vec![r#"module.app["blue"]"#]
+        );
+
+        let quoted = Resource {
+            index: Some(r#""blue""#.to_string()),
+            ..module.clone()
+        };
+        assert_eq!(quoted.target_string(), r#"module.app["blue"]"#);
+
+        let resource = Resource {
+            resource_type: "aws_instance".to_string(),
+            name: "web".to_string(),
+            is_module: false,
+            index: Some(r#"a"b"#.to_string()),
+            ..module
+        };
+        assert_eq!(resource.target_string(), r#"aws_instance.web["a\"b"]"#);
+    }
+
+    #[test]
+    fn test_split_instance_key_and_instance() {
+        assert_eq!(
+            split_instance_key(r#"aws_instance.web["blue"]"#),
+            Some(("aws_instance.web", r#""blue""#))
+        );
+        assert_eq!(
+            split_instance_key(r#"aws_instance.web["a[1]"]"#),
+            Some(("aws_instance.web", r#""a[1]""#))
+        );
+        assert_eq!(
+            split_instance_key("aws_instance.web[0]"),
+            Some(("aws_instance.web", "0"))
+        );
+        assert_eq!(split_instance_key("aws_instance.web"), None);
+        assert_eq!(split_instance_key("aws_instance.web[blue]"), None);
+
+        let block = Resource {
+            index: None,
+            ..count_resource("0")
+        };
+        assert_eq!(
+            block.instance("2").unwrap().target_string(),
+            "aws_instance.web[2]"
+        );
+        assert!(block.instance(r#""blue""#).is_none());
+        assert_eq!(block.instance("0-2").unwrap().index.as_deref(), Some("0-2"));
+        assert!(block.instance("0-").is_none());
+
+        let for_each = Resource {
+            has_count: false,
+            has_for_each: true,
+            ..block
+        };
+        let instance = for_each.instance(r#""a\"b""#).unwrap();
+        assert_eq!(instance.index.as_deref(), Some(r#"a"b"#));
+        assert_eq!(instance.target_string(), r#"aws_instance.web["a\"b"]"#);
+        assert!(for_each.instance("0").is_none());
+    }
+
+    #[test]
+    fn test_unicode_for_each_keys_are_kept_intact() {
+        let resource = |key: &str| Resource {
+            has_count: false,
+            has_for_each: true,
+            index: Some(key.to_string()),
+            ..count_resource("0")
+        };
+
+        assert_eq!(
+            resource("東京").target_string(),
+            r#"aws_instance.web["東京"]"#
+        );
+        assert_eq!(
+            resource("🚀 launch").target_strings().unwrap(),
+            vec![r#"aws_instance.web["🚀 launch"]"#]
+        );
+        assert_eq!(
+            resource(r#""大阪""#).target_string(),
+            r#"aws_instance.web["大阪"]"#
+        );
+    }
+
+    #[test]
+    fn test_short_name_drops_module_address() {
+        let resource = Resource {
+            module_address: Some("module.network.module.vpc".to_string()),
+            index: Some("1".to_string()),
+            ..count_resource("1")
+        };
+        assert_eq!(
+            resource.target_string(),
+            "module.network.module.vpc.aws_instance.web[1]"
+        );
+        assert_eq!(resource.short_name(), "aws_instance.web");
+    }
+
+    #[test]
+    fn test_provider_name() {
+        let resource = count_resource("0");
+        assert_eq!(resource.provider_name(), Some("aws"));
+
+        let aliased = Resource {
+            provider: Some("awscc.east".to_string()),
+            ..resource.clone()
+        };
+        assert_eq!(aliased.provider_name(), Some("awscc"));
+
+        let module = Resource {
+            is_module: true,
+            ..resource.clone()
+        };
+        assert_eq!(module.provider_name(), None);
+    }
+
+    fn count_resource(index: &str) -> Resource {
+        Resource {
+            has_count: true,
+            index: Some(index.to_string()),
+            ..Resource::for_test("aws_instance", "web")
+        }
+    }
+
+    #[test]
+    fn test_is_valid_address() {
+        let resource = |resource_type: &str, name: &str, is_module: bool| Resource {
+            resource_type: resource_type.to_string(),
+            name: name.to_string(),
+            is_module,
+            index: None,
+            has_count: false,
+            ..count_resource("0")
+        };
+        assert!(resource("aws_instance", "web", false).is_valid_address());
+        assert!(resource("aws_instance", "web-server_2", false).is_valid_address());
+        assert!(resource("", "vpc", true).is_valid_address());
+        assert!(!resource("", "vpc", false).is_valid_address());
+        assert!(!resource("aws_instance", "2web", false).is_valid_address());
+        assert!(!resource("aws instance", "web", false).is_valid_address());
+        assert!(!resource("aws_instance", "${var.name}", false).is_valid_address());
+        assert!(!resource("", "my.vpc", true).is_valid_address());
+    }
+
+    #[test]
+    fn test_parse_address() {
+        let address = Address::parse(r#"module.vpc["eu"].module.subnets.data.aws_ami.x[0]"#);
+        assert_eq!(
+            address,
+            Some(Address {
+                module_path: Some(r#"module.vpc["eu"].module.subnets"#),
+                is_module: false,
+                is_data: true,
+                resource_type: "aws_ami",
+                name: "x",
+                key: Some("0"),
+            })
+        );
+
+        let module = Address::parse(r#"module.network.module.vpc["a.b"]"#).unwrap();
+        assert_eq!(module.module_path, Some("module.network"));
+        assert!(module.is_module);
+        assert_eq!(module.name, "vpc");
+        assert_eq!(module.key, Some(r#""a.b""#));
+
+        assert_eq!(
+            Address::parse("module.vpc.aws_instance.web[0-2]").map(|a| a.key),
+            Some(Some("0-2"))
+        );
+
+        let resource = Address::parse("aws_instance.web").unwrap();
+        assert_eq!(resource.module_path, None);
+        assert_eq!(
+            (resource.resource_type, resource.name),
+            ("aws_instance", "web")
+        );
+
+        for invalid in [
+            "aws_vpc",
+            "aws_vpc.main.extra",
+            "module.vpc.extra",
+            "aws_instance[0].web",
+            "aws_instance.web[x]",
+            r#"aws_instance.web["open]"#,
+            "aws_instance.web[0",
+            "aws_instance.web[0-]",
+            "aws_instance.web[-2]",
+            "module.vpc[0-1].aws_subnet.a",
+            "aws_instance.web.",
+            "module[0].vpc",
+            "data.aws_ami",
+            "not an.address",
+        ] {
+            assert_eq!(Address::parse(invalid), None, "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_is_valid_identifier() {
+        assert!(is_valid_identifier("web"));
+        assert!(is_valid_identifier("web_01"));
+        assert!(is_valid_identifier("web-server-2"));
+        assert!(is_valid_identifier("_private"));
+        assert!(!is_valid_identifier("1web"));
+        assert!(!is_valid_identifier("web server"));
+        assert!(!is_valid_identifier("web'name"));
+        assert!(!is_valid_identifier(""));
+    }
+
+    #[test]
+    fn test_target_strings_expands_range() {
+        let targets = count_resource("0-2").target_strings().unwrap();
+        assert_eq!(
+            targets,
+            vec![
+                "aws_instance.web[0]",
+                "aws_instance.web[1]",
+                "aws_instance.web[2]"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_target_strings_single_index() {
+        let targets = count_resource("1").target_strings().unwrap();
+        assert_eq!(targets, vec!["aws_instance.web[1]"]);
+    }
+
+    #[test]
+    fn test_target_strings_rejects_reversed_range() {
+        assert!(count_resource("3-1").target_strings().is_err());
+    }
 }
@@ -0,0 +1,262 @@
+use std::cmp::Ordering;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::display::Display;
+use crate::error::{Result, TfocusError};
+use crate::types::VersionRequirement;
+
+/// A `major.minor.patch` version; missing parts are zero
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Version {
+    major: u64,
+    minor: u64,
+    patch: u64,
+}
+
+impl Version {
+    /// Parses `1.6.3`, `v1.6` or `1.7.0-beta1` (pre-release suffixes are ignored)
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim().trim_start_matches('v');
+        let core = text.split(['-', '+']).next().unwrap_or(text);
+        let mut parts = core.split('.').map(|part| part.parse::<u64>());
+        let major = parts.next()?.ok()?;
+        let minor = parts.next().transpose().ok()?.unwrap_or(0);
+        let patch = parts.next().transpose().ok()?.unwrap_or(0);
+        if parts.next().is_some() {
+            return None;
+        }
+        Some(Self {
+            major,
+            minor,
+            patch,
+        })
+    }
+}
+
+impl std::fmt::Display for Version {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Returns whether `version` satisfies a Terraform version constraint
+///
+/// Supports comma-separated `=`, `!=`, `>`, `>=`, `<`, `<=` and `~>` terms.
+/// `~> 1.6` allows any 1.x from 1.6 on; `~> 1.6.3` allows 1.6.x from 1.6.3 on.
+pub fn satisfies(version: Version, constraint: &str) -> std::result::Result<bool, String> {
+    for term in constraint.split(',') {
+        let term = term.trim();
+        let split = term
+            .find(|c: char| c.is_ascii_digit() || c == 'v')
+            .ok_or_else(|| format!("invalid constraint '{}'", term))?;
+        let (operator, wanted) = term.split_at(split);
+        let wanted_version =
+            Version::parse(wanted).ok_or_else(|| format!("invalid version '{}'", wanted))?;
+        let ordering = version.cmp(&wanted_version);
+
+        let ok = match operator.trim() {
+            "" | "=" => ordering == Ordering::Equal,
+            "!=" => ordering != Ordering::Equal,
+            ">" => ordering == Ordering::Greater,
+            ">=" => ordering != Ordering::Less,
+            "<" => ordering == Ordering::Less,
+            "<=" => ordering != Ordering::Greater,
+            "~>" => {
+                // The last given part may grow; the parts before it are fixed
+                let upper = if wanted.trim().split('.').count() >= 3 {
+                    Version {
+                        minor: wanted_version.minor + 1,
+                        patch: 0,
+                        ..wanted_version
+                    }
+                } else {
+                    Version {
+                        major: wanted_version.major + 1,
+                        minor: 0,
+                        patch: 0,
+                    }
+                };
+                ordering != Ordering::Less && version < upper
+            }
+            other => return Err(format!("unknown operator '{}'", other)),
+        };
+        if !ok {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Checks the installed version against every `required_version`
+///
+/// A mismatch prints a warning, or fails with `strict`. Constraints that cannot
+/// be parsed are reported and skipped.
+pub fn check(version: Version, requirements: &[VersionRequirement], strict: bool) -> Result<()> {
+    for requirement in requirements {
+        match satisfies(version, &requirement.constraint) {
+            Ok(true) => {}
+            Ok(false) => {
+                let error = TfocusError::VersionMismatch {
+                    version: version.to_string(),
+                    constraint: requirement.constraint.clone(),
+                    file: requirement.file_path.display().to_string(),
+                };
+                if strict {
+                    return Err(error);
+                }
+                Display::print_warning(&error.to_string());
+            }
+            Err(e) => Display::print_warning(&format!(
+                "cannot check required_version in {}: {}",
+                requirement.file_path.display(),
+                e
+            )),
+        }
+    }
+    Ok(())
+}
+
+/// File in which tfenv pins the terraform version of a directory
+pub const VERSION_FILE: &str = ".terraform-version";
+
+/// A version pinned in a `.terraform-version` file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PinnedVersion {
+    /// The pin as written, e.g. `1.6.3` or `latest:^1.6`
+    pub text: String,
+    pub file_path: PathBuf,
+}
+
+/// Reads the version pinned in `dir/.terraform-version`, if there is one
+///
+/// The first line that is neither blank nor a `#` comment is the pin.
+pub fn read_pinned(dir: &Path) -> Result<Option<PinnedVersion>> {
+    let file_path = dir.join(VERSION_FILE);
+    if !file_path.is_file() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(&file_path)?;
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|text| PinnedVersion {
+            text: text.to_string(),
+            file_path,
+        }))
+}
+
+/// Shows the pinned version and compares it with the installed one
+///
+/// A difference prints a warning, or fails with `strict`. Pins that are not an
+/// exact version (e.g. `latest` or `min-required`) are shown but not compared.
+/// With `quiet` nothing is printed; a strict mismatch still fails.
+pub fn check_pinned(
+    version: Version,
+    pinned: &PinnedVersion,
+    strict: bool,
+    quiet: bool,
+) -> Result<()> {
+    if !quiet {
+        Display::print_info(&format!(
+            "Terraform {} pinned in {}",
+            pinned.text,
+            pinned.file_path.display()
+        ));
+    }
+    match Version::parse(&pinned.text) {
+        Some(wanted) if wanted != version => {
+            let error = TfocusError::PinnedVersionMismatch {
+                version: version.to_string(),
+                pinned: pinned.text.clone(),
+                file: pinned.file_path.display().to_string(),
+            };
+            if strict {
+                return Err(error);
+            }
+            if !quiet {
+                Display::print_warning(&error.to_string());
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn v(text: &str) -> Version {
+        Version::parse(text).unwrap()
+    }
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(v("1.6.3").to_string(), "1.6.3");
+        assert_eq!(v("v1.6").to_string(), "1.6.0");
+        assert_eq!(v("1.7.0-beta1").to_string(), "1.7.0");
+        assert!(Version::parse("latest").is_none());
+        assert!(Version::parse("1.2.3.4").is_none());
+    }
+
+    #[test]
+    fn test_satisfies_constraints() {
+        assert!(satisfies(v("1.7.5"), ">= 1.6").unwrap());
+        assert!(!satisfies(v("1.5.7"), ">= 1.6").unwrap());
+        assert!(satisfies(v("1.6.0"), "1.6.0").unwrap());
+        assert!(satisfies(v("1.9.0"), ">= 1.5, < 2.0.0").unwrap());
+        assert!(!satisfies(v("1.9.0"), ">= 1.5, != 1.9.0").unwrap());
+        assert!(satisfies(v("1.9.1"), "~> 1.6").unwrap());
+        assert!(!satisfies(v("2.0.0"), "~> 1.6").unwrap());
+        assert!(satisfies(v("1.6.9"), "~> 1.6.3").unwrap());
+        assert!(!satisfies(v("1.7.0"), "~> 1.6.3").unwrap());
+        assert!(satisfies(v("1.6.0"), "=> 1.6").is_err());
+        assert!(satisfies(v("1.6.0"), ">= latest").is_err());
+    }
+
+    #[test]
+    fn test_read_and_check_pinned() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(read_pinned(dir.path()).unwrap(), None);
+
+        std::fs::write(
+            dir.path().join(VERSION_FILE),
+            "# pinned by tfenv\n\n1.6.3\n",
+        )
+        .unwrap();
+        let pinned = read_pinned(dir.path()).unwrap().unwrap();
+        assert_eq!(pinned.text, "1.6.3");
+
+        assert!(check_pinned(v("1.6.3"), &pinned, true, true).is_ok());
+        assert!(check_pinned(v("1.7.0"), &pinned, false, true).is_ok());
+        assert!(matches!(
+            check_pinned(v("1.7.0"), &pinned, true, true),
+            Err(TfocusError::PinnedVersionMismatch { .. })
+        ));
+
+        let latest = PinnedVersion {
+            text: "latest:^1.6".to_string(),
+            ..pinned
+        };
+        assert!(check_pinned(v("1.7.0"), &latest, true, true).is_ok());
+    }
+
+    #[test]
+    fn test_check_strict_mismatch() {
+        let requirements = vec![VersionRequirement {
+            constraint: ">= 1.6".to_string(),
+            file_path: PathBuf::from("versions.tf"),
+        }];
+        assert!(check(v("1.7.0"), &requirements, true).is_ok());
+        assert!(check(v("1.5.0"), &requirements, false).is_ok());
+
+        let err = check(v("1.5.0"), &requirements, true).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Terraform 1.5.0 does not satisfy required_version \">= 1.6\" in versions.tf"
+        );
+    }
+}